@@ -0,0 +1,71 @@
+#ifndef JRSONNET_PLUGIN_H
+#define JRSONNET_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/** \file Interface of native function plugins, loaded by `jrsonnet --native-plugin lib.so`.
+ *
+ * Plugin is a shared library, exporting `jrsonnet_plugin_abi_version` and `jrsonnet_plugin_init`.
+ * Functions registered in `jrsonnet_plugin_init` are available via `std.native("name")`.
+ */
+
+/** Plugin is only loaded if jrsonnet_plugin_abi_version() returns this value. */
+#define JRSONNET_PLUGIN_ABI_VERSION 1
+
+/** Kind of JrsonnetPluginValue. */
+#define JRSONNET_PLUGIN_NULL 0   /* Only allowed as return value */
+#define JRSONNET_PLUGIN_BOOL 1   /* Value is in `boolean` */
+#define JRSONNET_PLUGIN_NUMBER 2 /* Value is in `number` */
+#define JRSONNET_PLUGIN_STRING 3 /* Value is in `string`/`string_len` */
+#define JRSONNET_PLUGIN_JSON 4   /* Any value, serialized as JSON in `string`/`string_len` */
+
+/** Value passed between jrsonnet and plugin.
+ *
+ * Strings are UTF-8, and not NUL-terminated.
+ */
+typedef struct JrsonnetPluginValue {
+    uint32_t kind;
+    int boolean;
+    double number;
+    const char *string;
+    size_t string_len;
+} JrsonnetPluginValue;
+
+/** Parameter of registered function, arguments are converted to the parameter kind before call. */
+typedef struct JrsonnetPluginParam {
+    const char *name;
+    uint32_t kind;
+} JrsonnetPluginParam;
+
+/** Implementation of registered function.
+ *
+ * \param ctx User pointer, passed on registration.
+ * \param args Arguments, in the same order and of the same kinds as registered params.
+ * \param out Result of the call, initialized to JRSONNET_PLUGIN_NULL.
+ * \returns 0 on success, otherwise `out` may be set to JRSONNET_PLUGIN_STRING error message.
+ *
+ * Strings written to `out` should be valid until the next call into the plugin,
+ * jrsonnet copies them right after the call.
+ */
+typedef int (*JrsonnetPluginFunction)(void *ctx, const JrsonnetPluginValue *args, size_t args_len,
+                                      JrsonnetPluginValue *out);
+
+/** Registers native function, name and params are copied.
+ *
+ * \returns 0 on success, non-zero if registration is invalid (i.e unknown param kind).
+ */
+typedef int (*JrsonnetPluginRegister)(void *registrar, const char *name,
+                                      const JrsonnetPluginParam *params, size_t params_len,
+                                      JrsonnetPluginFunction function, void *ctx);
+
+/** Should return JRSONNET_PLUGIN_ABI_VERSION. */
+uint32_t jrsonnet_plugin_abi_version(void);
+
+/** Registers plugin functions by calling `register_fn(registrar, ...)`.
+ *
+ * \returns 0 on success.
+ */
+int jrsonnet_plugin_init(void *registrar, JrsonnetPluginRegister register_fn);
+
+#endif  // JRSONNET_PLUGIN_H
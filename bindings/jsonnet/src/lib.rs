@@ -73,6 +73,9 @@ pub extern "C" fn jsonnet_make() -> *mut State {
 		state.clone(),
 		PathResolver::new_cwd_fallback(),
 	));
+	// Same defaults as in libjsonnet
+	state.set_gc_min_objects(Some(1000));
+	state.set_gc_growth_trigger(2.0);
 	Box::into_raw(Box::new(state))
 }
 
@@ -90,16 +93,17 @@ pub extern "C" fn jsonnet_max_stack(vm: &State, v: c_uint) {
 }
 
 /// Set the number of objects required before a garbage collection cycle is allowed.
-///
-/// No-op for now
 #[no_mangle]
-pub extern "C" fn jsonnet_gc_min_objects(_vm: &State, _v: c_uint) {}
+pub extern "C" fn jsonnet_gc_min_objects(vm: &State, v: c_uint) {
+	vm.set_gc_min_objects(Some(v as usize));
+}
 
-/// Run the garbage collector after this amount of growth in the number of objects
-///
-/// No-op for now
+/// Run the garbage collector after this amount of growth in the number of objects.
+/// Values below 1.1, and NaN, are treated as 1.1
 #[no_mangle]
-pub extern "C" fn jsonnet_gc_growth_trigger(_vm: &State, _v: c_double) {}
+pub extern "C" fn jsonnet_gc_growth_trigger(vm: &State, v: c_double) {
+	vm.set_gc_growth_trigger(v);
+}
 
 /// Expect a string as output and don't JSON encode it.
 #[no_mangle]
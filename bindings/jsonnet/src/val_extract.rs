@@ -11,7 +11,9 @@ use jrsonnet_evaluator::{State, Val};
 #[no_mangle]
 pub extern "C" fn jsonnet_json_extract_string(_vm: &State, v: &Val) -> *mut c_char {
 	match v {
-		Val::Str(s) => CString::new(s as &str).unwrap().into_raw(),
+		Val::Str(s) => CString::new(&s.clone().into_flat() as &str)
+			.unwrap()
+			.into_raw(),
 		_ => std::ptr::null_mut(),
 	}
 }
@@ -24,6 +26,10 @@ pub extern "C" fn jsonnet_json_extract_number(_vm: &State, v: &Val, out: &mut c_
 			*out = *n;
 			1
 		}
+		Val::Int(n) => {
+			*out = n.to_f64();
+			1
+		}
 		_ => 0,
 	}
 }
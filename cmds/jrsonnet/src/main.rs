@@ -1,12 +1,16 @@
 use std::{
-	fs::{create_dir_all, File},
-	io::{Read, Write},
+	collections::BTreeSet,
+	fs::{self, create_dir_all, File},
+	io::{ErrorKind, Read, Write},
+	path::{Component, Path, PathBuf},
+	rc::Rc,
 };
 
 use clap::{AppSettings, IntoApp, Parser};
 use clap_complete::Shell;
 use jrsonnet_cli::{ConfigureState, GcOpts, GeneralOpts, ManifestOpts, OutputOpts};
-use jrsonnet_evaluator::{error::LocError, State};
+use jrsonnet_evaluator::{error::LocError, Breakpoint, State};
+use similar::TextDiff;
 
 #[cfg(feature = "mimalloc")]
 #[global_allocator]
@@ -24,10 +28,26 @@ enum SubOpts {
 #[derive(Parser)]
 #[clap(next_help_heading = "DEBUG")]
 struct DebugOpts {
-	/// Required OS stack size.
-	/// This shouldn't be changed unless jrsonnet is failing with stack overflow error.
-	#[clap(long, name = "size")]
+	/// Initial OS stack size in MiB.
+	/// Not needed anymore, as stack is grown on demand, kept for compatibility.
+	#[clap(long, name = "size", hide = true)]
 	pub os_stack: Option<usize>,
+	/// Print every value, produced by expression starting at `file:LINE[:COL]`, after evaluation.
+	/// If column is omitted, first expression on the line is used.
+	#[clap(long, name = "location", multiple_occurrences = true)]
+	pub breakpoint: Vec<String>,
+}
+
+/// Parses `file:LINE[:COL]` breakpoint location
+fn parse_breakpoint(spec: &str) -> Option<(&str, usize, Option<usize>)> {
+	let (rest, last) = spec.rsplit_once(':')?;
+	let last = last.parse().ok()?;
+	if let Some((file, line)) = rest.rsplit_once(':') {
+		if let Ok(line) = line.parse() {
+			return Some((file, line, Some(last)));
+		}
+	}
+	Some((rest, last, None))
 }
 
 #[derive(Parser)]
@@ -46,6 +66,12 @@ struct InputOpts {
 	global_setting = AppSettings::DeriveDisplayOrder,
 	args_conflicts_with_subcommands = true,
 )]
+#[cfg_attr(
+	not(all(feature = "native-plugin", feature = "native-exec")),
+	clap(
+		after_help = "Loading natives with --native-plugin and --native-exec is opt-in, build jrsonnet with `--features native-plugin,native-exec` to enable it"
+	)
+)]
 struct Opts {
 	#[clap(subcommand)]
 	sub: Option<SubOpts>,
@@ -105,6 +131,14 @@ enum Error {
 	Utf8(#[from] std::str::Utf8Error),
 	#[error("missing input argument")]
 	MissingInputArgument,
+	#[error("--check requires either --output-file or --multi")]
+	CheckWithoutOutput,
+	#[error("output file {0} is outside of --multi directory")]
+	OutputOutsideMulti(String),
+	#[error("{0} output file(s) are not up to date")]
+	OutdatedOutput(usize),
+	#[error("invalid breakpoint {0}, expected file:LINE[:COL]")]
+	InvalidBreakpoint(String),
 }
 impl From<LocError> for Error {
 	fn from(e: LocError) -> Self {
@@ -115,7 +149,10 @@ impl From<LocError> for Error {
 fn main_catch(opts: Opts) -> bool {
 	let _printer = opts.gc.stats_printer();
 	let s = State::default();
-	if let Err(e) = main_real(&s, opts) {
+	let mut breakpoints = Vec::new();
+	let result = main_real(&s, opts, &mut breakpoints);
+	print_breakpoints(&s, &breakpoints);
+	if let Err(e) = result {
 		if let Error::Evaluation(e) = e {
 			eprintln!("{}", s.stringify_err(&e));
 		} else {
@@ -126,11 +163,39 @@ fn main_catch(opts: Opts) -> bool {
 	true
 }
 
-fn main_real(s: &State, opts: Opts) -> Result<(), Error> {
+fn print_breakpoints(s: &State, breakpoints: &[(String, Rc<Breakpoint>)]) {
+	for (spec, breakpoint) in breakpoints {
+		let collected = breakpoint.take_collected();
+		eprintln!("breakpoint {spec}: {} value(s)", collected.len());
+		for (i, value) in collected.into_iter().enumerate() {
+			match value.and_then(|v| s.manifest(v)) {
+				Ok(v) => eprintln!("#{i}: {v}"),
+				Err(e) => eprintln!("#{i}: {}", s.stringify_err(&e)),
+			}
+		}
+	}
+}
+
+fn main_real(
+	s: &State,
+	opts: Opts,
+	breakpoints: &mut Vec<(String, Rc<Breakpoint>)>,
+) -> Result<(), Error> {
 	opts.general.configure(s)?;
 	opts.manifest.configure(s)?;
+	opts.gc.configure(s)?;
+	for spec in opts.debug.breakpoint {
+		let Some((file, line, column)) = parse_breakpoint(&spec) else {
+			return Err(Error::InvalidBreakpoint(spec));
+		};
+		let breakpoint = s.add_breakpoint(s.resolve(file)?, line, column)?;
+		breakpoints.push((spec, breakpoint));
+	}
 
 	let input = opts.input.input.ok_or(Error::MissingInputArgument)?;
+	if opts.output.check && opts.output.multi.is_none() && opts.output.output_file.is_none() {
+		return Err(Error::CheckWithoutOutput);
+	}
 	let val = if opts.input.exec {
 		s.evaluate_snippet("<cmdline>".to_owned(), &input as &str)?
 	} else if input == "-" {
@@ -143,16 +208,40 @@ fn main_real(s: &State, opts: Opts) -> Result<(), Error> {
 	};
 
 	let val = s.with_tla(val)?;
+	let val = if let Some(path) = &opts.output.select {
+		val.get_path(s.clone(), path)?
+	} else {
+		val
+	};
 
+	#[cfg(feature = "json-schema")]
+	if let Some(schema) = &opts.output.schema {
+		let schema = jrsonnet_evaluator::JsonSchema::parse(&fs::read_to_string(schema)?)?;
+		s.validate_schema(val.clone(), &schema)?;
+	}
+
+	let mut outdated = 0;
 	if let Some(multi) = opts.output.multi {
-		if opts.output.create_output_dirs {
+		if opts.output.create_output_dirs && !opts.output.check {
 			let mut dir = multi.clone();
 			dir.pop();
 			create_dir_all(dir)?;
 		}
+		let mut produced = BTreeSet::new();
 		for (file, data) in s.manifest_multi(val)?.iter() {
 			let mut path = multi.clone();
 			path.push(file as &str);
+			if opts.output.prune {
+				let relative = normalize_output_name(file)
+					.ok_or_else(|| Error::OutputOutsideMulti(file.to_string()))?;
+				produced.insert(relative);
+			}
+			if opts.output.check {
+				if !check_output(&path, Some(data), &mut std::io::stdout())? {
+					outdated += 1;
+				}
+				continue;
+			}
 			if opts.output.create_output_dirs {
 				let mut dir = path.clone();
 				dir.pop();
@@ -162,14 +251,43 @@ fn main_real(s: &State, opts: Opts) -> Result<(), Error> {
 			let mut file = File::create(path)?;
 			writeln!(file, "{}", data)?;
 		}
+		if opts.output.prune {
+			let previous = read_outputs_list(&multi)?;
+			let mut kept = produced;
+			for relative in collect_stale_files(&multi, &previous, &kept) {
+				let path = multi.join(&relative);
+				if opts.output.check {
+					if !check_output(&path, None, &mut std::io::stdout())? {
+						outdated += 1;
+					}
+				} else if opts.output.prune_dry_run {
+					eprintln!("would remove {}", path.display());
+					kept.insert(relative);
+				} else {
+					eprintln!("removing {}", path.display());
+					fs::remove_file(path)?;
+					remove_empty_parents(&multi, &relative);
+				}
+			}
+			if !opts.output.check {
+				write_outputs_list(&multi, &kept)?;
+			}
+		}
 	} else if let Some(path) = opts.output.output_file {
-		if opts.output.create_output_dirs {
-			let mut dir = path.clone();
-			dir.pop();
-			create_dir_all(dir)?;
+		let data = s.manifest(val)?;
+		if opts.output.check {
+			if !check_output(&path, Some(&data), &mut std::io::stdout())? {
+				outdated += 1;
+			}
+		} else {
+			if opts.output.create_output_dirs {
+				let mut dir = path.clone();
+				dir.pop();
+				create_dir_all(dir)?;
+			}
+			let mut file = File::create(path)?;
+			writeln!(file, "{}", data)?;
 		}
-		let mut file = File::create(path)?;
-		writeln!(file, "{}", s.manifest(val)?)?;
 	} else {
 		let output = s.manifest(val)?;
 		if !output.is_empty() {
@@ -177,5 +295,367 @@ fn main_real(s: &State, opts: Opts) -> Result<(), Error> {
 		}
 	}
 
+	if outdated != 0 {
+		return Err(Error::OutdatedOutput(outdated));
+	}
+	Ok(())
+}
+
+/// Compares file on disk with the data which would be written to it,
+/// writing unified diff to `out` in case of mismatch.
+/// `None` data means file shouldn't exist
+fn check_output(path: &Path, data: Option<&str>, out: &mut impl Write) -> Result<bool, Error> {
+	let expected = data.map(|data| format!("{}\n", data));
+	let actual = match fs::read_to_string(path) {
+		Ok(v) => Some(v),
+		Err(e) if e.kind() == ErrorKind::NotFound => None,
+		Err(e) => return Err(e.into()),
+	};
+	if actual == expected {
+		return Ok(true);
+	}
+	let name = path.display().to_string();
+	let diff = TextDiff::from_lines(
+		actual.as_deref().unwrap_or(""),
+		expected.as_deref().unwrap_or(""),
+	);
+	write!(
+		out,
+		"{}",
+		diff.unified_diff().header(
+			if actual.is_some() { &name } else { "/dev/null" },
+			if expected.is_some() {
+				&name
+			} else {
+				"/dev/null"
+			},
+		)
+	)?;
+	Ok(false)
+}
+
+/// Resolves `.` and `..` in `--multi` output file name, returning path relative to output directory,
+/// or `None` if it escapes the directory
+fn normalize_output_name(name: &str) -> Option<PathBuf> {
+	let mut out = PathBuf::new();
+	for component in Path::new(name).components() {
+		match component {
+			Component::Normal(c) => out.push(c),
+			Component::CurDir => {}
+			Component::ParentDir => {
+				if !out.pop() {
+					return None;
+				}
+			}
+			Component::RootDir | Component::Prefix(_) => return None,
+		}
+	}
+	Some(out)
+}
+
+/// File in `--multi` directory, listing outputs of the last `--prune` run, one per line
+const OUTPUTS_LIST: &str = ".jrsonnet-outputs";
+
+/// Reads outputs, listed by the previous `--prune` run, entries escaping the directory are ignored
+fn read_outputs_list(dir: &Path) -> Result<BTreeSet<PathBuf>, Error> {
+	let list = match fs::read_to_string(dir.join(OUTPUTS_LIST)) {
+		Ok(v) => v,
+		Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeSet::new()),
+		Err(e) => return Err(e.into()),
+	};
+	Ok(list
+		.lines()
+		.filter_map(normalize_output_name)
+		.filter(|p| !p.as_os_str().is_empty())
+		.collect())
+}
+
+/// Records outputs of this run, so the next `--prune` run only removes files written by jrsonnet
+fn write_outputs_list(dir: &Path, outputs: &BTreeSet<PathBuf>) -> Result<(), Error> {
+	let path = dir.join(OUTPUTS_LIST);
+	if outputs.is_empty() {
+		return match fs::remove_file(path) {
+			Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+			_ => Ok(()),
+		};
+	}
+	let mut file = File::create(path)?;
+	for output in outputs {
+		writeln!(file, "{}", output.display())?;
+	}
 	Ok(())
 }
+
+/// Outputs of the previous run, which weren't produced by this one, and still exist as files in `root`.
+/// Both `previous` and `produced` paths are normalized and relative to `root`
+fn collect_stale_files(
+	root: &Path,
+	previous: &BTreeSet<PathBuf>,
+	produced: &BTreeSet<PathBuf>,
+) -> Vec<PathBuf> {
+	previous
+		.difference(produced)
+		.filter(|relative| {
+			fs::symlink_metadata(root.join(relative)).is_ok_and(|meta| !meta.is_dir())
+		})
+		.cloned()
+		.collect()
+}
+
+/// Removes directories, left empty after removal of `relative` file, stopping at `root`
+fn remove_empty_parents(root: &Path, relative: &Path) {
+	for parent in relative.ancestors().skip(1) {
+		if parent.as_os_str().is_empty() || fs::remove_dir(root.join(parent)).is_err() {
+			break;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		collections::BTreeSet,
+		fs,
+		path::{Path, PathBuf},
+	};
+
+	use clap::Parser;
+
+	use super::{
+		check_output, collect_stale_files, main_catch, normalize_output_name, read_outputs_list,
+		Opts, OUTPUTS_LIST,
+	};
+
+	/// Fresh directory in system temp dir, removed on drop
+	struct TempDir(PathBuf);
+	impl TempDir {
+		fn new(name: &str) -> Self {
+			let path =
+				std::env::temp_dir().join(format!("jrsonnet-test-{name}-{}", std::process::id()));
+			let _ = fs::remove_dir_all(&path);
+			fs::create_dir_all(&path).expect("temp dir created");
+			Self(path)
+		}
+		fn file(&self, name: &str) {
+			self.file_with(name, "");
+		}
+		fn file_with(&self, name: &str, data: &str) {
+			let path = self.0.join(name);
+			fs::create_dir_all(path.parent().expect("has parent")).expect("dir created");
+			fs::write(path, data).expect("file written");
+		}
+	}
+	impl Drop for TempDir {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.0);
+		}
+	}
+
+	fn paths(names: &[&str]) -> BTreeSet<PathBuf> {
+		names.iter().map(PathBuf::from).collect()
+	}
+
+	#[test]
+	fn normalizes_output_names() {
+		assert_eq!(normalize_output_name("a.json"), Some("a.json".into()));
+		assert_eq!(
+			normalize_output_name("./a/./b.json"),
+			Some("a/b.json".into())
+		);
+		assert_eq!(normalize_output_name("a/../b.json"), Some("b.json".into()));
+		assert_eq!(normalize_output_name("a//b.json"), Some("a/b.json".into()));
+		assert_eq!(normalize_output_name("../b.json"), None);
+		assert_eq!(normalize_output_name("a/../../b.json"), None);
+		assert_eq!(normalize_output_name("/etc/passwd"), None);
+	}
+
+	#[test]
+	fn collects_previous_outputs_only() {
+		let dir = TempDir::new("stale");
+		dir.file("a.json");
+		dir.file("b.json");
+		dir.file("sub/c.json");
+		dir.file("README.md");
+		dir.file(".git/HEAD");
+
+		assert_eq!(
+			collect_stale_files(
+				&dir.0,
+				&paths(&["a.json", "b.json", "sub/c.json", "sub", "missing.json"]),
+				&paths(&["a.json"]),
+			),
+			["b.json", "sub/c.json"].map(PathBuf::from)
+		);
+		assert!(collect_stale_files(&dir.0, &BTreeSet::new(), &paths(&[])).is_empty());
+	}
+
+	#[test]
+	fn outputs_list_ignores_escaping_entries() {
+		let dir = TempDir::new("outputs-list");
+		assert!(read_outputs_list(&dir.0).expect("read").is_empty());
+		dir.file_with(
+			OUTPUTS_LIST,
+			"../outside.json\n./a.json\n\n/etc/passwd\nsub/../b.json\n",
+		);
+		assert_eq!(
+			read_outputs_list(&dir.0).expect("read"),
+			paths(&["a.json", "b.json"])
+		);
+	}
+
+	fn check(path: &Path, data: Option<&str>) -> (bool, String) {
+		let mut out = Vec::new();
+		let up_to_date = check_output(path, data, &mut out).expect("checked");
+		(up_to_date, String::from_utf8(out).expect("diff is utf8"))
+	}
+
+	#[test]
+	fn check_prints_diff() {
+		let dir = TempDir::new("check-diff");
+		dir.file_with("a.json", "{\n  \"a\": 1\n}\n");
+		let path = dir.0.join("a.json");
+		let name = path.display();
+
+		assert_eq!(
+			check(&path, Some("{\n  \"a\": 1\n}")),
+			(true, String::new())
+		);
+		assert_eq!(
+			check(&path, Some("{\n  \"a\": 2\n}")),
+			(
+				false,
+				format!(
+					"--- {name}\n+++ {name}\n@@ -1,3 +1,3 @@\n {{\n-  \"a\": 1\n+  \"a\": 2\n }}\n"
+				)
+			)
+		);
+		// File is checked, but never written
+		assert_eq!(fs::read_to_string(&path).unwrap(), "{\n  \"a\": 1\n}\n");
+	}
+
+	#[test]
+	fn check_diffs_missing_files_against_dev_null() {
+		let dir = TempDir::new("check-missing");
+		dir.file_with("stale.json", "1\n");
+		let missing = dir.0.join("missing.json");
+		let stale = dir.0.join("stale.json");
+
+		assert_eq!(
+			check(&missing, Some("1")),
+			(
+				false,
+				format!(
+					"--- /dev/null\n+++ {}\n@@ -0,0 +1 @@\n+1\n",
+					missing.display()
+				)
+			)
+		);
+		assert_eq!(
+			check(&stale, None),
+			(
+				false,
+				format!(
+					"--- {}\n+++ /dev/null\n@@ -1 +0,0 @@\n-1\n",
+					stale.display()
+				)
+			)
+		);
+		assert_eq!(check(&missing, None), (true, String::new()));
+	}
+
+	fn run(args: &[&str]) -> bool {
+		let opts = Opts::try_parse_from(["jrsonnet"].iter().chain(args)).expect("valid args");
+		main_catch(opts)
+	}
+
+	#[test]
+	fn check_fails_on_outdated_output() {
+		let dir = TempDir::new("check-exit");
+		let output = dir.0.join("out.json");
+		let output = output.to_str().unwrap();
+
+		assert!(!run(&["--check", "-o", output, "-e", "1"]));
+		assert!(!Path::new(output).exists());
+		assert!(run(&["-o", output, "-e", "1"]));
+		assert!(run(&["--check", "-o", output, "-e", "1"]));
+		assert!(!run(&["--check", "-o", output, "-e", "2"]));
+		assert_eq!(fs::read_to_string(output).unwrap(), "1\n");
+	}
+
+	#[test]
+	fn prune_keeps_files_not_written_by_jrsonnet() {
+		let dir = TempDir::new("prune");
+		let multi = format!("{}/", dir.0.display());
+		dir.file(".git/HEAD");
+		dir.file(".hidden");
+		dir.file("README.md");
+		dir.file("sub/kustomization.yaml");
+
+		assert!(run(&[
+			"--prune",
+			"-c",
+			"-m",
+			&multi,
+			"-e",
+			"{'a.json': 1, 'sub/b.json': 2, 'deep/er/c.json': 3}"
+		]));
+		assert!(run(&["--prune", "-m", &multi, "-e", "{'a.json': 1}"]));
+
+		for kept in [
+			".git/HEAD",
+			".hidden",
+			"README.md",
+			"sub/kustomization.yaml",
+			"a.json",
+		] {
+			assert!(dir.0.join(kept).exists(), "{kept} is kept");
+		}
+		assert!(!dir.0.join("sub/b.json").exists());
+		// Directories left empty are removed too
+		assert!(!dir.0.join("deep").exists());
+		assert_eq!(
+			fs::read_to_string(dir.0.join(OUTPUTS_LIST)).unwrap(),
+			"a.json\n"
+		);
+
+		// Dry run keeps stale files listed, so they are removed by the next run
+		assert!(run(&[
+			"--prune",
+			"--prune-dry-run",
+			"-m",
+			&multi,
+			"-e",
+			"{}"
+		]));
+		assert!(dir.0.join("a.json").exists());
+		assert!(run(&["--prune", "-m", &multi, "-e", "{}"]));
+		assert!(!dir.0.join("a.json").exists());
+		assert!(!dir.0.join(OUTPUTS_LIST).exists());
+		assert!(dir.0.join(".git/HEAD").exists());
+	}
+
+	#[test]
+	fn check_with_prune_reports_stale_files() {
+		let dir = TempDir::new("check-prune");
+		let multi = format!("{}/", dir.0.display());
+		let code = "{'a.json': 1, 'sub/b.json': 2}";
+
+		assert!(run(&["--prune", "-c", "-m", &multi, "-e", code]));
+		assert!(run(&["--check", "--prune", "-m", &multi, "-e", code]));
+
+		// Files not written by jrsonnet are never stale
+		dir.file("unrelated.json");
+		assert!(run(&["--check", "--prune", "-m", &multi, "-e", code]));
+
+		let code = "{'a.json': 1}";
+		assert!(run(&["--check", "-m", &multi, "-e", code]));
+		assert!(!run(&["--check", "--prune", "-m", &multi, "-e", code]));
+		// Nothing is removed in check mode
+		assert!(dir.0.join("sub/b.json").exists());
+
+		assert!(run(&["--prune", "-m", &multi, "-e", code]));
+		assert!(!dir.0.join("sub/b.json").exists());
+		assert!(dir.0.join("unrelated.json").exists());
+		assert!(run(&["--check", "--prune", "-m", &multi, "-e", code]));
+	}
+}
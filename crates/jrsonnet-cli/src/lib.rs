@@ -1,4 +1,8 @@
 mod manifest;
+#[cfg(feature = "native-exec")]
+pub mod native_exec;
+#[cfg(feature = "native-plugin")]
+pub mod plugin;
 mod stdlib;
 mod tla;
 mod trace;
@@ -42,6 +46,11 @@ pub struct MiscOpts {
 	/// which should contain a colon-separated (semicolon-separated on Windows) list of directories.
 	#[clap(long, short = 'J', multiple_occurrences = true)]
 	jpath: Vec<PathBuf>,
+
+	/// Simplify code before evaluation: fold constant expressions,
+	/// inline locals used only once, and pre-evaluate `std` calls with constant arguments.
+	#[clap(long)]
+	optimize: bool,
 }
 impl ConfigureState for MiscOpts {
 	fn configure(&self, s: &State) -> Result<()> {
@@ -54,6 +63,7 @@ impl ConfigureState for MiscOpts {
 		s.set_import_resolver(Box::new(FileImportResolver::new(library_paths)));
 
 		s.set_max_stack(self.max_stack);
+		s.set_optimize(self.optimize);
 		Ok(())
 	}
 }
@@ -99,6 +109,20 @@ pub struct GcOpts {
 	/// Does nothing useless --gc-print-stats is specified
 	#[clap(long)]
 	gc_collect_before_printing_stats: bool,
+	/// Collect reference cycles during evaluation, once this number of objects is allocated.
+	/// By default garbage is only collected on exit.
+	#[clap(long)]
+	gc_min_objects: Option<usize>,
+	/// After collection, run the next one once the number of objects grows by this factor.
+	#[clap(long, default_value = "2.0")]
+	gc_growth_trigger: f64,
+}
+impl ConfigureState for GcOpts {
+	fn configure(&self, s: &State) -> Result<()> {
+		s.set_gc_min_objects(self.gc_min_objects);
+		s.set_gc_growth_trigger(self.gc_growth_trigger);
+		Ok(())
+	}
 }
 impl GcOpts {
 	pub fn stats_printer(&self) -> (Option<GcStatsPrinter>, Option<LeakSpace>) {
@@ -1,7 +1,7 @@
 use std::{path::PathBuf, str::FromStr};
 
 use clap::Parser;
-use jrsonnet_evaluator::{error::Result, ManifestFormat, State};
+use jrsonnet_evaluator::{error::Result, ManifestFormat, State, ValuePath};
 
 use crate::ConfigureState;
 
@@ -78,6 +78,10 @@ impl ConfigureState for ManifestOpts {
 
 #[derive(Parser)]
 pub struct OutputOpts {
+	/// Only output value at the specified path, i.e `a.b[0].c` or `["dotted.field"]`.
+	/// Unlike post-processing output, other fields aren't evaluated at all
+	#[clap(long)]
+	pub select: Option<ValuePath>,
 	/// Write to the output file rather than stdout
 	#[clap(long, short = 'o')]
 	pub output_file: Option<PathBuf>,
@@ -87,4 +91,22 @@ pub struct OutputOpts {
 	/// Write multiple files to the directory, list files on stdout
 	#[clap(long, short = 'm')]
 	pub multi: Option<PathBuf>,
+	/// Do not write anything, instead compare output with files already present on disk,
+	/// print unified diff for every mismatch, and fail if any file is not up to date.
+	/// Can only be used with `--output-file` or `--multi`
+	#[clap(long)]
+	pub check: bool,
+	/// Remove files, written to `--multi` directory by the previous `--prune` run, which weren't produced by this evaluation.
+	/// Written files are listed in `.jrsonnet-outputs` file in the directory, other files are never removed.
+	/// Removed files are listed on stderr, output file names escaping the directory are rejected
+	#[clap(long, requires = "multi")]
+	pub prune: bool,
+	/// List files which would be removed by `--prune`, without removing them
+	#[clap(long, requires = "prune")]
+	pub prune_dry_run: bool,
+	/// Validate output against the JSON Schema (draft 2020-12) file before writing it.
+	/// Only local schemas are supported, external `$ref`s are not resolved
+	#[cfg(feature = "json-schema")]
+	#[clap(long)]
+	pub schema: Option<PathBuf>,
 }
@@ -0,0 +1,266 @@
+//! Native functions implemented by external processes
+//!
+//! Process is started on the first call, and receives one JSON-RPC 2.0 request per line on stdin:
+//! `{"jsonrpc":"2.0","id":1,"method":"name","params":[...]}`, where `params` is an array for positional arguments,
+//! or an object for named arguments.
+//! For every request it should write one line with response to stdout:
+//! `{"jsonrpc":"2.0","id":1,"result":...}` or `{"jsonrpc":"2.0","id":1,"error":{"code":1,"message":"..."}}`
+//!
+//! If response isn't received in time, process is killed, and restarted on the next call.
+//! On shutdown stdin is closed, and process is given a second to exit, after which it is killed.
+//! On unix process is started in its own process group, and the whole group is killed.
+
+use std::{
+	cell::{Cell, RefCell},
+	io::{BufRead, BufReader, Write},
+	process::{Child, ChildStdin, Command, Stdio},
+	sync::mpsc::{self, Receiver, RecvTimeoutError},
+	thread,
+	time::{Duration, Instant},
+};
+
+use jrsonnet_evaluator::{
+	error::{Error::RuntimeError, Result},
+	function::{
+		builtin::{Builtin, BuiltinParam},
+		ArgsLike, CallLocation,
+	},
+	throw, throw_runtime,
+	typed::Typed,
+	Context, State, Val,
+};
+use jrsonnet_gcmodule::Trace;
+use serde_json::{Map, Value};
+
+/// Time given to process to exit after its stdin is closed
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Process {
+	child: Child,
+	stdin: ChildStdin,
+	/// Lines of stdout, read by separate thread, so reads can time out
+	lines: Receiver<std::io::Result<String>>,
+}
+impl Process {
+	/// Closes stdin, and waits for process to exit, killing it on timeout
+	fn shutdown(self) {
+		let Self {
+			mut child, stdin, ..
+		} = self;
+		// Closed stdin is the signal to exit
+		drop(stdin);
+		let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+		while Instant::now() < deadline {
+			match child.try_wait() {
+				Ok(Some(_)) | Err(_) => return,
+				Ok(None) => thread::sleep(Duration::from_millis(10)),
+			}
+		}
+		kill(&mut child);
+	}
+}
+
+fn kill(child: &mut Child) {
+	#[cfg(unix)]
+	{
+		// Process group id is equal to process id, see `ExecNative::spawn`
+		let pgid = libc::pid_t::try_from(child.id()).expect("pid fits");
+		// SAFETY: plain syscall, group can't be reused, as its leader isn't reaped yet
+		unsafe { libc::kill(-pgid, libc::SIGKILL) };
+	}
+	let _ = child.kill();
+	let _ = child.wait();
+}
+
+/// Native function, calls to which are forwarded to the long-lived child process
+#[derive(Trace)]
+#[trace(skip)]
+pub struct ExecNative {
+	name: String,
+	command: String,
+	timeout: Duration,
+	process: RefCell<Option<Process>>,
+	next_id: Cell<u64>,
+}
+impl ExecNative {
+	/// Default time to wait for the response
+	pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+	/// `command` is executed by system shell
+	pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			command: command.into(),
+			timeout: Self::DEFAULT_TIMEOUT,
+			process: RefCell::new(None),
+			next_id: Cell::new(1),
+		}
+	}
+
+	/// Time to wait for the response, after which process is killed
+	#[must_use]
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	fn spawn(&self) -> std::io::Result<Process> {
+		#[cfg(unix)]
+		let mut command = {
+			use std::os::unix::process::CommandExt;
+			let mut command = Command::new("sh");
+			command.arg("-c").arg(&self.command);
+			// Own process group, so processes spawned by the command are killed with it
+			command.process_group(0);
+			command
+		};
+		#[cfg(windows)]
+		let mut command = {
+			let mut command = Command::new("cmd");
+			command.arg("/C").arg(&self.command);
+			command
+		};
+		let mut child = command
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::inherit())
+			.spawn()?;
+		let stdin = child.stdin.take().expect("stdin is piped");
+		let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+		let (sender, lines) = mpsc::channel();
+		// Finishes once stdout is closed, or nobody listens for the lines
+		thread::spawn(move || loop {
+			let mut line = String::new();
+			let line = match stdout.read_line(&mut line) {
+				Ok(0) => Err(std::io::Error::new(
+					std::io::ErrorKind::UnexpectedEof,
+					"process exited",
+				)),
+				Ok(_) => Ok(line),
+				Err(e) => Err(e),
+			};
+			let finished = line.is_err();
+			if sender.send(line).is_err() || finished {
+				break;
+			}
+		});
+		Ok(Process {
+			child,
+			stdin,
+			lines,
+		})
+	}
+
+	fn request(&self, id: u64, params: &Value) -> std::io::Result<String> {
+		let mut process = self.process.borrow_mut();
+		if process.is_none() {
+			*process = Some(self.spawn()?);
+		}
+		let Process { stdin, lines, .. } = process.as_mut().expect("spawned");
+		let result = (|| {
+			let method = Value::String(self.name.clone());
+			writeln!(
+				stdin,
+				r#"{{"jsonrpc":"2.0","id":{id},"method":{method},"params":{params}}}"#
+			)?;
+			stdin.flush()?;
+			match lines.recv_timeout(self.timeout) {
+				Ok(line) => line,
+				Err(RecvTimeoutError::Timeout) => Err(std::io::Error::new(
+					std::io::ErrorKind::TimedOut,
+					format!("no response in {:?}", self.timeout),
+				)),
+				Err(RecvTimeoutError::Disconnected) => Err(std::io::Error::new(
+					std::io::ErrorKind::UnexpectedEof,
+					"process exited",
+				)),
+			}
+		})();
+		if result.is_err() {
+			// Process is in unknown state, restart it on the next call
+			if let Some(mut process) = process.take() {
+				kill(&mut process.child);
+			}
+		}
+		result
+	}
+}
+impl Drop for ExecNative {
+	fn drop(&mut self) {
+		if let Some(process) = self.process.take() {
+			process.shutdown();
+		}
+	}
+}
+
+impl Builtin for ExecNative {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn params(&self) -> &[BuiltinParam] {
+		// Any arguments are forwarded to the process
+		&[]
+	}
+
+	fn call(
+		&self,
+		s: State,
+		ctx: Context,
+		_loc: CallLocation<'_>,
+		args: &dyn ArgsLike,
+	) -> Result<Val> {
+		let mut named = false;
+		args.named_names(&mut |_| named = true);
+		let params = if named {
+			if args.unnamed_len() != 0 {
+				throw_runtime!(
+					"{}: can't mix positional and named arguments in native call",
+					self.name
+				)
+			}
+			let mut params = Map::new();
+			args.named_iter(s.clone(), ctx, true, &mut |name, arg| {
+				params.insert(
+					name.to_string(),
+					Value::from_untyped(arg.evaluate(s.clone())?, s.clone())?,
+				);
+				Ok(())
+			})?;
+			Value::Object(params)
+		} else {
+			let mut params = Vec::with_capacity(args.unnamed_len());
+			args.unnamed_iter(s.clone(), ctx, true, &mut |_, arg| {
+				params.push(Value::from_untyped(arg.evaluate(s.clone())?, s.clone())?);
+				Ok(())
+			})?;
+			Value::Array(params)
+		};
+
+		let id = self.next_id.get();
+		self.next_id.set(id + 1);
+		let fail = |e: String| RuntimeError(format!("{}: {e}", self.name).into());
+		let line = self.request(id, &params).map_err(|e| {
+			fail(format!(
+				"failed to communicate with `{}`: {e}",
+				self.command
+			))
+		})?;
+		let mut response: Map<String, Value> =
+			serde_json::from_str(&line).map_err(|e| fail(format!("invalid response: {e}")))?;
+		if response.get("id") != Some(&Value::from(id)) {
+			throw!(fail(format!("response id mismatch, expected {id}")))
+		}
+		if let Some(error) = response.remove("error") {
+			let message = match error.get("message") {
+				Some(Value::String(message)) => message.clone(),
+				_ => error.to_string(),
+			};
+			throw!(fail(message))
+		}
+		let result = response
+			.remove("result")
+			.ok_or_else(|| fail("response has neither result nor error".to_owned()))?;
+		Value::into_untyped(result, s)
+	}
+}
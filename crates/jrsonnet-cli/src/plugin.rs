@@ -0,0 +1,350 @@
+//! Native functions loaded from shared-library plugins
+//!
+//! Plugin side of the ABI is described in `bindings/c/jrsonnet_plugin.h`
+
+use std::{
+	ffi::{c_char, c_int, c_void, CStr},
+	path::Path,
+	rc::Rc,
+	slice, str,
+};
+
+use jrsonnet_evaluator::{
+	error::{Error::RuntimeError, Result},
+	function::{
+		builtin::{Builtin, BuiltinParam},
+		parse::parse_builtin_call,
+		ArgsLike, CallLocation,
+	},
+	tb, throw, throw_runtime,
+	typed::Typed,
+	Context, IStr, State, Val,
+};
+use jrsonnet_gcmodule::{Cc, Trace};
+use jrsonnet_stdlib::ContextInitializer;
+use libloading::Library;
+
+/// Version of plugin ABI, plugin is only loaded if its `jrsonnet_plugin_abi_version` returns the same value
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Type of [`PluginValue`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct PluginKind(pub u32);
+impl PluginKind {
+	/// Only allowed as return value
+	pub const NULL: Self = Self(0);
+	pub const BOOL: Self = Self(1);
+	pub const NUMBER: Self = Self(2);
+	pub const STRING: Self = Self(3);
+	/// Any value, passed as serialized JSON
+	pub const JSON: Self = Self(4);
+
+	const fn is_param(self) -> bool {
+		matches!(self, Self::BOOL | Self::NUMBER | Self::STRING | Self::JSON)
+	}
+}
+
+/// Value passed between jrsonnet and plugin
+///
+/// Strings are UTF-8, and not NUL-terminated
+#[repr(C)]
+pub struct PluginValue {
+	pub kind: PluginKind,
+	pub boolean: c_int,
+	pub number: f64,
+	pub string: *const c_char,
+	pub string_len: usize,
+}
+impl PluginValue {
+	const fn new(kind: PluginKind) -> Self {
+		Self {
+			kind,
+			boolean: 0,
+			number: 0.0,
+			string: std::ptr::null(),
+			string_len: 0,
+		}
+	}
+	fn with_str(kind: PluginKind, s: &str) -> Self {
+		Self {
+			string: s.as_ptr().cast(),
+			string_len: s.len(),
+			..Self::new(kind)
+		}
+	}
+	/// # Safety
+	///
+	/// `string` should point to `string_len` bytes, valid for `'a`
+	unsafe fn as_str<'a>(&self) -> Result<&'a str> {
+		if self.string.is_null() {
+			if self.string_len == 0 {
+				return Ok("");
+			}
+			throw!(RuntimeError("plugin returned null string".into()))
+		}
+		let bytes = slice::from_raw_parts(self.string.cast::<u8>(), self.string_len);
+		str::from_utf8(bytes)
+			.map_err(|_| RuntimeError("plugin returned non utf-8 string".into()).into())
+	}
+}
+
+/// Parameter of registered function
+#[repr(C)]
+pub struct PluginParam {
+	/// NUL-terminated name
+	pub name: *const c_char,
+	pub kind: PluginKind,
+}
+
+/// Implementation of registered function
+///
+/// Returns 0 on success, in this case `out` contains function result.
+/// Otherwise `out` may contain error message as `STRING`.
+/// Strings written to `out` should be valid until the next call into the plugin
+pub type PluginFunction = unsafe extern "C" fn(
+	ctx: *mut c_void,
+	args: *const PluginValue,
+	args_len: usize,
+	out: *mut PluginValue,
+) -> c_int;
+/// Passed to [`PluginInit`], returns 0 if function is registered
+pub type PluginRegister = unsafe extern "C" fn(
+	registrar: *mut c_void,
+	name: *const c_char,
+	params: *const PluginParam,
+	params_len: usize,
+	function: PluginFunction,
+	ctx: *mut c_void,
+) -> c_int;
+/// `jrsonnet_plugin_init` symbol, returns 0 on success
+pub type PluginInit =
+	unsafe extern "C" fn(registrar: *mut c_void, register: PluginRegister) -> c_int;
+/// `jrsonnet_plugin_abi_version` symbol
+pub type PluginAbiVersion = unsafe extern "C" fn() -> u32;
+
+#[derive(Trace)]
+struct PluginNative {
+	#[trace(skip)]
+	name: String,
+	params: Vec<BuiltinParam>,
+	#[trace(skip)]
+	kinds: Vec<PluginKind>,
+	#[trace(skip)]
+	function: PluginFunction,
+	#[trace(skip)]
+	ctx: *mut c_void,
+	/// Function pointers are only valid while library is loaded
+	#[trace(skip)]
+	_library: Option<Rc<Library>>,
+}
+
+impl Builtin for PluginNative {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn params(&self) -> &[BuiltinParam] {
+		&self.params
+	}
+
+	fn call(
+		&self,
+		s: State,
+		ctx: Context,
+		_loc: CallLocation<'_>,
+		args: &dyn ArgsLike,
+	) -> Result<Val> {
+		let args = parse_builtin_call(s.clone(), ctx, &self.params, args, true)?;
+		// Owners of strings, pointed to by values, plugin may also return them as result
+		let mut strings = Vec::new();
+		let mut values = Vec::with_capacity(args.len());
+		for (arg, kind) in args.into_iter().zip(self.kinds.iter().copied()) {
+			let arg = arg
+				.expect("plugin natives have no default params")
+				.evaluate(s.clone())?;
+			let value = match kind {
+				PluginKind::BOOL => PluginValue {
+					boolean: bool::from_untyped(arg, s.clone())?.into(),
+					..PluginValue::new(kind)
+				},
+				PluginKind::NUMBER => PluginValue {
+					number: f64::from_untyped(arg, s.clone())?,
+					..PluginValue::new(kind)
+				},
+				PluginKind::STRING => {
+					let str = String::from_untyped(arg, s.clone())?;
+					let value = PluginValue::with_str(kind, &str);
+					strings.push(str);
+					value
+				}
+				PluginKind::JSON => {
+					let json = serde_json::Value::from_untyped(arg, s.clone())?;
+					let str = serde_json::to_string(&json).expect("json is serializable");
+					let value = PluginValue::with_str(kind, &str);
+					strings.push(str);
+					value
+				}
+				_ => unreachable!("checked on registration"),
+			};
+			values.push(value);
+		}
+
+		let mut out = PluginValue::new(PluginKind::NULL);
+		// SAFETY: arguments are matching declared params, strings are alive until the end of call
+		let code = unsafe { (self.function)(self.ctx, values.as_ptr(), values.len(), &mut out) };
+		// SAFETY (as_str): plugin keeps returned string valid until the next call
+		if code != 0 {
+			let message = match out.kind {
+				PluginKind::STRING => unsafe { out.as_str()? },
+				_ => "unknown error",
+			};
+			throw_runtime!("{}: {message}", self.name)
+		}
+		Ok(match out.kind {
+			PluginKind::NULL => Val::Null,
+			PluginKind::BOOL => Val::Bool(out.boolean != 0),
+			PluginKind::NUMBER if out.number.is_finite() => Val::Num(out.number),
+			PluginKind::NUMBER => throw_runtime!("{}: returned non-finite number", self.name),
+			PluginKind::STRING => Val::Str(unsafe { out.as_str()? }.into()),
+			PluginKind::JSON => {
+				let json = serde_json::from_str(unsafe { out.as_str()? }).map_err(|e| {
+					RuntimeError(format!("{}: returned invalid json: {e}", self.name).into())
+				})?;
+				serde_json::Value::into_untyped(json, s)?
+			}
+			PluginKind(kind) => {
+				throw_runtime!("{}: returned value of unknown kind {kind}", self.name)
+			}
+		})
+	}
+}
+
+struct Registrar {
+	natives: Vec<PluginNative>,
+	error: Option<String>,
+	library: Option<Rc<Library>>,
+}
+
+unsafe extern "C" fn register(
+	registrar: *mut c_void,
+	name: *const c_char,
+	params: *const PluginParam,
+	params_len: usize,
+	function: PluginFunction,
+	ctx: *mut c_void,
+) -> c_int {
+	unsafe fn c_str<'a>(s: *const c_char, what: &str) -> Result<&'a str, String> {
+		if s.is_null() {
+			return Err(format!("{what} is null"));
+		}
+		CStr::from_ptr(s)
+			.to_str()
+			.map_err(|_| format!("{what} is not utf-8"))
+	}
+	let registrar = &mut *registrar.cast::<Registrar>();
+	let native = (|| {
+		let name = c_str(name, "function name")?;
+		let params = if params_len == 0 {
+			&[]
+		} else if params.is_null() {
+			return Err(format!("{name}: params are null"));
+		} else {
+			slice::from_raw_parts(params, params_len)
+		};
+		let mut builtin_params = Vec::with_capacity(params.len());
+		let mut kinds = Vec::with_capacity(params.len());
+		for param in params {
+			let param_name = c_str(param.name, "param name")?;
+			if !param.kind.is_param() {
+				return Err(format!(
+					"{name}: param {param_name} has unsupported kind {}",
+					param.kind.0
+				));
+			}
+			builtin_params.push(BuiltinParam {
+				name: Some(param_name.to_owned().into()),
+				has_default: false,
+			});
+			kinds.push(param.kind);
+		}
+		Ok(PluginNative {
+			name: name.to_owned(),
+			params: builtin_params,
+			kinds,
+			function,
+			ctx,
+			_library: registrar.library.clone(),
+		})
+	})();
+	match native {
+		Ok(native) => {
+			registrar.natives.push(native);
+			0
+		}
+		Err(e) => {
+			registrar.error.get_or_insert(e);
+			1
+		}
+	}
+}
+
+unsafe fn init(
+	ctx: &ContextInitializer,
+	init: PluginInit,
+	library: Option<Rc<Library>>,
+) -> Result<(), String> {
+	let mut registrar = Registrar {
+		natives: Vec::new(),
+		error: None,
+		library,
+	};
+	let code = init((&mut registrar as *mut Registrar).cast(), register);
+	if let Some(error) = registrar.error {
+		return Err(error);
+	}
+	if code != 0 {
+		return Err(format!("init returned {code}"));
+	}
+	for native in registrar.natives {
+		ctx.add_native(IStr::from(native.name.as_str()), Cc::new(tb!(native)));
+	}
+	Ok(())
+}
+
+/// Registers natives of statically linked plugin
+///
+/// # Safety
+///
+/// `plugin_init` should follow plugin ABI
+pub unsafe fn init_plugin(ctx: &ContextInitializer, plugin_init: PluginInit) -> Result<()> {
+	init(ctx, plugin_init, None)
+		.map_err(|e| RuntimeError(format!("native plugin initialization failed: {e}").into()))?;
+	Ok(())
+}
+
+/// Loads shared library, and registers its natives
+///
+/// # Safety
+///
+/// Library initialization routines are executed on load, and exported functions should follow plugin ABI
+pub unsafe fn load_plugin(ctx: &ContextInitializer, path: &Path) -> Result<()> {
+	let fail = |e: String| RuntimeError(format!("native plugin {}: {e}", path.display()).into());
+	// Error message already contains the path
+	let library = Library::new(path)
+		.map_err(|e| RuntimeError(format!("failed to load native plugin: {e}").into()))?;
+	let abi_version = *library
+		.get::<PluginAbiVersion>(b"jrsonnet_plugin_abi_version\0")
+		.map_err(|e| fail(e.to_string()))?;
+	let version = abi_version();
+	if version != PLUGIN_ABI_VERSION {
+		throw!(fail(format!(
+			"unsupported abi version {version}, expected {PLUGIN_ABI_VERSION}"
+		)))
+	}
+	let plugin_init = *library
+		.get::<PluginInit>(b"jrsonnet_plugin_init\0")
+		.map_err(|e| fail(e.to_string()))?;
+	init(ctx, plugin_init, Some(Rc::new(library))).map_err(fail)?;
+	Ok(())
+}
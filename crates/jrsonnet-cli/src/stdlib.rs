@@ -55,6 +55,24 @@ impl FromStr for ExtFile {
 	}
 }
 
+#[derive(Clone)]
+pub struct NativeExec {
+	pub name: String,
+	pub command: String,
+}
+
+impl FromStr for NativeExec {
+	type Err = &'static str;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let (name, command) = s.split_once('=').ok_or("bad native-exec syntax")?;
+		Ok(Self {
+			name: name.to_owned(),
+			command: command.to_owned(),
+		})
+	}
+}
+
 #[derive(Parser)]
 #[clap(next_help_heading = "STANDARD LIBRARY")]
 pub struct StdOpts {
@@ -104,6 +122,27 @@ pub struct StdOpts {
 		multiple_occurrences = true
 	)]
 	ext_code_file: Vec<ExtFile>,
+	/// Load native functions from shared library plugin.
+	/// Functions registered by plugin can be accessed from code via `std.native("name")`.
+	/// See `bindings/c/jrsonnet_plugin.h` for plugin interface.
+	#[cfg(feature = "native-plugin")]
+	#[clap(long, name = "plugin path", multiple_occurrences = true)]
+	native_plugin: Vec<std::path::PathBuf>,
+	/// Add native function, implemented by long-lived process.
+	/// Calls are sent to its stdin as line-delimited JSON-RPC requests, see `jrsonnet_cli::native_exec`.
+	/// Can be accessed from code via `std.native("name")`.
+	#[cfg(feature = "native-exec")]
+	#[clap(
+		long,
+		name = "name=command",
+		number_of_values = 1,
+		multiple_occurrences = true
+	)]
+	native_exec: Vec<NativeExec>,
+	/// Seconds to wait for the response of `--native-exec` process, after which it is killed.
+	#[cfg(feature = "native-exec")]
+	#[clap(long, name = "seconds", default_value = "60")]
+	native_exec_timeout: u64,
 }
 impl ConfigureState for StdOpts {
 	fn configure(&self, s: &State) -> Result<()> {
@@ -124,6 +163,21 @@ impl ConfigureState for StdOpts {
 		for ext in self.ext_code_file.iter() {
 			ctx.add_ext_code(&ext.name as &str, &ext.value as &str)?;
 		}
+		#[cfg(feature = "native-plugin")]
+		for path in self.native_plugin.iter() {
+			// SAFETY: user explicitly asked to load this library
+			unsafe { crate::plugin::load_plugin(&ctx, path)? };
+		}
+		#[cfg(feature = "native-exec")]
+		for native in self.native_exec.iter() {
+			ctx.add_native(
+				(&native.name as &str).into(),
+				jrsonnet_gcmodule::Cc::new(jrsonnet_evaluator::tb!(
+					crate::native_exec::ExecNative::new(&native.name, &native.command)
+						.with_timeout(std::time::Duration::from_secs(self.native_exec_timeout))
+				)),
+			);
+		}
 		s.settings_mut().context_initializer = Box::new(ctx);
 		Ok(())
 	}
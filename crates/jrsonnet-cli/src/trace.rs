@@ -13,6 +13,8 @@ use crate::ConfigureState;
 pub enum TraceFormatName {
 	Compact,
 	Explaining,
+	#[cfg(feature = "json-trace")]
+	Json,
 }
 
 impl FromStr for TraceFormatName {
@@ -21,6 +23,8 @@ impl FromStr for TraceFormatName {
 		Ok(match s {
 			"compact" => TraceFormatName::Compact,
 			"explaining" => TraceFormatName::Explaining,
+			#[cfg(feature = "json-trace")]
+			"json" => TraceFormatName::Json,
 			_ => return Err("no such format"),
 		})
 	}
@@ -33,7 +37,16 @@ pub struct TraceOpts {
 	/// `compact` format only shows `filename:line:column`s
 	/// while `explaining` displays source code with attached trace annotations
 	/// thus being more verbose.
-	#[clap(long, possible_values = &["compact", "explaining"])]
+	/// `json` outputs error and trace as single-line JSON object, for consumption by other tools.
+	#[clap(
+		long,
+		possible_values = &[
+			"compact",
+			"explaining",
+			#[cfg(feature = "json-trace")]
+			"json",
+		]
+	)]
 	trace_format: Option<TraceFormatName>,
 	/// Amount of stack trace elements to be displayed.
 	/// If set to `0` then full stack trace will be displayed.
@@ -55,6 +68,10 @@ impl ConfigureState for TraceOpts {
 			TraceFormatName::Explaining => {
 				s.set_trace_format(Box::new(ExplainingFormat { resolver }))
 			}
+			#[cfg(feature = "json-trace")]
+			TraceFormatName::Json => {
+				s.set_trace_format(Box::new(jrsonnet_evaluator::trace::JsonFormat { resolver }))
+			}
 		}
 		s.set_max_trace(self.max_trace);
 		Ok(())
@@ -2,9 +2,12 @@ use std::fmt::Debug;
 
 use jrsonnet_gcmodule::{Cc, Trace};
 use jrsonnet_interner::IStr;
+use jrsonnet_parser::VarSlot;
 
 use crate::{
-	error::Error::*, gc::GcHashMap, map::LayeredHashMap, ObjValue, Pending, Result, Thunk, Val,
+	error::Error::*,
+	map::{Bindings, LayeredBindings},
+	throw, ObjValue, Pending, Result, Thunk, Val,
 };
 
 #[derive(Trace)]
@@ -12,7 +15,7 @@ struct ContextInternals {
 	dollar: Option<ObjValue>,
 	sup: Option<ObjValue>,
 	this: Option<ObjValue>,
-	bindings: LayeredHashMap,
+	bindings: LayeredBindings,
 }
 impl Debug for ContextInternals {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -47,7 +50,7 @@ impl Context {
 			dollar: None,
 			this: None,
 			sup: None,
-			bindings: LayeredHashMap::default(),
+			bindings: LayeredBindings::default(),
 		}))
 	}
 
@@ -63,28 +66,31 @@ impl Context {
 
 	#[cfg(feature = "friendly-errors")]
 	pub fn binding(&self, name: IStr) -> Result<Thunk<Val>> {
-		use std::cmp::Ordering;
-
-		use crate::throw;
-
 		if let Some(val) = self.0.bindings.get(&name).cloned() {
 			return Ok(val);
 		}
 
-		let mut heap = Vec::new();
-		self.0.bindings.clone().iter_keys(|k| {
-			let conf = strsim::jaro_winkler(&k as &str, &name as &str);
-			if conf < 0.8 {
-				return;
-			}
-			heap.push((conf, k));
-		});
-		heap.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
-
-		throw!(VariableIsNotDefined(
-			name,
-			heap.into_iter().map(|(_, k)| k).collect()
-		))
+		let suggestions = crate::similar_names(&name, self.binding_names());
+		throw!(VariableIsNotDefined(name, suggestions))
+	}
+	/// Get variable by slot, assigned by [`jrsonnet_parser::resolve_scoped`]
+	///
+	/// Fails if expression was resolved against another scope chain, i.e by hand-built context
+	pub fn binding_at(&self, name: &IStr, slot: VarSlot) -> Result<Thunk<Val>> {
+		let Some(value) = self.0.bindings.get_slot(name, slot) else {
+			throw!(VariableSlotMismatch(name.clone(), slot))
+		};
+		Ok(value.clone())
+	}
+	/// Names of all variables visible in this context, innermost first
+	pub fn binding_names(&self) -> Vec<IStr> {
+		let mut out = Vec::new();
+		self.0.bindings.clone().iter_keys(|k| out.push(k));
+		out
+	}
+	/// Names of variables of every scope layer, innermost first, in slot order
+	pub fn binding_layers(&self) -> Vec<Vec<IStr>> {
+		self.0.bindings.layer_names()
 	}
 	pub fn contains_binding(&self, name: IStr) -> bool {
 		self.0.bindings.contains_key(&name)
@@ -99,15 +105,15 @@ impl Context {
 
 	#[must_use]
 	pub fn with_var(self, name: IStr, value: Val) -> Self {
-		let mut new_bindings = GcHashMap::with_capacity(1);
-		new_bindings.insert(name, Thunk::evaluated(value));
-		self.extend(new_bindings, None, None, None)
+		let mut builder = ContextBuilder::extend(self);
+		builder.bind(name, Thunk::evaluated(value));
+		builder.build()
 	}
 
 	#[must_use]
 	pub fn extend(
 		self,
-		new_bindings: GcHashMap<IStr, Thunk<Val>>,
+		new_bindings: Bindings,
 		new_dollar: Option<ObjValue>,
 		new_sup: Option<ObjValue>,
 		new_this: Option<ObjValue>,
@@ -143,7 +149,7 @@ impl PartialEq for Context {
 }
 
 pub struct ContextBuilder {
-	bindings: GcHashMap<IStr, Thunk<Val>>,
+	bindings: Bindings,
 	extend: Option<Context>,
 }
 
@@ -153,21 +159,23 @@ impl ContextBuilder {
 	}
 	pub fn with_capacity(capacity: usize) -> Self {
 		Self {
-			bindings: GcHashMap::with_capacity(capacity),
+			bindings: Bindings::with_capacity(capacity),
 			extend: None,
 		}
 	}
 	pub fn extend(parent: Context) -> Self {
 		Self {
-			bindings: GcHashMap::new(),
+			bindings: Bindings::new(),
 			extend: Some(parent),
 		}
 	}
 	/// # Panics
 	/// If `name` is already bound
 	pub fn bind(&mut self, name: IStr, value: Thunk<Val>) -> &mut Self {
-		let old = self.bindings.insert(name, value);
-		assert!(old.is_none(), "variable bound twice in single context call");
+		assert!(
+			self.bindings.bind(name, value).is_ok(),
+			"variable bound twice in single context call"
+		);
 		self
 	}
 	pub fn build(self) -> Context {
@@ -175,7 +183,7 @@ impl ContextBuilder {
 			parent.extend(self.bindings, None, None, None)
 		} else {
 			Context(Cc::new(ContextInternals {
-				bindings: LayeredHashMap::new(self.bindings),
+				bindings: LayeredBindings::new(self.bindings),
 				dollar: None,
 				sup: None,
 				this: None,
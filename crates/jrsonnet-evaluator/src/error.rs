@@ -2,10 +2,12 @@ use std::{fmt::Debug, path::PathBuf};
 
 use jrsonnet_gcmodule::Trace;
 use jrsonnet_interner::IStr;
-use jrsonnet_parser::{BinaryOpType, ExprLocation, Source, SourcePath, UnaryOpType};
+use jrsonnet_parser::{BinaryOpType, ExprLocation, Source, SourcePath, UnaryOpType, VarSlot};
 use jrsonnet_types::ValType;
 use thiserror::Error;
 
+#[cfg(feature = "json-schema")]
+use crate::typed::TypeLocErrorList;
 use crate::{stdlib::format::FormatError, typed::TypeLocError};
 
 fn format_found(list: &[IStr], what: &str) -> String {
@@ -65,6 +67,20 @@ const fn format_empty_str(str: &str) -> &str {
 	}
 }
 
+fn format_syntax_error(path: &Source, error: &jrsonnet_parser::ParseError) -> String {
+	jrsonnet_parser::explain_error(path.code(), error).map_or_else(
+		|| {
+			let got = path
+				.code()
+				.get(error.location.offset..)
+				.and_then(|rest| rest.chars().next())
+				.map_or_else(|| "EOF".into(), |c| c.to_string());
+			format!("expected {}, got {got:?}", error.expected)
+		},
+		|e| e.message,
+	)
+}
+
 type FunctionSignature = Vec<(Option<IStr>, bool)>;
 
 /// Possible errors
@@ -102,6 +118,8 @@ pub enum Error {
 	VariableIsNotDefined(IStr, Vec<IStr>),
 	#[error("duplicate local var: {0}")]
 	DuplicateLocalVar(IStr),
+	#[error("internal error: variable {0} is not found at depth {}, index {}, code was resolved against another context", .1.depth, .1.index)]
+	VariableSlotMismatch(IStr, VarSlot),
 
 	#[error("type mismatch: expected {}, got {2} {0}", .1.iter().map(|e| format!("{e}")).collect::<Vec<_>>().join(", "))]
 	TypeMismatch(&'static str, Vec<ValType>, ValType),
@@ -157,12 +175,7 @@ pub enum Error {
 	AbsoluteImportNotSupported(PathBuf),
 	#[error("can't import from virtual file")]
 	CantImportFromVirtualFile,
-	#[error(
-		"syntax error: expected {}, got {:?}",
-		.error.expected,
-		.path.code().chars().nth(error.location.offset)
-		.map_or_else(|| "EOF".into(), |c| c.to_string())
-	)]
+	#[error("syntax error: {}", format_syntax_error(.path, .error))]
 	ImportSyntaxError {
 		path: Source,
 		#[trace(skip)]
@@ -179,6 +192,8 @@ pub enum Error {
 	FractionalIndex,
 	#[error("attempted to divide by zero")]
 	DivisionByZero,
+	#[error("integer overflow, result doesn't fit in 64 bits")]
+	IntegerOverflow,
 
 	#[error("string manifest output is not an string")]
 	StringManifestOutputIsNotAString,
@@ -201,12 +216,89 @@ pub enum Error {
 	Format(#[from] FormatError),
 	#[error("type error: {0}")]
 	TypeError(TypeLocError),
+	#[cfg(feature = "json-schema")]
+	#[error("invalid json schema: {0}")]
+	InvalidJsonSchema(String),
+	#[cfg(feature = "json-schema")]
+	#[error("schema validation failed:\n{0}")]
+	SchemaValidationFailed(TypeLocErrorList),
 
 	#[cfg(feature = "anyhow-error")]
 	#[error(transparent)]
 	Other(Rc<anyhow::Error>),
 }
 
+impl Error {
+	/// Name of the error variant, stable identifier for machine-readable output
+	pub const fn kind(&self) -> &'static str {
+		match self {
+			Self::IntrinsicNotFound(..) => "IntrinsicNotFound",
+			Self::UnaryOperatorDoesNotOperateOnType(..) => "UnaryOperatorDoesNotOperateOnType",
+			Self::BinaryOperatorDoesNotOperateOnValues(..) => {
+				"BinaryOperatorDoesNotOperateOnValues"
+			}
+			Self::NoTopLevelObjectFound => "NoTopLevelObjectFound",
+			Self::CantUseSelfOutsideOfObject => "CantUseSelfOutsideOfObject",
+			Self::NoSuperFound => "NoSuperFound",
+			Self::InComprehensionCanOnlyIterateOverArray => {
+				"InComprehensionCanOnlyIterateOverArray"
+			}
+			Self::ArrayBoundsError(..) => "ArrayBoundsError",
+			Self::StringBoundsError(..) => "StringBoundsError",
+			Self::AssertionFailed(..) => "AssertionFailed",
+			Self::VariableIsNotDefined(..) => "VariableIsNotDefined",
+			Self::DuplicateLocalVar(..) => "DuplicateLocalVar",
+			Self::VariableSlotMismatch(..) => "VariableSlotMismatch",
+			Self::TypeMismatch(..) => "TypeMismatch",
+			Self::NoSuchField(..) => "NoSuchField",
+			Self::OnlyFunctionsCanBeCalledGot(..) => "OnlyFunctionsCanBeCalledGot",
+			Self::UnknownFunctionParameter(..) => "UnknownFunctionParameter",
+			Self::BindingParameterASecondTime(..) => "BindingParameterASecondTime",
+			Self::TooManyArgsFunctionHas(..) => "TooManyArgsFunctionHas",
+			Self::FunctionParameterNotBoundInCall(..) => "FunctionParameterNotBoundInCall",
+			Self::UndefinedExternalVariable(..) => "UndefinedExternalVariable",
+			Self::FieldMustBeStringGot(..) => "FieldMustBeStringGot",
+			Self::DuplicateFieldName(..) => "DuplicateFieldName",
+			Self::AttemptedIndexAnArrayWithString(..) => "AttemptedIndexAnArrayWithString",
+			Self::ValueIndexMustBeTypeGot(..) => "ValueIndexMustBeTypeGot",
+			Self::CantIndexInto(..) => "CantIndexInto",
+			Self::ValueIsNotIndexable(..) => "ValueIsNotIndexable",
+			Self::StandaloneSuper => "StandaloneSuper",
+			Self::ImportFileNotFound(..) => "ImportFileNotFound",
+			Self::AbsoluteImportFileNotFound(..) => "AbsoluteImportFileNotFound",
+			Self::ResolvedFileNotFound(..) => "ResolvedFileNotFound",
+			Self::ImportIsADirectory(..) => "ImportIsADirectory",
+			Self::ImportBadFileUtf8(..) => "ImportBadFileUtf8",
+			Self::ImportIo(..) => "ImportIo",
+			Self::ImportNotSupported(..) => "ImportNotSupported",
+			Self::AbsoluteImportNotSupported(..) => "AbsoluteImportNotSupported",
+			Self::CantImportFromVirtualFile => "CantImportFromVirtualFile",
+			Self::ImportSyntaxError { .. } => "ImportSyntaxError",
+			Self::RuntimeError(..) => "RuntimeError",
+			Self::StackOverflow => "StackOverflow",
+			Self::InfiniteRecursionDetected => "InfiniteRecursionDetected",
+			Self::FractionalIndex => "FractionalIndex",
+			Self::DivisionByZero => "DivisionByZero",
+			Self::IntegerOverflow => "IntegerOverflow",
+			Self::StringManifestOutputIsNotAString => "StringManifestOutputIsNotAString",
+			Self::StreamManifestOutputIsNotAArray => "StreamManifestOutputIsNotAArray",
+			Self::MultiManifestOutputIsNotAObject => "MultiManifestOutputIsNotAObject",
+			Self::StreamManifestOutputCannotBeRecursed => "StreamManifestOutputCannotBeRecursed",
+			Self::StreamManifestCannotNestString => "StreamManifestCannotNestString",
+			Self::ImportCallbackError(..) => "ImportCallbackError",
+			Self::InvalidUnicodeCodepointGot(..) => "InvalidUnicodeCodepointGot",
+			Self::Format(..) => "Format",
+			Self::TypeError(..) => "TypeError",
+			#[cfg(feature = "json-schema")]
+			Self::InvalidJsonSchema(..) => "InvalidJsonSchema",
+			#[cfg(feature = "json-schema")]
+			Self::SchemaValidationFailed(..) => "SchemaValidationFailed",
+			#[cfg(feature = "anyhow-error")]
+			Self::Other(..) => "Other",
+		}
+	}
+}
+
 #[cfg(feature = "anyhow-error")]
 impl From<anyhow::Error> for LocError {
 	fn from(e: anyhow::Error) -> Self {
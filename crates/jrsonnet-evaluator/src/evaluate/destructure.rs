@@ -3,11 +3,7 @@ use jrsonnet_interner::IStr;
 use jrsonnet_parser::{BindSpec, Destruct, LocExpr, ParamsDesc};
 
 use crate::{
-	error::{Error::*, Result},
-	evaluate, evaluate_method, evaluate_named,
-	gc::GcHashMap,
-	tb, throw,
-	val::ThunkValue,
+	error::Result, evaluate, evaluate_method, evaluate_named, map::Bindings, tb, val::ThunkValue,
 	Context, Pending, State, Thunk, Val,
 };
 
@@ -17,14 +13,11 @@ pub fn destruct(
 	d: &Destruct,
 	parent: Thunk<Val>,
 	fctx: Pending<Context>,
-	new_bindings: &mut GcHashMap<IStr, Thunk<Val>>,
+	new_bindings: &mut Bindings,
 ) -> Result<()> {
 	match d {
 		Destruct::Full(v) => {
-			let old = new_bindings.insert(v.clone(), parent);
-			if old.is_some() {
-				throw!(DuplicateLocalVar(v.clone()))
-			}
+			new_bindings.bind(v.0.clone(), parent)?;
 		}
 		#[cfg(feature = "exp-destruct")]
 		Destruct::Skip => {}
@@ -115,15 +108,13 @@ pub fn destruct(
 						}
 					}
 
-					destruct(
-						&Destruct::Full(v.clone()),
+					new_bindings.bind(
+						v.clone(),
 						Thunk::new(tb!(RestThunk {
 							full: full.clone(),
 							start: start.len(),
 							end: end.len(),
 						})),
-						fctx.clone(),
-						new_bindings,
 					)?;
 				}
 				Some(DestructRest::Drop) => {}
@@ -233,12 +224,7 @@ pub fn destruct(
 				if let Some(d) = d {
 					destruct(d, value, fctx.clone(), new_bindings)?;
 				} else {
-					destruct(
-						&Destruct::Full(field.clone()),
-						value,
-						fctx.clone(),
-						new_bindings,
-					)?;
+					new_bindings.bind(field.clone(), value)?;
 				}
 			}
 		}
@@ -249,7 +235,7 @@ pub fn destruct(
 pub fn evaluate_dest(
 	d: &BindSpec,
 	fctx: Pending<Context>,
-	new_bindings: &mut GcHashMap<IStr, Thunk<Val>>,
+	new_bindings: &mut Bindings,
 ) -> Result<()> {
 	match d {
 		BindSpec::Field { into, value } => {
@@ -301,18 +287,15 @@ pub fn evaluate_dest(
 				}
 			}
 
-			let old = new_bindings.insert(
-				name.clone(),
+			new_bindings.bind(
+				name.0.clone(),
 				Thunk::new(tb!(MethodThunk {
 					fctx,
-					name: name.clone(),
+					name: name.0.clone(),
 					params: params.clone(),
 					value: value.clone()
 				})),
-			);
-			if old.is_some() {
-				throw!(DuplicateLocalVar(name.clone()))
-			}
+			)?;
 		}
 	}
 	Ok(())
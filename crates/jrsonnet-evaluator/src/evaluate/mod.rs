@@ -3,8 +3,8 @@ use std::{cmp::Ordering, rc::Rc};
 use jrsonnet_gcmodule::{Cc, Trace};
 use jrsonnet_interner::IStr;
 use jrsonnet_parser::{
-	ArgsDesc, AssertStmt, BindSpec, CompSpec, Expr, FieldMember, FieldName, ForSpecData,
-	IfSpecData, LiteralType, LocExpr, Member, ObjBody, ParamsDesc,
+	ArgsDesc, AssertStmt, BindSpec, CompSpec, Expr, ExprLocation, FieldMember, FieldName,
+	ForSpecData, IfSpecData, LiteralType, LocExpr, Member, ObjBody, ParamsDesc,
 };
 use jrsonnet_types::ValType;
 
@@ -13,11 +13,11 @@ use crate::{
 	error::Error::*,
 	evaluate::operator::{evaluate_add_op, evaluate_binary_op_special, evaluate_unary_op},
 	function::{CallLocation, FuncDesc, FuncVal},
+	map::Bindings,
 	tb, throw,
 	typed::Typed,
 	val::{ArrValue, CachedUnbound, IndexableVal, Thunk, ThunkValue},
-	Context, GcHashMap, ObjValue, ObjValueBuilder, ObjectAssertion, Pending, Result, State,
-	Unbound, Val,
+	Context, ObjValue, ObjValueBuilder, ObjectAssertion, Pending, Result, State, Unbound, Val,
 };
 pub mod destructure;
 pub mod operator;
@@ -33,7 +33,7 @@ pub fn evaluate_method(ctx: Context, name: IStr, params: ParamsDesc, body: LocEx
 
 pub fn evaluate_field_name(s: State, ctx: Context, field_name: &FieldName) -> Result<Option<IStr>> {
 	Ok(match field_name {
-		FieldName::Fixed(n) => Some(n.clone()),
+		FieldName::Fixed(n) => Some(n.0.clone()),
 		FieldName::Dyn(expr) => s.push(
 			CallLocation::new(&expr.1),
 			|| "evaluating field name".to_string(),
@@ -68,7 +68,7 @@ pub fn evaluate_comp(
 					for item in list.iter(s.clone()) {
 						evaluate_comp(
 							s.clone(),
-							ctx.clone().with_var(var.clone(), item?.clone()),
+							ctx.clone().with_var(var.0.clone(), item?.clone()),
 							&specs[1..],
 							callback,
 						)?;
@@ -103,7 +103,7 @@ fn evaluate_object_locals(
 			this: Option<ObjValue>,
 		) -> Result<Context> {
 			let fctx = Context::new_future();
-			let mut new_bindings = GcHashMap::new();
+			let mut new_bindings = Bindings::with_capacity(self.locals.len());
 			for b in self.locals.iter() {
 				evaluate_dest(b, fctx.clone(), &mut new_bindings)?;
 			}
@@ -269,7 +269,7 @@ pub fn evaluate_member_list_object(s: State, ctx: Context, members: &[Member]) -
 		}
 	}
 	let this = builder.build();
-	fctx.fill(ctx.extend(GcHashMap::new(), None, None, Some(this.clone())));
+	fctx.fill(ctx.extend(Bindings::new(), None, None, Some(this.clone())));
 	Ok(this)
 }
 
@@ -311,7 +311,7 @@ pub fn evaluate_object(s: State, ctx: Context, object: &ObjBody) -> Result<ObjVa
 								Ok(Thunk::evaluated(evaluate(
 									s.clone(),
 									self.uctx.bind(s, sup, this.clone())?.extend(
-										GcHashMap::new(),
+										Bindings::new(),
 										None,
 										None,
 										this,
@@ -321,7 +321,7 @@ pub fn evaluate_object(s: State, ctx: Context, object: &ObjBody) -> Result<ObjVa
 							}
 						}
 						builder
-							.member(n)
+							.member(n.into_flat())
 							.with_location(obj.value.1.clone())
 							.with_add(obj.plus)
 							.bindable(
@@ -341,7 +341,7 @@ pub fn evaluate_object(s: State, ctx: Context, object: &ObjBody) -> Result<ObjVa
 			let this = builder.build();
 			for (ctx, fctx) in ctxs {
 				let _ctx = ctx
-					.extend(GcHashMap::new(), None, None, Some(this.clone()))
+					.extend(Bindings::new(), None, None, Some(this.clone()))
 					.into_future(fctx);
 			}
 			this
@@ -405,240 +405,316 @@ pub fn evaluate_named(s: State, ctx: Context, expr: &LocExpr, name: IStr) -> Res
 	})
 }
 
-#[allow(clippy::too_many_lines)]
+/// Context of `local` expression body
+fn evaluate_locals(ctx: Context, bindings: &[BindSpec]) -> Result<Context> {
+	let mut new_bindings = Bindings::with_capacity(bindings.len());
+	let fctx = Context::new_future();
+	for b in bindings {
+		evaluate_dest(b, fctx.clone(), &mut new_bindings)?;
+	}
+	Ok(ctx.extend(new_bindings, None, None, None).into_future(fctx))
+}
+
+/// Fields of object, which have names similar to missing `key`, most similar first
+#[cfg(feature = "friendly-errors")]
+pub fn similar_fields(obj: &ObjValue, key: &str) -> Vec<IStr> {
+	let mut heap = Vec::new();
+	for field in obj.fields_ex(
+		true,
+		#[cfg(feature = "exp-preserve-order")]
+		false,
+	) {
+		let conf = strsim::jaro_winkler(&field as &str, key);
+		if conf < 0.8 {
+			continue;
+		}
+		heap.push((conf, field));
+	}
+	heap.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+	heap.into_iter().map(|(_, v)| v).collect()
+}
+#[cfg(not(feature = "friendly-errors"))]
+pub fn similar_fields(_obj: &ObjValue, _key: &str) -> Vec<IStr> {
+	vec![]
+}
+
+/// `value[index]`, `loc` is used for field access stack frame
+fn evaluate_index(s: State, loc: &ExprLocation, value: Val, index: Val) -> Result<Val> {
+	Ok(match (value, index) {
+		(Val::Obj(v), Val::Str(key)) => {
+			let key = key.into_flat();
+			s.push(
+				CallLocation::new(loc),
+				|| format!("field <{key}> access"),
+				|| match v.get(s.clone(), key.clone()) {
+					Ok(Some(v)) => Ok(v),
+					Ok(None) => throw!(NoSuchField(key.clone(), similar_fields(&v, &key))),
+					Err(e) => Err(e),
+				},
+			)?
+		}
+		(Val::Obj(_), n) => throw!(ValueIndexMustBeTypeGot(
+			ValType::Obj,
+			ValType::Str,
+			n.value_type(),
+		)),
+
+		(Val::Arr(v), Val::Num(n)) => {
+			if n.fract() > f64::EPSILON {
+				throw!(FractionalIndex)
+			}
+			v.get(s, n as usize)?
+				.ok_or_else(|| ArrayBoundsError(n as usize, v.len()))?
+		}
+		// Integers are out of `f64` safe range, so they can't be within bounds
+		(Val::Arr(v), Val::Int(n)) => throw!(ArrayBoundsError(n.to_f64() as usize, v.len())),
+		(Val::Arr(_), Val::Str(n)) => throw!(AttemptedIndexAnArrayWithString(n.into_flat())),
+		(Val::Arr(_), n) => throw!(ValueIndexMustBeTypeGot(
+			ValType::Arr,
+			ValType::Num,
+			n.value_type(),
+		)),
+
+		(Val::Str(s), Val::Num(n)) => Val::Str({
+			let s = s.into_flat();
+			let v: IStr = s
+				.chars()
+				.skip(n as usize)
+				.take(1)
+				.collect::<String>()
+				.into();
+			if v.is_empty() {
+				let size = s.chars().count();
+				throw!(StringBoundsError(n as usize, size))
+			}
+			v.into()
+		}),
+		(Val::Str(s), Val::Int(n)) => throw!(StringBoundsError(
+			n.to_f64() as usize,
+			s.into_flat().chars().count()
+		)),
+		(Val::Str(_), n) => throw!(ValueIndexMustBeTypeGot(
+			ValType::Str,
+			ValType::Num,
+			n.value_type(),
+		)),
+
+		(v, _) => throw!(CantIndexInto(v.value_type())),
+	})
+}
+
 pub fn evaluate(s: State, ctx: Context, expr: &LocExpr) -> Result<Val> {
+	evaluate_inner(s, ctx, expr, false)
+}
+
+/// Expressions in tail position (`local` body, taken `if` branch, body of `tailstrict` call)
+/// are evaluated in the same loop iteration instead of recursive call.
+///
+/// First `tailstrict` call still pushes stack frame, and evaluates function body with `in_tail_call`,
+/// where next calls are unrolled, so only non-tail recursion grows the stack
+#[allow(clippy::too_many_lines)]
+fn evaluate_inner(s: State, mut ctx: Context, expr: &LocExpr, in_tail_call: bool) -> Result<Val> {
 	use Expr::*;
-	let LocExpr(expr, loc) = expr;
-	// let bp = with_state(|s| s.0.stop_at.borrow().clone());
-	Ok(match &**expr {
-		Literal(LiteralType::This) => {
-			Val::Obj(ctx.this().clone().ok_or(CantUseSelfOutsideOfObject)?)
+	// Owns body of the last tailstrict call, as it is not referenced by the calling expression
+	let mut tail_body: LocExpr;
+	let mut expr = expr;
+	loop {
+		let LocExpr(raw_expr, loc) = expr;
+		let breakpoints = s.enter_breakpoints(loc);
+		if !breakpoints.is_empty() {
+			// Recorded value should belong to this expression, so it can't be replaced with tail expression here
+			let result = evaluate_inner(s, ctx, expr, in_tail_call);
+			for breakpoint in breakpoints {
+				breakpoint.leave(&result);
+			}
+			return result;
 		}
-		Literal(LiteralType::Super) => Val::Obj(
-			ctx.super_obj().clone().ok_or(NoSuperFound)?.with_this(
-				ctx.this()
-					.clone()
-					.expect("if super exists - then this should to"),
+		return Ok(match &**raw_expr {
+			Literal(LiteralType::This) => {
+				Val::Obj(ctx.this().clone().ok_or(CantUseSelfOutsideOfObject)?)
+			}
+			Literal(LiteralType::Super) => Val::Obj(
+				ctx.super_obj().clone().ok_or(NoSuperFound)?.with_this(
+					ctx.this()
+						.clone()
+						.expect("if super exists - then this should to"),
+				),
 			),
-		),
-		Literal(LiteralType::Dollar) => {
-			Val::Obj(ctx.dollar().clone().ok_or(NoTopLevelObjectFound)?)
-		}
-		Literal(LiteralType::True) => Val::Bool(true),
-		Literal(LiteralType::False) => Val::Bool(false),
-		Literal(LiteralType::Null) => Val::Null,
-		Parened(e) => evaluate(s, ctx, e)?,
-		Str(v) => Val::Str(v.clone()),
-		Num(v) => Val::new_checked_num(*v)?,
-		BinaryOp(v1, o, v2) => evaluate_binary_op_special(s, ctx, v1, *o, v2)?,
-		UnaryOp(o, v) => evaluate_unary_op(*o, &evaluate(s, ctx, v)?)?,
-		Var(name) => s.push(
-			CallLocation::new(loc),
-			|| format!("variable <{name}> access"),
-			|| ctx.binding(name.clone())?.evaluate(s.clone()),
-		)?,
-		Index(value, index) => {
-			match (
+			Literal(LiteralType::Dollar) => {
+				Val::Obj(ctx.dollar().clone().ok_or(NoTopLevelObjectFound)?)
+			}
+			Literal(LiteralType::True) => Val::Bool(true),
+			Literal(LiteralType::False) => Val::Bool(false),
+			Literal(LiteralType::Null) => Val::Null,
+			Parened(e) => {
+				expr = e;
+				continue;
+			}
+			Str(v) => Val::Str(v.clone().into()),
+			Num(v) => Val::new_checked_num(*v)?,
+			BinaryOp(v1, o, v2) => evaluate_binary_op_special(s, ctx, v1, *o, v2)?,
+			UnaryOp(o, v) => evaluate_unary_op(*o, &evaluate(s, ctx, v)?)?,
+			Var(name) => s.push(
+				CallLocation::new(loc),
+				|| format!("variable <{name}> access"),
+				|| ctx.binding(name.clone())?.evaluate(s.clone()),
+			)?,
+			ResolvedVar(name, slot) => s.push(
+				CallLocation::new(loc),
+				|| format!("variable <{name}> access"),
+				|| ctx.binding_at(name, *slot)?.evaluate(s.clone()),
+			)?,
+			Index(value, index) => evaluate_index(
+				s.clone(),
+				loc,
 				evaluate(s.clone(), ctx.clone(), value)?,
-				evaluate(s.clone(), ctx, index)?,
-			) {
-				(Val::Obj(v), Val::Str(key)) => s.push(
-					CallLocation::new(loc),
-					|| format!("field <{key}> access"),
-					|| match v.get(s.clone(), key.clone()) {
-						Ok(Some(v)) => Ok(v),
-						#[cfg(not(feature = "friendly-errors"))]
-						Ok(None) => throw!(NoSuchField(key.clone(), vec![])),
-						#[cfg(feature = "friendly-errors")]
-						Ok(None) => {
-							let mut heap = Vec::new();
-							for field in v.fields_ex(
-								true,
-								#[cfg(feature = "exp-preserve-order")]
-								false,
-							) {
-								let conf = strsim::jaro_winkler(&field as &str, &key as &str);
-								if conf < 0.8 {
-									continue;
-								}
-								heap.push((conf, field));
-							}
-							heap.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
-
-							throw!(NoSuchField(
-								key.clone(),
-								heap.into_iter().map(|(_, v)| v).collect()
-							))
+				evaluate(s, ctx, index)?,
+			)?,
+			LocalExpr(bindings, returned) => {
+				ctx = evaluate_locals(ctx, bindings)?;
+				expr = returned;
+				continue;
+			}
+			Arr(items) => {
+				let mut out = Vec::with_capacity(items.len());
+				for item in items {
+					// TODO: Implement ArrValue::Lazy with same context for every element?
+					#[derive(Trace)]
+					struct ArrayElement {
+						ctx: Context,
+						item: LocExpr,
+					}
+					impl ThunkValue for ArrayElement {
+						type Output = Val;
+						fn get(self: Box<Self>, s: State) -> Result<Val> {
+							evaluate(s, self.ctx, &self.item)
 						}
-						Err(e) => Err(e),
-					},
-				)?,
-				(Val::Obj(_), n) => throw!(ValueIndexMustBeTypeGot(
-					ValType::Obj,
-					ValType::Str,
-					n.value_type(),
-				)),
-
-				(Val::Arr(v), Val::Num(n)) => {
-					if n.fract() > f64::EPSILON {
-						throw!(FractionalIndex)
 					}
-					v.get(s, n as usize)?
-						.ok_or_else(|| ArrayBoundsError(n as usize, v.len()))?
+					out.push(Thunk::new(tb!(ArrayElement {
+						ctx: ctx.clone(),
+						item: item.clone(),
+					})));
 				}
-				(Val::Arr(_), Val::Str(n)) => throw!(AttemptedIndexAnArrayWithString(n)),
-				(Val::Arr(_), n) => throw!(ValueIndexMustBeTypeGot(
-					ValType::Arr,
-					ValType::Num,
-					n.value_type(),
-				)),
-
-				(Val::Str(s), Val::Num(n)) => Val::Str({
-					let v: IStr = s
-						.chars()
-						.skip(n as usize)
-						.take(1)
-						.collect::<String>()
-						.into();
-					if v.is_empty() {
-						let size = s.chars().count();
-						throw!(StringBoundsError(n as usize, size))
-					}
-					v
-				}),
-				(Val::Str(_), n) => throw!(ValueIndexMustBeTypeGot(
-					ValType::Str,
-					ValType::Num,
-					n.value_type(),
-				)),
-
-				(v, _) => throw!(CantIndexInto(v.value_type())),
+				Val::Arr(out.into())
 			}
-		}
-		LocalExpr(bindings, returned) => {
-			let mut new_bindings: GcHashMap<IStr, Thunk<Val>> =
-				GcHashMap::with_capacity(bindings.len());
-			let fctx = Context::new_future();
-			for b in bindings {
-				evaluate_dest(b, fctx.clone(), &mut new_bindings)?;
+			ArrComp(expr, comp_specs) => {
+				let mut out = Vec::new();
+				evaluate_comp(s.clone(), ctx, comp_specs, &mut |ctx| {
+					out.push(evaluate(s.clone(), ctx, expr)?);
+					Ok(())
+				})?;
+				Val::Arr(ArrValue::Eager(Cc::new(out)))
 			}
-			let ctx = ctx.extend(new_bindings, None, None, None).into_future(fctx);
-			evaluate(s, ctx, &returned.clone())?
-		}
-		Arr(items) => {
-			let mut out = Vec::with_capacity(items.len());
-			for item in items {
-				// TODO: Implement ArrValue::Lazy with same context for every element?
-				#[derive(Trace)]
-				struct ArrayElement {
-					ctx: Context,
-					item: LocExpr,
-				}
-				impl ThunkValue for ArrayElement {
-					type Output = Val;
-					fn get(self: Box<Self>, s: State) -> Result<Val> {
-						evaluate(s, self.ctx, &self.item)
+			Obj(body) => Val::Obj(evaluate_object(s, ctx, body)?),
+			ObjExtend(a, b) => evaluate_add_op(
+				s.clone(),
+				&evaluate(s.clone(), ctx.clone(), a)?,
+				&Val::Obj(evaluate_object(s, ctx, b)?),
+			)?,
+			Apply(value, args, true) => match evaluate(s.clone(), ctx.clone(), value)? {
+				Val::Func(FuncVal::Normal(func)) => {
+					ctx = func.call_body_context(s.clone(), ctx, args, true)?;
+					if !in_tail_call {
+						return s.push(
+							CallLocation::new(loc),
+							|| format!("function <{}> call", func.name),
+							|| evaluate_inner(s.clone(), ctx, &func.body, true),
+						);
 					}
+					// Body of the called function replaces current expression
+					tail_body = func.body.clone();
+					expr = &tail_body;
+					continue;
 				}
-				out.push(Thunk::new(tb!(ArrayElement {
-					ctx: ctx.clone(),
-					item: item.clone(),
-				})));
-			}
-			Val::Arr(out.into())
-		}
-		ArrComp(expr, comp_specs) => {
-			let mut out = Vec::new();
-			evaluate_comp(s.clone(), ctx, comp_specs, &mut |ctx| {
-				out.push(evaluate(s.clone(), ctx, expr)?);
-				Ok(())
-			})?;
-			Val::Arr(ArrValue::Eager(Cc::new(out)))
-		}
-		Obj(body) => Val::Obj(evaluate_object(s, ctx, body)?),
-		ObjExtend(a, b) => evaluate_add_op(
-			s.clone(),
-			&evaluate(s.clone(), ctx.clone(), a)?,
-			&Val::Obj(evaluate_object(s, ctx, b)?),
-		)?,
-		Apply(value, args, tailstrict) => {
-			evaluate_apply(s, ctx, value, args, CallLocation::new(loc), *tailstrict)?
-		}
-		Function(params, body) => {
-			evaluate_method(ctx, "anonymous".into(), params.clone(), body.clone())
-		}
-		AssertExpr(assert, returned) => {
-			evaluate_assert(s.clone(), ctx.clone(), assert)?;
-			evaluate(s, ctx, returned)?
-		}
-		ErrorStmt(e) => s.push(
-			CallLocation::new(loc),
-			|| "error statement".to_owned(),
-			|| {
-				throw!(RuntimeError(
-					evaluate(s.clone(), ctx, e)?.to_string(s.clone())?,
-				))
+				Val::Func(f) => f.evaluate(s, ctx, CallLocation::new(loc), args, true)?,
+				v => throw!(OnlyFunctionsCanBeCalledGot(v.value_type())),
 			},
-		)?,
-		IfElse {
-			cond,
-			cond_then,
-			cond_else,
-		} => {
-			if s.push(
+			Apply(value, args, false) => {
+				evaluate_apply(s, ctx, value, args, CallLocation::new(loc), false)?
+			}
+			Function(params, body) => {
+				evaluate_method(ctx, "anonymous".into(), params.clone(), body.clone())
+			}
+			AssertExpr(assert, returned) => {
+				evaluate_assert(s.clone(), ctx.clone(), assert)?;
+				expr = returned;
+				continue;
+			}
+			ErrorStmt(e) => s.push(
 				CallLocation::new(loc),
-				|| "if condition".to_owned(),
-				|| bool::from_untyped(evaluate(s.clone(), ctx.clone(), &cond.0)?, s.clone()),
-			)? {
-				evaluate(s, ctx, cond_then)?
-			} else {
+				|| "error statement".to_owned(),
+				|| {
+					throw!(RuntimeError(
+						evaluate(s.clone(), ctx, e)?.to_string(s.clone())?,
+					))
+				},
+			)?,
+			IfElse {
+				cond,
+				cond_then,
+				cond_else,
+			} => {
+				if s.push(
+					CallLocation::new(loc),
+					|| "if condition".to_owned(),
+					|| bool::from_untyped(evaluate(s.clone(), ctx.clone(), &cond.0)?, s.clone()),
+				)? {
+					expr = cond_then;
+					continue;
+				}
 				match cond_else {
-					Some(v) => evaluate(s, ctx, v)?,
+					Some(v) => {
+						expr = v;
+						continue;
+					}
 					None => Val::Null,
 				}
 			}
-		}
-		Slice(value, desc) => {
-			fn parse_idx<T: Typed>(
-				loc: CallLocation<'_>,
-				s: State,
-				ctx: &Context,
-				expr: &Option<LocExpr>,
-				desc: &'static str,
-			) -> Result<Option<T>> {
-				if let Some(value) = expr {
-					Ok(Some(s.push(
-						loc,
-						|| format!("slice {desc}"),
-						|| T::from_untyped(evaluate(s.clone(), ctx.clone(), value)?, s.clone()),
-					)?))
-				} else {
-					Ok(None)
+			Slice(value, desc) => {
+				fn parse_idx<T: Typed>(
+					loc: CallLocation<'_>,
+					s: State,
+					ctx: &Context,
+					expr: &Option<LocExpr>,
+					desc: &'static str,
+				) -> Result<Option<T>> {
+					if let Some(value) = expr {
+						Ok(Some(s.push(
+							loc,
+							|| format!("slice {desc}"),
+							|| T::from_untyped(evaluate(s.clone(), ctx.clone(), value)?, s.clone()),
+						)?))
+					} else {
+						Ok(None)
+					}
 				}
-			}
 
-			let indexable = evaluate(s.clone(), ctx.clone(), value)?;
-			let loc = CallLocation::new(loc);
+				let indexable = evaluate(s.clone(), ctx.clone(), value)?;
+				let loc = CallLocation::new(loc);
 
-			let start = parse_idx(loc, s.clone(), &ctx, &desc.start, "start")?;
-			let end = parse_idx(loc, s.clone(), &ctx, &desc.end, "end")?;
-			let step = parse_idx(loc, s.clone(), &ctx, &desc.step, "step")?;
+				let start = parse_idx(loc, s.clone(), &ctx, &desc.start, "start")?;
+				let end = parse_idx(loc, s.clone(), &ctx, &desc.end, "end")?;
+				let step = parse_idx(loc, s.clone(), &ctx, &desc.step, "step")?;
 
-			IndexableVal::into_untyped(indexable.into_indexable()?.slice(start, end, step)?, s)?
-		}
-		i @ (Import(path) | ImportStr(path) | ImportBin(path)) => {
-			let tmp = loc.clone().0;
-			let resolved_path = s.resolve_from(tmp.source_path(), path as &str)?;
-			match i {
-				Import(_) => s.push(
-					CallLocation::new(loc),
-					|| format!("import {:?}", path.clone()),
-					|| s.import_resolved(resolved_path),
-				)?,
-				ImportStr(_) => Val::Str(s.import_resolved_str(resolved_path)?),
-				ImportBin(_) => Val::Arr(ArrValue::Bytes(s.import_resolved_bin(resolved_path)?)),
-				_ => unreachable!(),
+				IndexableVal::into_untyped(indexable.into_indexable()?.slice(start, end, step)?, s)?
 			}
-		}
-	})
+			i @ (Import(path) | ImportStr(path) | ImportBin(path)) => {
+				let tmp = loc.clone().0;
+				let resolved_path = s.resolve_from(tmp.source_path(), path as &str)?;
+				s.record_import(tmp.source_path(), &resolved_path);
+				match i {
+					Import(_) => s.push(
+						CallLocation::new(loc),
+						|| format!("import {:?}", path.clone()),
+						|| s.import_resolved(resolved_path),
+					)?,
+					ImportStr(_) => Val::Str(s.import_resolved_str(resolved_path)?.into()),
+					ImportBin(_) => {
+						Val::Arr(ArrValue::Bytes(s.import_resolved_bin(resolved_path)?))
+					}
+					_ => unreachable!(),
+				}
+			}
+		});
+	}
 }
@@ -3,17 +3,88 @@ use std::cmp::Ordering;
 use jrsonnet_parser::{BinaryOpType, LocExpr, UnaryOpType};
 
 use crate::{
-	error::Error::*, evaluate, stdlib::std_format, throw, typed::Typed, val::equals, Context,
-	Result, State, Val,
+	error::Error::*, evaluate, stdlib::std_format, throw, typed::Typed, val::equals, val::StrValue,
+	Context, Result, State, Val,
 };
 
+/// Exact integer value of number, `None` if it has fractional part
+fn int_operand(v: &Val) -> Option<crate::Int> {
+	match v {
+		Val::Int(i) => Some(i.clone()),
+		Val::Num(n) => crate::Int::from_exact_f64(*n),
+		_ => unreachable!("only numbers are passed"),
+	}
+}
+
+fn float_operand(v: &Val) -> f64 {
+	match v {
+		Val::Int(i) => i.to_f64(),
+		Val::Num(n) => *n,
+		_ => unreachable!("only numbers are passed"),
+	}
+}
+
+/// Arithmetic on numbers, at least one of which is [`Val::Int`]
+///
+/// Operation is exact, unless the other number has fractional part, or the result overflows
+fn evaluate_int_op(a: &Val, op: BinaryOpType, b: &Val) -> Result<Val> {
+	use BinaryOpType::*;
+	if let (Some(a), Some(b)) = (int_operand(a), int_operand(b)) {
+		let result = match op {
+			Add => a.checked_add(&b),
+			Sub => a.checked_sub(&b),
+			Mul => a.checked_mul(&b),
+			Mod => Some(a.checked_rem(&b)?),
+			Div => a.exact_div(&b)?,
+			_ => unreachable!("not an arithmetic operator"),
+		};
+		if let Some(result) = result {
+			return Ok(result.into_val());
+		}
+	}
+	let (a, b) = (float_operand(a), float_operand(b));
+	Val::new_checked_num(match op {
+		Add => a + b,
+		Sub => a - b,
+		Mul => a * b,
+		Div => a / b,
+		Mod => a % b,
+		_ => unreachable!("not an arithmetic operator"),
+	})
+}
+
+/// Bitwise operators work on 64 bit (or arbitrary precision, with `exp-bigint`) integers,
+/// fractional part of numbers is truncated
+fn evaluate_bitwise_op(a: &Val, op: BinaryOpType, b: &Val) -> Result<Val> {
+	use BinaryOpType::*;
+	let operand = |v: &Val| match v {
+		Val::Int(i) => Ok(i.clone()),
+		Val::Num(n) => crate::Int::from_f64(*n),
+		_ => unreachable!("only numbers are passed"),
+	};
+	let (a, b) = (operand(a)?, operand(b)?);
+	Ok(match op {
+		BitAnd => a.bit_and(&b),
+		BitOr => a.bit_or(&b),
+		BitXor => a.bit_xor(&b),
+		Lhs => a.checked_shl(&b)?,
+		Rhs => a.checked_shr(&b)?,
+		_ => unreachable!("not a bitwise operator"),
+	}
+	.into_val())
+}
+
 pub fn evaluate_unary_op(op: UnaryOpType, b: &Val) -> Result<Val> {
 	use UnaryOpType::*;
 	use Val::*;
 	Ok(match (op, b) {
 		(Not, Bool(v)) => Bool(!v),
 		(Minus, Num(n)) => Num(-*n),
-		(BitNot, Num(n)) => Num(f64::from(!(*n as i32))),
+		(Minus, Int(n)) => n
+			.checked_neg()
+			.map_or_else(|| Num(-n.to_f64()), crate::Int::into_val),
+		(BitNot, Num(n)) => crate::Int::from_f64(*n)?.bit_not().into_val(),
+		(BitNot, Int(n)) => n.bit_not().into_val(),
 		(op, o) => throw!(UnaryOperatorDoesNotOperateOnType(op, o.value_type())),
 	})
 }
@@ -21,17 +92,14 @@ pub fn evaluate_unary_op(op: UnaryOpType, b: &Val) -> Result<Val> {
 pub fn evaluate_add_op(s: State, a: &Val, b: &Val) -> Result<Val> {
 	use Val::*;
 	Ok(match (a, b) {
-		(Str(a), Str(b)) if a.is_empty() => Val::Str(b.clone()),
-		(Str(a), Str(b)) if b.is_empty() => Val::Str(a.clone()),
-		(Str(v1), Str(v2)) => Str(((**v1).to_owned() + v2).into()),
+		(Str(v1), Str(v2)) => Str(StrValue::concat(v1.clone(), v2.clone())),
 
 		// Can't use generic json serialization way, because it depends on number to string concatenation (std.jsonnet:890)
-		(Num(a), Str(b)) => Str(format!("{a}{b}").into()),
-		(Str(a), Num(b)) => Str(format!("{a}{b}").into()),
+		(Num(a), Str(b)) => Str(StrValue::concat(a.to_string().into(), b.clone())),
+		(Str(a), Num(b)) => Str(StrValue::concat(a.clone(), b.to_string().into())),
 
-		(Str(a), o) | (o, Str(a)) if a.is_empty() => Val::Str(o.clone().to_string(s)?),
-		(Str(a), o) => Str(format!("{a}{}", o.clone().to_string(s)?).into()),
-		(o, Str(a)) => Str(format!("{}{a}", o.clone().to_string(s)?).into()),
+		(Str(a), o) => Str(StrValue::concat(a.clone(), o.clone().to_string(s)?.into())),
+		(o, Str(a)) => Str(StrValue::concat(o.clone().to_string(s)?.into(), a.clone())),
 
 		(Obj(v1), Obj(v2)) => Obj(v2.extend_from(v1.clone())),
 		(Arr(a), Arr(b)) => {
@@ -41,6 +109,7 @@ pub fn evaluate_add_op(s: State, a: &Val, b: &Val) -> Result<Val> {
 			Arr(out.into())
 		}
 		(Num(v1), Num(v2)) => Val::new_checked_num(v1 + v2)?,
+		(Int(_), Num(_) | Int(_)) | (Num(_), Int(_)) => evaluate_int_op(a, BinaryOpType::Add, b)?,
 		_ => throw!(BinaryOperatorDoesNotOperateOnValues(
 			BinaryOpType::Add,
 			a.value_type(),
@@ -58,9 +127,11 @@ pub fn evaluate_mod_op(s: State, a: &Val, b: &Val) -> Result<Val> {
 			}
 			Ok(Num(a % b))
 		}
-		(Str(str), vals) => {
-			String::into_untyped(std_format(s.clone(), str.clone(), vals.clone())?, s)
-		}
+		(Int(_), Num(_) | Int(_)) | (Num(_), Int(_)) => evaluate_int_op(a, BinaryOpType::Mod, b),
+		(Str(str), vals) => String::into_untyped(
+			std_format(s.clone(), str.clone().into_flat(), vals.clone())?,
+			s,
+		),
 		(a, b) => throw!(BinaryOperatorDoesNotOperateOnValues(
 			BinaryOpType::Mod,
 			a.value_type(),
@@ -90,6 +161,9 @@ pub fn evaluate_compare_op(s: State, a: &Val, op: BinaryOpType, b: &Val) -> Resu
 	Ok(match (a, b) {
 		(Str(a), Str(b)) => a.cmp(b),
 		(Num(a), Num(b)) => a.partial_cmp(b).expect("jsonnet numbers are non NaN"),
+		(Int(a), Int(b)) => a.cmp(b),
+		(Int(a), Num(b)) => a.cmp_f64(*b),
+		(Num(a), Int(b)) => b.cmp_f64(*a).reverse(),
 		(Arr(a), Arr(b)) => {
 			let ai = a.iter(s.clone());
 			let bi = b.iter(s.clone());
@@ -125,10 +199,10 @@ pub fn evaluate_binary_op_normal(s: State, a: &Val, op: BinaryOpType, b: &Val) -
 		(a, Lte, b) => Bool(evaluate_compare_op(s, a, Lte, b)?.is_le()),
 		(a, Gte, b) => Bool(evaluate_compare_op(s, a, Gte, b)?.is_ge()),
 
-		(Str(a), In, Obj(obj)) => Bool(obj.has_field_ex(a.clone(), true)),
+		(Str(a), In, Obj(obj)) => Bool(obj.has_field_ex(a.clone().into_flat(), true)),
 		(a, Mod, b) => evaluate_mod_op(s, a, b)?,
 
-		(Str(v1), Mul, Num(v2)) => Str(v1.repeat(*v2 as usize).into()),
+		(Str(v1), Mul, Num(v2)) => Str(v1.clone().into_flat().repeat(*v2 as usize).into()),
 
 		// Bool X Bool
 		(Bool(a), And, Bool(b)) => Bool(*a && *b),
@@ -145,20 +219,12 @@ pub fn evaluate_binary_op_normal(s: State, a: &Val, op: BinaryOpType, b: &Val) -
 
 		(Num(v1), Sub, Num(v2)) => Val::new_checked_num(v1 - v2)?,
 
-		(Num(v1), BitAnd, Num(v2)) => Num(f64::from((*v1 as i32) & (*v2 as i32))),
-		(Num(v1), BitOr, Num(v2)) => Num(f64::from((*v1 as i32) | (*v2 as i32))),
-		(Num(v1), BitXor, Num(v2)) => Num(f64::from((*v1 as i32) ^ (*v2 as i32))),
-		(Num(v1), Lhs, Num(v2)) => {
-			if *v2 < 0.0 {
-				throw!(RuntimeError("shift by negative exponent".into()))
-			}
-			Num(f64::from((*v1 as i32) << (*v2 as i32)))
+		(Int(_), Sub | Mul | Div, Num(_) | Int(_)) | (Num(_), Sub | Mul | Div, Int(_)) => {
+			evaluate_int_op(a, op, b)?
 		}
-		(Num(v1), Rhs, Num(v2)) => {
-			if *v2 < 0.0 {
-				throw!(RuntimeError("shift by negative exponent".into()))
-			}
-			Num(f64::from((*v1 as i32) >> (*v2 as i32)))
+
+		(Num(_) | Int(_), BitAnd | BitOr | BitXor | Lhs | Rhs, Num(_) | Int(_)) => {
+			evaluate_bitwise_op(a, op, b)?
 		}
 
 		_ => throw!(BinaryOperatorDoesNotOperateOnValues(
@@ -1,5 +1,3 @@
-use std::mem::replace;
-
 use jrsonnet_gcmodule::Trace;
 use jrsonnet_interner::IStr;
 use jrsonnet_parser::{LocExpr, ParamsDesc};
@@ -9,7 +7,7 @@ use crate::{
 	destructure::destruct,
 	error::{Error::*, Result},
 	evaluate_named,
-	gc::GcHashMap,
+	map::Bindings,
 	tb, throw,
 	val::ThunkValue,
 	Context, Pending, State, Thunk, Val,
@@ -45,7 +43,6 @@ pub fn parse_function_call(
 	args: &dyn ArgsLike,
 	tailstrict: bool,
 ) -> Result<Context> {
-	let mut passed_args = GcHashMap::with_capacity(params.len());
 	if args.unnamed_len() > params.len() {
 		throw!(TooManyArgsFunctionHas(
 			params.len(),
@@ -53,93 +50,51 @@ pub fn parse_function_call(
 		))
 	}
 
-	let mut filled_named = 0;
-	let mut filled_positionals = 0;
+	let mut passed_args: Vec<Option<Thunk<Val>>> = vec![None; params.len()];
 
 	args.unnamed_iter(s.clone(), ctx.clone(), tailstrict, &mut |id, arg| {
-		let name = params[id].0.clone();
-		destruct(
-			&name,
-			arg,
-			Pending::new_filled(ctx.clone()),
-			&mut passed_args,
-		)?;
-		filled_positionals += 1;
+		passed_args[id] = Some(arg);
 		Ok(())
 	})?;
 
 	args.named_iter(s, ctx, tailstrict, &mut |name, value| {
 		// FIXME: O(n) for arg existence check
-		if !params.iter().any(|p| p.0.name().as_ref() == Some(name)) {
+		let Some(id) = params
+			.iter()
+			.position(|p| p.0.name().as_ref() == Some(name))
+		else {
 			throw!(UnknownFunctionParameter((name as &str).to_owned()));
-		}
-		if passed_args.insert(name.clone(), value).is_some() {
+		};
+		if passed_args[id].replace(value).is_some() {
 			throw!(BindingParameterASecondTime(name.clone()));
 		}
-		filled_named += 1;
 		Ok(())
 	})?;
 
-	if filled_named + filled_positionals < params.len() {
-		// Some args are unset, but maybe we have defaults for them
-		// Default values should be created in newly created context
-		let fctx = Context::new_future();
-		let mut defaults =
-			GcHashMap::with_capacity(params.len() - filled_named - filled_positionals);
-
-		for (idx, param) in params.iter().enumerate().filter(|p| p.1 .1.is_some()) {
-			if let Some(name) = param.0.name() {
-				if passed_args.contains_key(&name) {
-					continue;
-				}
-			} else if idx < filled_positionals {
-				continue;
-			}
-
-			destruct(
-				&param.0,
-				Thunk::new(tb!(EvaluateNamedThunk {
-					ctx: fctx.clone(),
-					name: param.0.name().unwrap_or_else(|| "<destruct>".into()),
-					value: param.1.clone().expect("default exists"),
-				})),
-				fctx.clone(),
-				&mut defaults,
-			)?;
-			if param.0.name().is_some() {
-				filled_named += 1;
-			} else {
-				filled_positionals += 1;
-			}
-		}
-
-		// Some args still weren't filled
-		if filled_named + filled_positionals != params.len() {
-			for param in params.iter().skip(args.unnamed_len()) {
-				let mut found = false;
-				args.named_names(&mut |name| {
-					if Some(name) == param.0.name().as_ref() {
-						found = true;
-					}
-				});
-				if !found {
-					throw!(FunctionParameterNotBoundInCall(
-						param.0.clone().name(),
-						params.iter().map(|p| (p.0.name(), p.1.is_some())).collect()
-					));
-				}
-			}
-			unreachable!();
-		}
-
-		Ok(body_ctx
-			.extend(passed_args, None, None, None)
-			.extend(defaults, None, None, None)
-			.into_future(fctx))
-	} else {
-		let body_ctx = body_ctx.extend(passed_args, None, None, None);
-		Ok(body_ctx)
+	// Parameters and their default values share single scope, which is created in newly created context
+	let fctx = Context::new_future();
+	let mut bindings = Bindings::with_capacity(params.len());
+	for (param, arg) in params.iter().zip(passed_args) {
+		let value = if let Some(arg) = arg {
+			arg
+		} else if let Some(default) = &param.1 {
+			Thunk::new(tb!(EvaluateNamedThunk {
+				ctx: fctx.clone(),
+				name: param.0.name().unwrap_or_else(|| "<destruct>".into()),
+				value: default.clone(),
+			}))
+		} else {
+			throw!(FunctionParameterNotBoundInCall(
+				param.0.name(),
+				params.iter().map(|p| (p.0.name(), p.1.is_some())).collect()
+			));
+		};
+		destruct(&param.0, value, fctx.clone(), &mut bindings)?;
 	}
+
+	Ok(body_ctx
+		.extend(bindings, None, None, None)
+		.into_future(fctx))
 }
 
 /// You shouldn't probally use this function, use `jrsonnet_macros::builtin` instead
@@ -181,7 +136,7 @@ pub fn parse_builtin_call(
 			.iter()
 			.position(|p| p.name.as_ref().map_or(false, |v| v as &str == name as &str))
 			.ok_or_else(|| UnknownFunctionParameter((name as &str).to_owned()))?;
-		if replace(&mut passed_args[id], Some(arg)).is_some() {
+		if passed_args[id].replace(arg).is_some() {
 			throw!(BindingParameterASecondTime(name.clone()));
 		}
 		filled_args += 1;
@@ -243,7 +198,7 @@ pub fn parse_default_function_call(body_ctx: Context, params: &ParamsDesc) -> Re
 
 	let fctx = Context::new_future();
 
-	let mut bindings = GcHashMap::new();
+	let mut bindings = Bindings::with_capacity(params.len());
 
 	for param in params.iter() {
 		if let Some(v) = &param.1 {
@@ -0,0 +1,296 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::{error::Error::*, throw, Result, Val};
+
+/// All integers in `-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER` are exactly representable by `f64`
+pub const MAX_SAFE_INTEGER: i64 = 1 << 53;
+
+#[cfg(not(feature = "exp-bigint"))]
+type Repr = i64;
+#[cfg(feature = "exp-bigint")]
+type Repr = Box<num_bigint::BigInt>;
+
+/// Exact integer, which is out of `f64` safe integer range
+///
+/// Produced by bitwise operators and `std.parseInt`-like functions, arithmetic on them is exact.
+/// Integers are limited to `i64`, unless the `exp-bigint` feature is enabled, in which case they have
+/// arbitrary precision. Overflowing arithmetic falls back to `f64`, left shift of `i64` wraps around,
+/// as in other jsonnet implementations.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Int(Repr);
+
+impl Int {
+	/// Converts integer to value, integers in safe range are represented by [`Val::Num`]
+	pub fn into_val(self) -> Val {
+		match self.to_i64() {
+			Some(v) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&v) => Val::Num(v as f64),
+			_ => Val::Int(self),
+		}
+	}
+
+	/// Exact integer value of number, `None` if number has fractional part
+	pub fn from_exact_f64(v: f64) -> Option<Self> {
+		if v.fract() != 0.0 {
+			return None;
+		}
+		Self::from_f64(v).ok()
+	}
+
+	/// Numeric comparison with `f64`
+	pub fn cmp_f64(&self, other: f64) -> Ordering {
+		Self::from_exact_f64(other)
+			.map_or_else(|| self.to_f64().total_cmp(&other), |other| self.cmp(&other))
+	}
+
+	/// Parses integer with optional `-` sign, `None` if it is out of supported range
+	pub fn from_str_radix(str: &str, radix: u32) -> Result<Option<Self>> {
+		if str.starts_with('+') {
+			throw!(RuntimeError(
+				format!("{str} is not a base {radix} integer").into()
+			))
+		}
+		Self::parse(str, radix)
+	}
+
+	/// Shifts left, negative shift amounts are disallowed
+	pub fn checked_shl(&self, amount: &Self) -> Result<Self> {
+		if amount.is_negative() {
+			throw!(RuntimeError("shift by negative exponent".into()))
+		}
+		#[cfg(not(feature = "exp-bigint"))]
+		return Ok(self.shl(amount));
+		#[cfg(feature = "exp-bigint")]
+		return self.shl(amount);
+	}
+
+	/// Arithmetic right shift, negative shift amounts are disallowed
+	pub fn checked_shr(&self, amount: &Self) -> Result<Self> {
+		if amount.is_negative() {
+			throw!(RuntimeError("shift by negative exponent".into()))
+		}
+		Ok(self.shr(amount))
+	}
+
+	/// Division, `None` if the result is not an integer, or overflows
+	pub fn exact_div(&self, other: &Self) -> Result<Option<Self>> {
+		if other.is_zero() {
+			throw!(DivisionByZero)
+		}
+		if !self.checked_rem(other)?.is_zero() {
+			return Ok(None);
+		}
+		Ok(self.checked_div(other))
+	}
+}
+
+#[cfg(not(feature = "exp-bigint"))]
+impl Int {
+	/// Truncates fractional part
+	pub fn from_f64(v: f64) -> Result<Self> {
+		let v = v.trunc();
+		// i64::MIN is exactly representable by f64, i64::MAX is rounded up
+		if v < i64::MIN as f64 || v >= i64::MAX as f64 {
+			throw!(IntegerOverflow)
+		}
+		Ok(Self(v as i64))
+	}
+	pub const fn to_f64(&self) -> f64 {
+		self.0 as f64
+	}
+	pub const fn to_i64(&self) -> Option<i64> {
+		Some(self.0)
+	}
+	pub const fn is_zero(&self) -> bool {
+		self.0 == 0
+	}
+	pub const fn is_negative(&self) -> bool {
+		self.0 < 0
+	}
+
+	fn parse(str: &str, radix: u32) -> Result<Option<Self>> {
+		use std::num::IntErrorKind;
+		match i64::from_str_radix(str, radix) {
+			Ok(v) => Ok(Some(Self(v))),
+			Err(e)
+				if matches!(
+					e.kind(),
+					IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+				) =>
+			{
+				Ok(None)
+			}
+			Err(_) => throw!(RuntimeError(
+				format!("{str} is not a base {radix} integer").into()
+			)),
+		}
+	}
+
+	pub fn checked_add(&self, other: &Self) -> Option<Self> {
+		self.0.checked_add(other.0).map(Self)
+	}
+	pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+		self.0.checked_sub(other.0).map(Self)
+	}
+	pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+		self.0.checked_mul(other.0).map(Self)
+	}
+	fn checked_div(&self, other: &Self) -> Option<Self> {
+		self.0.checked_div(other.0).map(Self)
+	}
+	/// Remainder, which has the sign of dividend
+	pub fn checked_rem(&self, other: &Self) -> Result<Self> {
+		if other.is_zero() {
+			throw!(DivisionByZero)
+		}
+		// i64::MIN % -1 overflows, while the result is representable
+		Ok(Self(self.0.checked_rem(other.0).unwrap_or(0)))
+	}
+	pub fn checked_neg(&self) -> Option<Self> {
+		self.0.checked_neg().map(Self)
+	}
+
+	#[must_use]
+	pub const fn bit_and(&self, other: &Self) -> Self {
+		Self(self.0 & other.0)
+	}
+	#[must_use]
+	pub const fn bit_or(&self, other: &Self) -> Self {
+		Self(self.0 | other.0)
+	}
+	#[must_use]
+	pub const fn bit_xor(&self, other: &Self) -> Self {
+		Self(self.0 ^ other.0)
+	}
+	#[must_use]
+	pub const fn bit_not(&self) -> Self {
+		Self(!self.0)
+	}
+	/// Bits shifted out are discarded, shift by 64 or more bits produces 0
+	fn shl(&self, amount: &Self) -> Self {
+		Self(
+			u32::try_from(amount.0)
+				.ok()
+				.and_then(|amount| self.0.checked_shl(amount))
+				.unwrap_or(0),
+		)
+	}
+	fn shr(&self, amount: &Self) -> Self {
+		Self(self.0 >> amount.0.min(63))
+	}
+}
+
+#[cfg(feature = "exp-bigint")]
+impl Int {
+	/// Truncates fractional part
+	pub fn from_f64(v: f64) -> Result<Self> {
+		use num_traits::FromPrimitive;
+		let v = num_bigint::BigInt::from_f64(v.trunc()).ok_or(IntegerOverflow)?;
+		Ok(Self(Box::new(v)))
+	}
+	pub fn to_f64(&self) -> f64 {
+		use num_traits::ToPrimitive;
+		self.0.to_f64().unwrap_or_else(|| {
+			if self.is_negative() {
+				f64::NEG_INFINITY
+			} else {
+				f64::INFINITY
+			}
+		})
+	}
+	pub fn to_i64(&self) -> Option<i64> {
+		use num_traits::ToPrimitive;
+		self.0.to_i64()
+	}
+	pub fn is_zero(&self) -> bool {
+		use num_traits::Zero;
+		self.0.is_zero()
+	}
+	pub fn is_negative(&self) -> bool {
+		self.0.sign() == num_bigint::Sign::Minus
+	}
+
+	#[allow(clippy::unnecessary_wraps)]
+	fn parse(str: &str, radix: u32) -> Result<Option<Self>> {
+		match num_bigint::BigInt::parse_bytes(str.as_bytes(), radix) {
+			Some(v) => Ok(Some(Self(Box::new(v)))),
+			None => throw!(RuntimeError(
+				format!("{str} is not a base {radix} integer").into()
+			)),
+		}
+	}
+
+	#[allow(clippy::unnecessary_wraps)]
+	pub fn checked_add(&self, other: &Self) -> Option<Self> {
+		Some(Self(Box::new(&*self.0 + &*other.0)))
+	}
+	#[allow(clippy::unnecessary_wraps)]
+	pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+		Some(Self(Box::new(&*self.0 - &*other.0)))
+	}
+	#[allow(clippy::unnecessary_wraps)]
+	pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+		Some(Self(Box::new(&*self.0 * &*other.0)))
+	}
+	#[allow(clippy::unnecessary_wraps)]
+	fn checked_div(&self, other: &Self) -> Option<Self> {
+		Some(Self(Box::new(&*self.0 / &*other.0)))
+	}
+	/// Remainder, which has the sign of dividend
+	pub fn checked_rem(&self, other: &Self) -> Result<Self> {
+		if other.is_zero() {
+			throw!(DivisionByZero)
+		}
+		Ok(Self(Box::new(&*self.0 % &*other.0)))
+	}
+	#[allow(clippy::unnecessary_wraps)]
+	pub fn checked_neg(&self) -> Option<Self> {
+		Some(Self(Box::new(-&*self.0)))
+	}
+
+	#[must_use]
+	pub fn bit_and(&self, other: &Self) -> Self {
+		Self(Box::new(&*self.0 & &*other.0))
+	}
+	#[must_use]
+	pub fn bit_or(&self, other: &Self) -> Self {
+		Self(Box::new(&*self.0 | &*other.0))
+	}
+	#[must_use]
+	pub fn bit_xor(&self, other: &Self) -> Self {
+		Self(Box::new(&*self.0 ^ &*other.0))
+	}
+	#[must_use]
+	pub fn bit_not(&self) -> Self {
+		Self(Box::new(!&*self.0))
+	}
+	fn shl(&self, amount: &Self) -> Result<Self> {
+		use num_traits::ToPrimitive;
+		if self.is_zero() {
+			return Ok(self.clone());
+		}
+		let Some(amount) = amount.0.to_u32() else {
+			throw!(IntegerOverflow)
+		};
+		Ok(Self(Box::new(&*self.0 << amount)))
+	}
+	fn shr(&self, amount: &Self) -> Self {
+		use num_traits::ToPrimitive;
+		let amount = amount.0.to_u64().unwrap_or(u64::MAX);
+		Self(Box::new(&*self.0 >> amount))
+	}
+}
+
+impl From<i64> for Int {
+	fn from(v: i64) -> Self {
+		#[cfg(not(feature = "exp-bigint"))]
+		return Self(v);
+		#[cfg(feature = "exp-bigint")]
+		return Self(Box::new(v.into()));
+	}
+}
+
+impl fmt::Display for Int {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
@@ -0,0 +1,70 @@
+use jsonschema::Validator;
+use serde_json::Value;
+
+use crate::{
+	error::{Error::*, Result},
+	throw,
+	typed::{TypeError, TypeLocError, TypeLocErrorList, Typed, ValuePathItem, ValuePathStack},
+	State, Val,
+};
+
+/// Compiled JSON Schema (draft 2020-12) document
+///
+/// Only schemas without external references are supported
+pub struct JsonSchema(Validator);
+impl JsonSchema {
+	pub fn new(schema: &Value) -> Result<Self> {
+		Ok(Self(
+			jsonschema::draft202012::new(schema).map_err(|e| InvalidJsonSchema(e.to_string()))?,
+		))
+	}
+	/// Parses schema from its JSON source
+	pub fn parse(schema: &str) -> Result<Self> {
+		let schema: Value =
+			serde_json::from_str(schema).map_err(|e| InvalidJsonSchema(e.to_string()))?;
+		Self::new(&schema)
+	}
+
+	/// Converts value to JSON and checks it against schema
+	pub fn validate(&self, s: State, val: Val) -> Result<()> {
+		let instance = Value::from_untyped(val, s)?;
+		let errors: Vec<_> = self
+			.0
+			.iter_errors(&instance)
+			.map(|e| {
+				TypeLocError::new(
+					TypeError::SchemaViolation(e.to_string()),
+					value_path(&instance, e.instance_path.as_str()),
+				)
+			})
+			.collect();
+		if !errors.is_empty() {
+			throw!(SchemaValidationFailed(TypeLocErrorList(errors)));
+		}
+		Ok(())
+	}
+}
+
+/// Converts JSON pointer to the same path format, as used by type checking errors
+///
+/// Pointer doesn't distinguish between array indexes and numeric field names, so value is
+/// walked alongside the pointer
+fn value_path(instance: &Value, pointer: &str) -> ValuePathStack {
+	let mut current = Some(instance);
+	let mut path = Vec::new();
+	for segment in pointer.split('/').skip(1) {
+		let segment = segment.replace("~1", "/").replace("~0", "~");
+		match (current, segment.parse::<usize>()) {
+			(Some(Value::Array(arr)), Ok(idx)) => {
+				current = arr.get(idx);
+				path.push(ValuePathItem::Index(idx as u64));
+			}
+			(value, _) => {
+				current = value.and_then(|v| v.get(segment.as_str()));
+				path.push(ValuePathItem::Field(segment.into()));
+			}
+		}
+	}
+	path.reverse();
+	ValuePathStack(path)
+}
@@ -1 +1,3 @@
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
 pub mod serde;
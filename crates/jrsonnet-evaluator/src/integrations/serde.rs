@@ -5,7 +5,7 @@ use crate::{
 	error::{Error::*, Result},
 	throw,
 	typed::Typed,
-	ObjValueBuilder, State, Val,
+	Int, ObjValueBuilder, State, Val,
 };
 
 impl Typed for Value {
@@ -15,9 +15,12 @@ impl Typed for Value {
 		Ok(match value {
 			Self::Null => Val::Null,
 			Self::Bool(v) => Val::Bool(v),
-			Self::Number(n) => Val::Num(n.as_f64().ok_or_else(|| {
-				RuntimeError(format!("json number can't be represented as jsonnet: {n}").into())
-			})?),
+			Self::Number(n) => match n.as_i64() {
+				Some(n) => Int::from(n).into_val(),
+				None => Val::Num(n.as_f64().ok_or_else(|| {
+					RuntimeError(format!("json number can't be represented as jsonnet: {n}").into())
+				})?),
+			},
 			Self::String(s) => Val::Str((&s as &str).into()),
 			Self::Array(a) => {
 				let mut out: Vec<Val> = Vec::with_capacity(a.len());
@@ -42,12 +45,17 @@ impl Typed for Value {
 		Ok(match value {
 			Val::Bool(b) => Self::Bool(b),
 			Val::Null => Self::Null,
-			Val::Str(s) => Self::String((&s as &str).into()),
+			Val::Str(s) => Self::String((&s.into_flat() as &str).into()),
 			Val::Num(n) => Self::Number(if n.fract() <= f64::EPSILON {
 				(n as i64).into()
 			} else {
 				Number::from_f64(n).expect("jsonnet numbers can't be infinite or NaN")
 			}),
+			// Without `arbitrary_precision` feature of serde_json, only 64 bit integers are lossless
+			Val::Int(n) => Self::Number(n.to_i64().map_or_else(
+				|| Number::from_f64(n.to_f64()).expect("integers are finite"),
+				Into::into,
+			)),
 			Val::Arr(a) => {
 				let mut out = Vec::with_capacity(a.len());
 				for item in a.iter(s.clone()) {
@@ -48,9 +48,12 @@ mod evaluate;
 pub mod function;
 pub mod gc;
 mod import;
+mod int;
 mod integrations;
 mod map;
 mod obj;
+mod optimize;
+mod select;
 pub mod stdlib;
 pub mod trace;
 pub mod typed;
@@ -70,14 +73,20 @@ pub use dynamic::*;
 use error::{Error::*, LocError, Result, StackTraceElement};
 pub use evaluate::*;
 use function::{CallLocation, TlaArg};
-use gc::{GcHashMap, TraceBox};
+use gc::{GcHashMap, GcHashSet, TraceBox};
 use hashbrown::hash_map::RawEntryMut;
 pub use import::*;
+pub use int::Int;
+#[cfg(feature = "json-schema")]
+pub use integrations::json_schema::JsonSchema;
 use jrsonnet_gcmodule::{Cc, Trace};
 pub use jrsonnet_interner::{IBytes, IStr};
 pub use jrsonnet_parser as parser;
 use jrsonnet_parser::*;
+pub use map::Bindings;
 pub use obj::*;
+pub use optimize::optimize;
+pub use select::*;
 use trace::{CompactFormat, TraceFormat};
 pub use val::{ManifestFormat, Thunk, Val};
 
@@ -141,6 +150,34 @@ impl ContextInitializer for DummyContextInitializer {
 	}
 }
 
+/// Native stack, which should be available to code between two jsonnet stack frames
+const STACK_RED_ZONE: usize = 1024 * 1024;
+/// Size of native stack segment, allocated when less than [`STACK_RED_ZONE`] is left
+const STACK_SEGMENT: usize = 8 * 1024 * 1024;
+
+/// Minimal number of stack frames between two checks of tracked object count, counting is
+/// proportional to the number of objects, so the interval grows with it
+const GC_CHECK_INTERVAL: usize = 4096;
+/// Smallest accepted [`EvaluationSettings::gc_growth_trigger`], lower values (and NaN) would make
+/// collection run on every check
+const MIN_GC_GROWTH_TRIGGER: f64 = 1.1;
+
+/// Number of tracked objects, which triggers the next collection
+fn next_gc_threshold(tracked: usize, growth_trigger: f64) -> usize {
+	let growth_trigger = if growth_trigger >= MIN_GC_GROWTH_TRIGGER {
+		growth_trigger
+	} else {
+		MIN_GC_GROWTH_TRIGGER
+	};
+	((tracked as f64 * growth_trigger) as usize).max(tracked + 1)
+}
+
+/// Runs code on a new native stack segment, if current one is close to exhaustion,
+/// so recursion depth is only limited by [`EvaluationSettings::max_stack`]
+fn grow_stack<T>(f: impl FnOnce() -> T) -> T {
+	stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, f)
+}
+
 /// Dynamically reconfigurable evaluation settings
 pub struct EvaluationSettings {
 	/// Limits recursion by limiting the number of stack frames
@@ -158,6 +195,14 @@ pub struct EvaluationSettings {
 	pub manifest_format: ManifestFormat,
 	/// Used for bindings
 	pub trace_format: Box<dyn TraceFormat>,
+	/// Simplify code with [`optimize`] before evaluation
+	pub optimize: bool,
+	/// Collect reference cycles during evaluation, once the number of tracked objects reaches this value,
+	/// `None` disables collection, so garbage is only freed by embedder
+	pub gc_min_objects: Option<usize>,
+	/// After collection, next one is triggered once the number of tracked objects grows by this factor.
+	/// Values below 1.1, and NaN, are treated as 1.1
+	pub gc_growth_trigger: f64,
 }
 impl Default for EvaluationSettings {
 	fn default() -> Self {
@@ -176,6 +221,9 @@ impl Default for EvaluationSettings {
 				padding: 4,
 				resolver: trace::PathResolver::Absolute,
 			}),
+			optimize: false,
+			gc_min_objects: None,
+			gc_growth_trigger: 2.0,
 		}
 	}
 }
@@ -191,11 +239,20 @@ struct EvaluationData {
 
 	/// Contains file source codes and evaluation results for imports and pretty-printed stacktraces
 	files: GcHashMap<SourcePath, FileData>,
+	/// Reverse import graph: for every imported file, files which have imported it
+	/// Used to cascade cache invalidation
+	importers: GcHashMap<SourcePath, GcHashSet<SourcePath>>,
+
+	/// Value of `stack_generation`, at which number of tracked objects should be checked
+	gc_next_check: usize,
+	/// Number of tracked objects, which triggers cycle collection
+	gc_threshold: usize,
 }
 struct FileData {
 	string: Option<IStr>,
 	bytes: Option<IBytes>,
-	parsed: Option<LocExpr>,
+	/// Prepared code, and scopes of context it was resolved against
+	parsed: Option<(LocExpr, Vec<Vec<IStr>>)>,
 	evaluated: Option<Val>,
 
 	evaluating: bool,
@@ -221,35 +278,52 @@ impl FileData {
 	}
 }
 
-#[allow(clippy::type_complexity)]
+/// Records values of expressions, which start in the specified location range
 pub struct Breakpoint {
 	loc: ExprLocation,
-	collected: RefCell<HashMap<usize, (usize, Vec<Result<Val>>)>>,
+	/// Locations and stack depths of matching expressions, which are currently being evaluated
+	active: RefCell<Vec<(u32, u32, usize)>>,
+	collected: RefCell<Vec<Result<Val>>>,
+}
+impl Breakpoint {
+	/// Range of offsets, at which recorded expressions may start
+	pub fn location(&self) -> &ExprLocation {
+		&self.loc
+	}
+	/// Values and errors, produced by the outermost expressions starting at breakpoint location,
+	/// in order of evaluation completion
+	pub fn take_collected(&self) -> Vec<Result<Val>> {
+		std::mem::take(&mut self.collected.borrow_mut())
+	}
+
+	fn leave(&self, result: &Result<Val>) {
+		self.active.borrow_mut().pop();
+		self.collected.borrow_mut().push(result.clone());
+	}
 }
 #[derive(Default)]
 struct Breakpoints(Vec<Rc<Breakpoint>>);
 impl Breakpoints {
-	fn insert(
-		&self,
-		stack_depth: usize,
-		stack_generation: usize,
-		loc: &ExprLocation,
-		result: Result<Val>,
-	) -> Result<Val> {
-		if self.0.is_empty() {
-			return result;
-		}
+	/// Every breakpoint is entered independently, as their locations may overlap
+	fn enter(&self, stack_depth: usize, loc: &ExprLocation) -> Vec<Rc<Breakpoint>> {
+		let mut entered = Vec::new();
 		for item in &self.0 {
-			if item.loc.belongs_to(loc) {
-				let mut collected = item.collected.borrow_mut();
-				let (depth, vals) = collected.entry(stack_generation).or_default();
-				if stack_depth > *depth {
-					vals.clear();
+			if !(item.loc.1..item.loc.2).contains(&loc.1) || item.loc.0 != loc.0 {
+				continue;
+			}
+			let mut active = item.active.borrow_mut();
+			if let Some(&(start, end, depth)) = active.last() {
+				// Value of enclosing expression is already recorded, unless this is the same
+				// expression evaluated again by recursive call
+				let same = start == loc.1 && end == loc.2;
+				if start <= loc.1 && loc.2 <= end && (!same || depth == stack_depth) {
+					continue;
 				}
-				vals.push(result.clone());
 			}
+			active.push((loc.1, loc.2, stack_depth));
+			entered.push(item.clone());
 		}
-		result
+		entered
 	}
 }
 
@@ -364,9 +438,14 @@ impl State {
 		}
 		let code = file.string.as_ref().expect("just set");
 		let file_name = Source::new(path.clone(), code.clone());
-		if file.parsed.is_none() {
-			file.parsed = Some(
-				jrsonnet_parser::parse(
+		let ctx = self.create_default_context(file_name.clone());
+		let scopes = ctx.binding_layers();
+		// Variable slots are only valid for the same context shape, which may change i.e with new globals
+		let (parsed, prepared) =
+			if let Some((parsed, _)) = file.parsed.as_ref().filter(|(_, s)| *s == scopes) {
+				(parsed.clone(), true)
+			} else {
+				let parsed = jrsonnet_parser::parse(
 					code,
 					&ParserSettings {
 						file_name: file_name.clone(),
@@ -375,30 +454,37 @@ impl State {
 				.map_err(|e| ImportSyntaxError {
 					path: file_name.clone(),
 					error: Box::new(e),
-				})?,
-			);
-		}
-		let parsed = file.parsed.as_ref().expect("just set").clone();
+				})?;
+				(parsed, false)
+			};
 		if file.evaluating {
 			throw!(InfiniteRecursionDetected)
 		}
 		file.evaluating = true;
 		// Dropping file here, as it borrows data, which may be used in evaluation
 		drop(data);
-		let res = evaluate(
-			self.clone(),
-			self.create_default_context(file_name),
-			&parsed,
-		);
+		let parsed = if prepared {
+			Ok(parsed)
+		} else {
+			self.prepare_code(parsed, &ctx, &scopes)
+		};
+		let res = match &parsed {
+			Ok(parsed) => evaluate(self.clone(), ctx, parsed),
+			Err(e) => Err(e.clone()),
+		};
 
 		let mut data = self.data_mut();
 		let mut file = data.files.raw_entry_mut().from_key(&path);
 
 		let file = match file {
 			RawEntryMut::Occupied(ref mut d) => d.get_mut(),
-			RawEntryMut::Vacant(_) => unreachable!("this file was just here!"),
+			// File was invalidated during its own evaluation, result shouldn't be cached
+			RawEntryMut::Vacant(_) => return res,
 		};
 		file.evaluating = false;
+		if let Ok(parsed) = parsed {
+			file.parsed = Some((parsed, scopes));
+		}
 		match res {
 			Ok(v) => {
 				file.evaluated = Some(v.clone());
@@ -411,6 +497,7 @@ impl State {
 	/// Has same semantics as `import 'path'` called from `from` file
 	pub fn import_from(&self, from: &SourcePath, path: &str) -> Result<Val> {
 		let resolved = self.resolve_from(from, path)?;
+		self.record_import(from, &resolved);
 		self.import_resolved(resolved)
 	}
 	pub fn import(&self, path: impl AsRef<Path>) -> Result<Val> {
@@ -418,12 +505,136 @@ impl State {
 		self.import_resolved(resolved)
 	}
 
+	/// Drops cached source, AST and evaluation result of the file, and of every file which has imported it,
+	/// so the next import of any of those will reload them from [`ImportResolver`]
+	///
+	/// Returns `false` if nothing was cached for this path
+	pub fn invalidate(&self, path: &SourcePath) -> bool {
+		let mut data = self.data_mut();
+		let mut invalidated = false;
+		let mut queue = vec![path.clone()];
+		let mut visited = GcHashSet::new();
+		while let Some(path) = queue.pop() {
+			if !visited.insert(path.clone()) {
+				continue;
+			}
+			invalidated |= data.files.remove(&path).is_some();
+			if let Some(importers) = data.importers.remove(&path) {
+				queue.extend(importers.0);
+			}
+		}
+		invalidated
+	}
+
+	/// Registers breakpoint at 1-based `line` and `column` of the file,
+	/// expressions starting anywhere in the line are recorded if `column` is not specified
+	pub fn add_breakpoint(
+		&self,
+		path: SourcePath,
+		line: usize,
+		column: Option<usize>,
+	) -> Result<Rc<Breakpoint>> {
+		let code = self.import_resolved_str(path.clone())?;
+		let source = Source::new(path, code);
+		let code = source.code();
+		let range = (line > 0)
+			.then(|| source.map_from_source_location(line, 1))
+			.flatten()
+			.and_then(|start| {
+				let line = code[start..].split('\n').next().unwrap_or_default();
+				column.map_or_else(
+					|| {
+						line.find(|c: char| !c.is_whitespace())
+							.map(|indent| (start + indent, start + line.len()))
+					},
+					|column| {
+						(1..=line.len())
+							.contains(&column)
+							.then(|| (start + column - 1, start + column))
+					},
+				)
+			});
+		let Some((from, to)) = range else {
+			throw!(RuntimeError(
+				format!(
+					"breakpoint location {line}:{} is out of file",
+					column.unwrap_or(1)
+				)
+				.into()
+			))
+		};
+		let breakpoint = Rc::new(Breakpoint {
+			loc: ExprLocation(source, from as u32, to as u32),
+			active: RefCell::new(Vec::new()),
+			collected: RefCell::new(Vec::new()),
+		});
+		self.data_mut().breakpoints.0.push(breakpoint.clone());
+		Ok(breakpoint)
+	}
+	/// Starts recording of expression value by every breakpoint, at which location it starts
+	fn enter_breakpoints(&self, loc: &ExprLocation) -> Vec<Rc<Breakpoint>> {
+		let data = self.data();
+		if data.breakpoints.0.is_empty() {
+			return Vec::new();
+		}
+		data.breakpoints.enter(data.stack_depth, loc)
+	}
+
 	/// Creates context with all passed global variables
 	pub fn create_default_context(&self, source: Source) -> Context {
 		let context_initializer = &self.settings().context_initializer;
 		context_initializer.initialize(self.clone(), source)
 	}
 
+	/// Accounts a new stack frame, failing if [`EvaluationSettings::max_stack`] is reached
+	fn enter_frame(&self) -> Result<()> {
+		let mut data = self.data_mut();
+		let stack_depth = &mut data.stack_depth;
+		if *stack_depth > self.max_stack() {
+			// Error creation uses data, so i drop guard here
+			drop(data);
+			throw!(StackOverflow);
+		}
+		*stack_depth += 1;
+		Ok(())
+	}
+	fn leave_frame(&self) {
+		let mut data = self.data_mut();
+		data.stack_depth -= 1;
+		data.stack_generation += 1;
+		if data.stack_generation >= data.gc_next_check {
+			drop(data);
+			self.maybe_collect_cycles();
+		}
+	}
+
+	/// Collects reference cycles, if number of tracked objects has grown past
+	/// [`EvaluationSettings::gc_growth_trigger`] since the last collection
+	fn maybe_collect_cycles(&self) {
+		let settings = self.settings();
+		let Some(min_objects) = settings.gc_min_objects else {
+			drop(settings);
+			let mut data = self.data_mut();
+			data.gc_next_check = data.stack_generation + GC_CHECK_INTERVAL;
+			return;
+		};
+		let growth_trigger = settings.gc_growth_trigger;
+		drop(settings);
+
+		let threshold = self.data().gc_threshold.max(min_objects);
+		let mut tracked = jrsonnet_gcmodule::count_thread_tracked();
+		let mut data = self.data_mut();
+		if tracked >= threshold {
+			// Destructors of collected objects should not observe borrowed state
+			drop(data);
+			jrsonnet_gcmodule::collect_thread_cycles();
+			tracked = jrsonnet_gcmodule::count_thread_tracked();
+			data = self.data_mut();
+			data.gc_threshold = next_gc_threshold(tracked, growth_trigger);
+		}
+		data.gc_next_check = data.stack_generation + tracked.max(GC_CHECK_INTERVAL);
+	}
+
 	/// Executes code creating a new stack frame
 	pub fn push<T>(
 		&self,
@@ -431,22 +642,9 @@ impl State {
 		frame_desc: impl FnOnce() -> String,
 		f: impl FnOnce() -> Result<T>,
 	) -> Result<T> {
-		{
-			let mut data = self.data_mut();
-			let stack_depth = &mut data.stack_depth;
-			if *stack_depth > self.max_stack() {
-				// Error creation uses data, so i drop guard here
-				drop(data);
-				throw!(StackOverflow);
-			}
-			*stack_depth += 1;
-		}
-		let result = f();
-		{
-			let mut data = self.data_mut();
-			data.stack_depth -= 1;
-			data.stack_generation += 1;
-		}
+		self.enter_frame()?;
+		let result = grow_stack(f);
+		self.leave_frame();
 		if let Err(mut err) = result {
 			err.trace_mut().0.push(StackTraceElement {
 				location: e.0.cloned(),
@@ -464,25 +662,9 @@ impl State {
 		frame_desc: impl FnOnce() -> String,
 		f: impl FnOnce() -> Result<Val>,
 	) -> Result<Val> {
-		{
-			let mut data = self.data_mut();
-			let stack_depth = &mut data.stack_depth;
-			if *stack_depth > self.max_stack() {
-				// Error creation uses data, so i drop guard here
-				drop(data);
-				throw!(StackOverflow);
-			}
-			*stack_depth += 1;
-		}
-		let mut result = f();
-		{
-			let mut data = self.data_mut();
-			data.stack_depth -= 1;
-			data.stack_generation += 1;
-			result = data
-				.breakpoints
-				.insert(data.stack_depth, data.stack_generation, e, result);
-		}
+		self.enter_frame()?;
+		let result = grow_stack(f);
+		self.leave_frame();
 		if let Err(mut err) = result {
 			err.trace_mut().0.push(StackTraceElement {
 				location: Some(e.clone()),
@@ -498,22 +680,9 @@ impl State {
 		frame_desc: impl FnOnce() -> String,
 		f: impl FnOnce() -> Result<T>,
 	) -> Result<T> {
-		{
-			let mut data = self.data_mut();
-			let stack_depth = &mut data.stack_depth;
-			if *stack_depth > self.max_stack() {
-				// Error creation uses data, so i drop guard here
-				drop(data);
-				throw!(StackOverflow);
-			}
-			*stack_depth += 1;
-		}
-		let result = f();
-		{
-			let mut data = self.data_mut();
-			data.stack_depth -= 1;
-			data.stack_generation += 1;
-		}
+		self.enter_frame()?;
+		let result = grow_stack(f);
+		self.leave_frame();
 		if let Err(mut err) = result {
 			err.trace_mut().0.push(StackTraceElement {
 				location: None,
@@ -547,6 +716,14 @@ impl State {
 	pub fn manifest_stream(&self, val: Val) -> Result<Vec<IStr>> {
 		val.manifest_stream(self.clone(), &self.manifest_format())
 	}
+	/// Checks that value conforms to the JSON Schema, all found violations are reported at once
+	#[cfg(feature = "json-schema")]
+	pub fn validate_schema(&self, val: Val, schema: &JsonSchema) -> Result<()> {
+		self.push_description(
+			|| "schema validation".to_string(),
+			|| schema.validate(self.clone(), val),
+		)
+	}
 
 	/// If passed value is function then call with set TLA
 	pub fn with_tla(&self, val: Val) -> Result<Val> {
@@ -571,8 +748,79 @@ impl State {
 	}
 }
 
+/// Resolves variables of freshly parsed code to slots, reporting undefined ones before evaluation
+///
+/// `scopes` are [layers](Context::binding_layers) of context, code will be evaluated in
+fn resolve_variables(parsed: LocExpr, scopes: &[Vec<IStr>]) -> Result<LocExpr> {
+	jrsonnet_parser::resolve_scoped(parsed, scopes).map_err(|errors| {
+		let UndefinedVariable {
+			name,
+			location,
+			visible,
+		} = errors
+			.into_iter()
+			.next()
+			.expect("at least one error is reported");
+		#[cfg(feature = "friendly-errors")]
+		let suggestions = similar_names(&name, visible);
+		#[cfg(not(feature = "friendly-errors"))]
+		let suggestions = {
+			drop(visible);
+			vec![]
+		};
+		let mut err = LocError::new(VariableIsNotDefined(name.clone(), suggestions));
+		err.trace_mut().0.push(StackTraceElement {
+			location: Some(location),
+			desc: format!("variable <{name}> access"),
+		});
+		err
+	})
+}
+
+/// Names from `candidates`, which look like `name`, most similar first
+#[cfg(feature = "friendly-errors")]
+fn similar_names(name: &str, candidates: impl IntoIterator<Item = IStr>) -> Vec<IStr> {
+	use std::cmp::Ordering;
+
+	let mut heap = Vec::new();
+	for k in candidates {
+		let conf = strsim::jaro_winkler(&k as &str, name);
+		if conf < 0.8 {
+			continue;
+		}
+		heap.push((conf, k));
+	}
+	heap.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+	heap.into_iter().map(|(_, k)| k).collect()
+}
+
 /// Internals
 impl State {
+	/// Remembers that `from` depends on `resolved`, used by [`State::invalidate`]
+	fn record_import(&self, from: &SourcePath, resolved: &SourcePath) {
+		self.data_mut()
+			.importers
+			.entry(resolved.clone())
+			.or_default()
+			.insert(from.clone());
+	}
+	/// Runs passes over freshly parsed code, which should be done once before its evaluation
+	fn prepare_code(
+		&self,
+		parsed: LocExpr,
+		ctx: &Context,
+		scopes: &[Vec<IStr>],
+	) -> Result<LocExpr> {
+		let parsed = if self.optimize() {
+			optimize(self.clone(), ctx, parsed)
+		} else {
+			parsed
+		};
+		resolve_variables(parsed, scopes)
+	}
+	fn data(&self) -> Ref<'_, EvaluationData> {
+		self.0.data.borrow()
+	}
 	fn data_mut(&self) -> RefMut<'_, EvaluationData> {
 		self.0.data.borrow_mut()
 	}
@@ -600,7 +848,9 @@ impl State {
 			path: source.clone(),
 			error: Box::new(e),
 		})?;
-		evaluate(self.clone(), self.create_default_context(source), &parsed)
+		let ctx = self.create_default_context(source);
+		let parsed = self.prepare_code(parsed, &ctx, &ctx.binding_layers())?;
+		evaluate(self.clone(), ctx, &parsed)
 	}
 }
 
@@ -683,4 +933,32 @@ impl State {
 	pub fn set_max_stack(&self, trace: usize) {
 		self.settings_mut().max_stack = trace;
 	}
+	pub fn optimize(&self) -> bool {
+		self.settings().optimize
+	}
+	pub fn set_optimize(&self, optimize: bool) {
+		self.settings_mut().optimize = optimize;
+	}
+	pub fn set_gc_min_objects(&self, min_objects: Option<usize>) {
+		self.settings_mut().gc_min_objects = min_objects;
+	}
+	pub fn set_gc_growth_trigger(&self, growth_trigger: f64) {
+		self.settings_mut().gc_growth_trigger = growth_trigger;
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::next_gc_threshold;
+
+	#[test]
+	fn gc_growth_trigger_is_clamped() {
+		assert_eq!(next_gc_threshold(1000, 2.0), 2000);
+		for trigger in [0.0, -1.0, 1.0, f64::NAN] {
+			assert_eq!(next_gc_threshold(1000, trigger), 1100);
+		}
+		// Threshold always grows, so collection doesn't run on every check
+		assert_eq!(next_gc_threshold(0, 0.0), 1);
+		assert_eq!(next_gc_threshold(5, f64::NAN), 6);
+	}
 }
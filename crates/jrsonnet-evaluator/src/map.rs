@@ -1,21 +1,83 @@
 use jrsonnet_gcmodule::{Cc, Trace};
 use jrsonnet_interner::IStr;
+use jrsonnet_parser::VarSlot;
+use rustc_hash::FxHashMap;
 
-use crate::{GcHashMap, Thunk, Val};
+use crate::{error::Error::*, throw, Result, Thunk, Val};
+
+/// Scopes with less variables than that are searched linearly, bigger ones are indexed by name
+const INDEX_THRESHOLD: usize = 8;
+
+/// Variables of a single scope, slots are ordered the same way, as variables are declared in code,
+/// see [`jrsonnet_parser::resolve`]
+#[derive(Trace, Default)]
+pub struct Bindings {
+	names: Vec<IStr>,
+	values: Vec<Thunk<Val>>,
+	/// Slot by name, only built for scopes with at least [`INDEX_THRESHOLD`] variables
+	#[trace(skip)]
+	index: Option<FxHashMap<IStr, usize>>,
+}
+
+impl Bindings {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			names: Vec::with_capacity(capacity),
+			values: Vec::with_capacity(capacity),
+			index: None,
+		}
+	}
+	fn position(&self, name: &IStr) -> Option<usize> {
+		if let Some(index) = &self.index {
+			return index.get(name).copied();
+		}
+		self.names.iter().position(|n| n == name)
+	}
+	/// Adds variable to the next slot
+	pub fn bind(&mut self, name: IStr, value: Thunk<Val>) -> Result<()> {
+		if self.position(&name).is_some() {
+			throw!(DuplicateLocalVar(name))
+		}
+		if let Some(index) = &mut self.index {
+			index.insert(name.clone(), self.names.len());
+		}
+		self.names.push(name);
+		self.values.push(value);
+		if self.index.is_none() && self.names.len() == INDEX_THRESHOLD {
+			self.index = Some(
+				self.names
+					.iter()
+					.enumerate()
+					.map(|(slot, name)| (name.clone(), slot))
+					.collect(),
+			);
+		}
+		Ok(())
+	}
+	pub const fn len(&self) -> usize {
+		self.names.len()
+	}
+	pub const fn is_empty(&self) -> bool {
+		self.names.is_empty()
+	}
+}
 
 #[derive(Trace)]
 #[trace(tracking(force))]
-pub struct LayeredHashMapInternals {
-	parent: Option<LayeredHashMap>,
-	current: GcHashMap<IStr, Thunk<Val>>,
+pub struct LayeredBindingsInternals {
+	parent: Option<LayeredBindings>,
+	current: Bindings,
 }
 
 #[derive(Trace)]
-pub struct LayeredHashMap(Cc<LayeredHashMapInternals>);
+pub struct LayeredBindings(Cc<LayeredBindingsInternals>);
 
-impl LayeredHashMap {
+impl LayeredBindings {
 	pub fn iter_keys(self, mut handler: impl FnMut(IStr)) {
-		for (k, _) in self.0.current.iter() {
+		for k in &self.0.current.names {
 			handler(k.clone());
 		}
 		if let Some(parent) = self.0.parent.clone() {
@@ -23,48 +85,67 @@ impl LayeredHashMap {
 		}
 	}
 
-	pub(crate) fn new(layer: GcHashMap<IStr, Thunk<Val>>) -> Self {
-		Self(Cc::new(LayeredHashMapInternals {
+	pub(crate) fn new(layer: Bindings) -> Self {
+		Self(Cc::new(LayeredBindingsInternals {
 			parent: None,
 			current: layer,
 		}))
 	}
 
-	pub fn extend(self, new_layer: GcHashMap<IStr, Thunk<Val>>) -> Self {
-		Self(Cc::new(LayeredHashMapInternals {
+	pub fn extend(self, new_layer: Bindings) -> Self {
+		Self(Cc::new(LayeredBindingsInternals {
 			parent: Some(self),
 			current: new_layer,
 		}))
 	}
 
 	pub fn get(&self, key: &IStr) -> Option<&Thunk<Val>> {
-		(self.0)
-			.current
-			.get(key)
+		let current = &self.0.current;
+		current
+			.position(key)
+			.map(|index| &current.values[index])
 			.or_else(|| self.0.parent.as_ref().and_then(|p| p.get(key)))
 	}
 
+	/// Returns `None` if there is no such slot, or it is occupied by variable with another name,
+	/// which means expression was resolved against another scope chain
+	pub fn get_slot(&self, key: &IStr, slot: VarSlot) -> Option<&Thunk<Val>> {
+		let mut layer = self;
+		for _ in 0..slot.depth {
+			layer = layer.0.parent.as_ref()?;
+		}
+		let current = &layer.0.current;
+		let index = slot.index as usize;
+		if current.names.get(index)? != key {
+			return None;
+		}
+		Some(&current.values[index])
+	}
+
+	/// Names of variables of every layer, innermost first, including empty layers
+	pub fn layer_names(&self) -> Vec<Vec<IStr>> {
+		let mut out = vec![self.0.current.names.clone()];
+		let mut layer = self;
+		while let Some(parent) = &layer.0.parent {
+			out.push(parent.0.current.names.clone());
+			layer = parent;
+		}
+		out
+	}
+
 	pub fn contains_key(&self, key: &IStr) -> bool {
-		(self.0).current.contains_key(key)
-			|| self
-				.0
-				.parent
-				.as_ref()
-				.map_or(false, |p| p.contains_key(key))
+		self.get(key).is_some()
 	}
 }
 
-impl Clone for LayeredHashMap {
+impl Clone for LayeredBindings {
 	fn clone(&self) -> Self {
 		Self(self.0.clone())
 	}
 }
 
-impl Default for LayeredHashMap {
+impl Default for LayeredBindings {
 	fn default() -> Self {
-		Self(Cc::new(LayeredHashMapInternals {
-			parent: None,
-			current: GcHashMap::new(),
-		}))
+		Self::new(Bindings::new())
 	}
 }
@@ -0,0 +1,305 @@
+//! Simplification of parsed code before evaluation
+//!
+//! Every simplified expression keeps location of the original one, so traces of errors, which are thrown
+//! in runtime, are not affected. Expressions, which fail to evaluate, are left as is, to fail in runtime.
+
+use std::{
+	collections::{HashMap, HashSet},
+	rc::Rc,
+};
+
+use jrsonnet_interner::IStr;
+use jrsonnet_parser::{
+	fold::{self, Folder},
+	visit::{self, Visitor},
+	ArgsDesc, BinaryOpType, BindSpec, Destruct, Expr, ExprLocation, ForSpecData, IfSpecData,
+	LiteralType, LocExpr,
+};
+
+use crate::{
+	evaluate,
+	evaluate::operator::{evaluate_binary_op_normal, evaluate_unary_op},
+	Context, State, Val,
+};
+
+/// `std` functions, which only depend on their arguments, and may be called before evaluation
+const PURE_STD_FUNCTIONS: &[&str] = &[
+	"length",
+	"type",
+	"isString",
+	"isNumber",
+	"isBoolean",
+	"isObject",
+	"isArray",
+	"isFunction",
+	"abs",
+	"sign",
+	"max",
+	"min",
+	"pow",
+	"exp",
+	"log",
+	"exponent",
+	"mantissa",
+	"floor",
+	"ceil",
+	"sqrt",
+	"sin",
+	"cos",
+	"tan",
+	"asin",
+	"acos",
+	"atan",
+	"modulo",
+	"codepoint",
+	"char",
+	"substr",
+	"startsWith",
+	"endsWith",
+	"asciiUpper",
+	"asciiLower",
+	"strReplace",
+	"stripChars",
+	"lstripChars",
+	"rstripChars",
+	"toString",
+	"escapeStringJson",
+	"md5",
+	"parseInt",
+	"parseOctal",
+	"parseHex",
+	"format",
+	"join",
+	"base64",
+	"base64Decode",
+];
+
+/// Simplifies code, evaluating parts of it, which don't depend on runtime values:
+/// - literal arithmetic, comparisons and string concatenation are folded
+/// - `if` with literal condition is replaced with the taken branch
+/// - `local` bindings with literal values, used exactly once, are inlined
+/// - `Parened` expressions are collapsed
+/// - calls to pure `std` functions with constant arguments are pre-evaluated
+///
+/// `ctx` should be the context code will be evaluated in, it is used to call `std` functions
+pub fn optimize(s: State, ctx: &Context, expr: LocExpr) -> LocExpr {
+	let mut usage = Usage::default();
+	usage.visit_loc_expr(&expr);
+	let mut optimizer = Optimizer {
+		s,
+		ctx: ctx.clone(),
+		fold_std: !usage.binders.contains(&IStr::from("std")) && ctx.contains_binding("std".into()),
+	};
+	optimizer.fold_loc_expr(expr)
+}
+
+struct Optimizer {
+	s: State,
+	ctx: Context,
+	/// Calls to `std` are only evaluated, if it refers to the global `std` everywhere in code
+	fold_std: bool,
+}
+
+impl Folder for Optimizer {
+	fn fold_loc_expr(&mut self, expr: LocExpr) -> LocExpr {
+		let expr = fold::walk_loc_expr(self, expr);
+		self.simplify(expr)
+	}
+}
+
+impl Optimizer {
+	/// Simplifies single expression, assuming its children are already simplified
+	fn simplify(&mut self, expr: LocExpr) -> LocExpr {
+		let loc = &expr.1.clone();
+		let simplified = match &*expr.0 {
+			Expr::Parened(inner) => return inner.clone(),
+			Expr::UnaryOp(op, value) => literal_value(value)
+				.and_then(|value| evaluate_unary_op(*op, &value).ok())
+				.and_then(value_literal),
+			// String repetition is not folded, as it may produce arbitrary large output
+			Expr::BinaryOp(a, BinaryOpType::Mul, _) if matches!(&*a.0, Expr::Str(_)) => None,
+			Expr::BinaryOp(a, op, b) => literal_value(a)
+				.zip(literal_value(b))
+				.and_then(|(a, b)| evaluate_binary_op_normal(self.s.clone(), &a, *op, &b).ok())
+				.and_then(value_literal),
+			Expr::IfElse {
+				cond: IfSpecData(cond),
+				cond_then,
+				cond_else,
+			} => match &*cond.0 {
+				Expr::Literal(LiteralType::True) => return cond_then.clone(),
+				Expr::Literal(LiteralType::False) => {
+					return cond_else.clone().unwrap_or_else(|| {
+						LocExpr(Rc::new(Expr::Literal(LiteralType::Null)), loc.clone())
+					})
+				}
+				_ => None,
+			},
+			Expr::Apply(target, args, _) if self.fold_std && is_constant_std_call(target, args) => {
+				evaluate(self.s.clone(), self.ctx.clone(), &expr)
+					.ok()
+					.and_then(value_literal)
+			}
+			Expr::LocalExpr(binds, body) => return self.inline_locals(binds, body, loc),
+			_ => None,
+		};
+		simplified.map_or(expr, |v| LocExpr(Rc::new(v), loc.clone()))
+	}
+
+	fn inline_locals(&mut self, binds: &[BindSpec], body: &LocExpr, loc: &ExprLocation) -> LocExpr {
+		let mut binds = binds.to_vec();
+		let mut body = body.clone();
+
+		let mut i = 0;
+		while i < binds.len() {
+			let BindSpec::Field {
+				into: Destruct::Full(name),
+				value,
+			} = &binds[i]
+			else {
+				i += 1;
+				continue;
+			};
+			// Inlined expression, which fails, would lose variable access frame in stack trace,
+			// so only values, which always succeed, are inlined
+			if literal_value(value).is_none() {
+				i += 1;
+				continue;
+			}
+
+			let mut body_usage = Usage::default();
+			body_usage.visit_loc_expr(&body);
+			let mut value_usage = Usage::default();
+			for bind in &binds {
+				value_usage.visit_bind_spec(bind);
+			}
+			if body_usage.uses(name) != 1
+				|| value_usage.uses(name) != 0
+				// Usage may refer to another variable with the same name
+				|| body_usage.binders.contains(name)
+			{
+				i += 1;
+				continue;
+			}
+
+			let mut substitute = Substitute {
+				optimizer: self,
+				name: name.0.clone(),
+				value: value.clone(),
+				replaced: false,
+			};
+			body = substitute.fold_loc_expr(body);
+			binds.remove(i);
+		}
+
+		if binds.is_empty() {
+			body
+		} else {
+			LocExpr(Rc::new(Expr::LocalExpr(binds, body)), loc.clone())
+		}
+	}
+}
+
+/// Replaces variable with its value
+struct Substitute<'o> {
+	optimizer: &'o mut Optimizer,
+	name: IStr,
+	value: LocExpr,
+	replaced: bool,
+}
+
+impl Folder for Substitute<'_> {
+	fn fold_loc_expr(&mut self, expr: LocExpr) -> LocExpr {
+		if self.replaced {
+			return expr;
+		}
+		if matches!(&*expr.0, Expr::Var(name) if *name == self.name) {
+			self.replaced = true;
+			return self.value.clone();
+		}
+		let expr = fold::walk_loc_expr(self, expr);
+		if self.replaced {
+			// Inlined value may allow further simplifications of its parents
+			self.optimizer.simplify(expr)
+		} else {
+			expr
+		}
+	}
+}
+
+/// Syntactic variable usages and definitions, regardless of scopes
+#[derive(Default)]
+struct Usage {
+	vars: HashMap<IStr, usize>,
+	binders: HashSet<IStr>,
+}
+
+impl Usage {
+	fn uses(&self, name: &IStr) -> usize {
+		self.vars.get(name).copied().unwrap_or(0)
+	}
+}
+
+impl Visitor for Usage {
+	fn visit_expr(&mut self, expr: &Expr) {
+		if let Expr::Var(name) = expr {
+			*self.vars.entry(name.clone()).or_default() += 1;
+		}
+		visit::walk_expr(self, expr);
+	}
+	fn visit_bind_spec(&mut self, bind: &BindSpec) {
+		if let BindSpec::Function { name, .. } = bind {
+			self.binders.insert(name.0.clone());
+		}
+		visit::walk_bind_spec(self, bind);
+	}
+	fn visit_destruct(&mut self, destruct: &Destruct) {
+		let mut names = Vec::new();
+		destruct.collect_names(&mut names);
+		self.binders.extend(names);
+		visit::walk_destruct(self, destruct);
+	}
+	fn visit_for_spec(&mut self, spec: &ForSpecData) {
+		self.binders.insert(spec.0 .0.clone());
+		visit::walk_for_spec(self, spec);
+	}
+}
+
+fn literal_value(expr: &LocExpr) -> Option<Val> {
+	Some(match &*expr.0 {
+		Expr::Str(v) => Val::Str(v.clone().into()),
+		Expr::Num(v) => Val::Num(*v),
+		Expr::Literal(LiteralType::True) => Val::Bool(true),
+		Expr::Literal(LiteralType::False) => Val::Bool(false),
+		Expr::Literal(LiteralType::Null) => Val::Null,
+		_ => return None,
+	})
+}
+
+fn value_literal(value: Val) -> Option<Expr> {
+	Some(match value {
+		Val::Str(v) => Expr::Str(v.into_flat()),
+		Val::Num(v) => Expr::Num(v),
+		Val::Bool(true) => Expr::Literal(LiteralType::True),
+		Val::Bool(false) => Expr::Literal(LiteralType::False),
+		Val::Null => Expr::Literal(LiteralType::Null),
+		_ => return None,
+	})
+}
+
+fn is_constant(expr: &LocExpr) -> bool {
+	match &*expr.0 {
+		Expr::Arr(items) => items.iter().all(|item| literal_value(item).is_some()),
+		_ => literal_value(expr).is_some(),
+	}
+}
+
+fn is_constant_std_call(target: &LocExpr, args: &ArgsDesc) -> bool {
+	let Expr::Index(obj, field) = &*target.0 else {
+		return false;
+	};
+	matches!(&*obj.0, Expr::Var(name) if name as &str == "std")
+		&& matches!(&*field.0, Expr::Str(name) if PURE_STD_FUNCTIONS.contains(&(name as &str)))
+		&& args.unnamed.iter().all(is_constant)
+		&& args.named.iter().all(|(_, arg)| is_constant(arg))
+}
@@ -0,0 +1,135 @@
+use std::{
+	fmt::{self, Display},
+	str::FromStr,
+};
+
+use jrsonnet_types::ValType;
+
+use crate::{
+	error::{Error::*, Result},
+	evaluate::similar_fields,
+	throw, State, Val,
+};
+
+/// Single step of [`ValuePath`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValuePathSegment {
+	/// `.name` or `["name"]`
+	Field(String),
+	/// `[0]`
+	Index(usize),
+}
+
+/// Path to the nested value, i.e `a.b[0].c`, `["dotted.name"][1]`
+///
+/// Resolved with [`Val::get_path`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValuePath(pub Vec<ValuePathSegment>);
+
+impl FromStr for ValuePath {
+	type Err = &'static str;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		fn field(s: &str) -> Result<(String, &str), &'static str> {
+			let end = s.find(['.', '[']).unwrap_or(s.len());
+			if end == 0 {
+				return Err("expected field name");
+			}
+			Ok((s[..end].into(), &s[end..]))
+		}
+		fn quoted(s: &str, quote: char) -> Result<(String, &str), &'static str> {
+			let mut out = String::new();
+			let mut chars = s.char_indices();
+			while let Some((i, c)) = chars.next() {
+				match c {
+					'\\' => out.push(chars.next().ok_or("unterminated string")?.1),
+					c if c == quote => return Ok((out, &s[i + c.len_utf8()..])),
+					c => out.push(c),
+				}
+			}
+			Err("unterminated string")
+		}
+
+		let mut out = Vec::new();
+		let mut rest = s;
+		if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+			let (name, tail) = field(rest)?;
+			out.push(ValuePathSegment::Field(name));
+			rest = tail;
+		}
+		while !rest.is_empty() {
+			if let Some(tail) = rest.strip_prefix('.') {
+				let (name, tail) = field(tail)?;
+				out.push(ValuePathSegment::Field(name));
+				rest = tail;
+			} else if let Some(tail) = rest.strip_prefix('[') {
+				let (segment, tail) = if let Some(q @ ('"' | '\'')) = tail.chars().next() {
+					let (name, tail) = quoted(&tail[1..], q)?;
+					(ValuePathSegment::Field(name), tail)
+				} else {
+					let end = tail.find(']').ok_or("unterminated index")?;
+					let index = tail[..end].parse().map_err(|_| "bad index")?;
+					(ValuePathSegment::Index(index), &tail[end..])
+				};
+				out.push(segment);
+				rest = tail.strip_prefix(']').ok_or("expected ]")?;
+			} else {
+				return Err("expected . or [");
+			}
+		}
+		Ok(Self(out))
+	}
+}
+
+impl Display for ValuePathSegment {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Field(name) if !name.is_empty() && !name.contains(['.', '[', ']', '"']) => {
+				write!(f, ".{name}")
+			}
+			Self::Field(name) => write!(f, "[{name:?}]"),
+			Self::Index(idx) => write!(f, "[{idx}]"),
+		}
+	}
+}
+impl Display for ValuePath {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for segment in &self.0 {
+			write!(f, "{segment}")?;
+		}
+		Ok(())
+	}
+}
+
+impl Val {
+	/// Resolves nested value by path
+	///
+	/// As object fields and array elements are lazy, only values on the path are evaluated
+	pub fn get_path(&self, s: State, path: &ValuePath) -> Result<Self> {
+		let mut current = self.clone();
+		for segment in &path.0 {
+			current =
+				s.push_description(
+					|| format!("selecting {segment}"),
+					|| match (&current, segment) {
+						(Self::Obj(obj), ValuePathSegment::Field(name)) => {
+							Ok(obj.get(s.clone(), name.as_str().into())?.ok_or_else(|| {
+								NoSuchField(name.as_str().into(), similar_fields(obj, name))
+							})?)
+						}
+						(Self::Arr(arr), ValuePathSegment::Index(idx)) => Ok(arr
+							.get(s.clone(), *idx)?
+							.ok_or_else(|| ArrayBoundsError(*idx, arr.len()))?),
+						(Self::Arr(_), ValuePathSegment::Field(name)) => {
+							throw!(AttemptedIndexAnArrayWithString(name.as_str().into()))
+						}
+						(Self::Obj(_), ValuePathSegment::Index(_)) => throw!(
+							ValueIndexMustBeTypeGot(ValType::Obj, ValType::Str, ValType::Num,)
+						),
+						(v, _) => throw!(CantIndexInto(v.value_type())),
+					},
+				)?;
+		}
+		Ok(current)
+	}
+}
@@ -309,7 +309,42 @@ pub fn render_integer(
 		}
 		nums
 	};
-	let neg = iv < 0.0;
+	let digits = digits
+		.into_iter()
+		.rev()
+		.map(|digit| {
+			let ch = NUMBERS[digit as usize] as char;
+			if caps {
+				ch.to_ascii_uppercase()
+			} else {
+				ch
+			}
+		})
+		.collect::<String>();
+	render_digits(
+		out,
+		iv < 0.0,
+		&digits,
+		padding,
+		precision,
+		blank,
+		sign,
+		prefix,
+	);
+}
+
+/// Writes already rendered digits of absolute value with sign, prefix and zero padding
+#[allow(clippy::too_many_arguments)]
+fn render_digits(
+	out: &mut String,
+	neg: bool,
+	digits: &str,
+	padding: usize,
+	precision: usize,
+	blank: bool,
+	sign: bool,
+	prefix: &str,
+) {
 	#[allow(clippy::bool_to_int_with_if)]
 	let zp = padding.saturating_sub(if neg || blank || sign { 1 } else { 0 });
 	let zp2 = zp
@@ -329,11 +364,7 @@ pub fn render_integer(
 		out.push('0');
 	}
 	out.push_str(prefix);
-
-	for digit in digits.into_iter().rev() {
-		let ch = NUMBERS[digit as usize] as char;
-		out.push(if caps { ch.to_ascii_uppercase() } else { ch });
-	}
+	out.push_str(digits);
 }
 
 pub fn render_decimal(
@@ -493,15 +524,30 @@ pub fn format_code(
 	match code.convtype {
 		ConvTypeV::String => tmp_out.push_str(&value.clone().to_string(s)?),
 		ConvTypeV::Decimal => {
-			let value = f64::from_untyped(value.clone(), s)?;
-			render_decimal(
-				&mut tmp_out,
-				value,
-				padding,
-				iprec,
-				clfags.blank,
-				clfags.sign,
-			);
+			// Integers are rendered exactly, as they may be out of `f64` safe range
+			if let Val::Int(value) = value {
+				let digits = value.to_string();
+				render_digits(
+					&mut tmp_out,
+					value.is_negative(),
+					digits.trim_start_matches('-'),
+					padding,
+					iprec,
+					clfags.blank,
+					clfags.sign,
+					"",
+				);
+			} else {
+				let value = f64::from_untyped(value.clone(), s)?;
+				render_decimal(
+					&mut tmp_out,
+					value,
+					padding,
+					iprec,
+					clfags.blank,
+					clfags.sign,
+				);
+			}
 		}
 		ConvTypeV::Octal => {
 			let value = f64::from_untyped(value.clone(), s)?;
@@ -590,6 +636,7 @@ pub fn format_code(
 					.ok_or_else(|| InvalidUnicodeCodepointGot(n as u32))?,
 			),
 			Val::Str(s) => {
+				let s = s.into_flat();
 				if s.chars().count() != 1 {
 					throw!(RuntimeError(
 						format!("%c expected 1 char string, got {}", s.chars().count()).into(),
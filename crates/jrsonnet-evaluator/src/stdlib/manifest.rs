@@ -48,8 +48,9 @@ fn manifest_json_ex_buf(
 			}
 		}
 		Val::Null => buf.push_str("null"),
-		Val::Str(s) => escape_string_json_buf(s, buf),
+		Val::Str(s) => escape_string_json_buf(&s.clone().into_flat(), buf),
 		Val::Num(n) => write!(buf, "{n}").unwrap(),
+		Val::Int(n) => write!(buf, "{n}").unwrap(),
 		Val::Arr(items) => {
 			buf.push('[');
 			if !items.is_empty() {
@@ -247,6 +248,7 @@ fn manifest_yaml_ex_buf(
 		}
 		Val::Null => buf.push_str("null"),
 		Val::Str(s) => {
+			let s = &s.clone().into_flat();
 			if s.is_empty() {
 				buf.push_str("\"\"");
 			} else if let Some(s) = s.strip_suffix('\n') {
@@ -264,6 +266,7 @@ fn manifest_yaml_ex_buf(
 			}
 		}
 		Val::Num(n) => write!(buf, "{}", *n).unwrap(),
+		Val::Int(n) => write!(buf, "{n}").unwrap(),
 		Val::Arr(a) => {
 			if a.is_empty() {
 				buf.push_str("[]");
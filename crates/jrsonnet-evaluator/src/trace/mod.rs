@@ -100,7 +100,7 @@ impl TraceFormat for CompactFormat {
 			);
 			let mut offset = error.location.offset;
 			let is_eof = if offset >= path.code().len() {
-				offset = path.code().len().saturating_sub(1);
+				offset = path.code().char_indices().next_back().map_or(0, |(i, _)| i);
 				true
 			} else {
 				false
@@ -200,6 +200,130 @@ impl TraceFormat for JsFormat {
 	}
 }
 
+/// Byte span of the syntax error, at least one character long, unless the code is empty
+#[cfg(any(feature = "serde_json", feature = "explaining-traces"))]
+fn syntax_error_span(
+	code: &str,
+	error: &jrsonnet_parser::ParseError,
+	explanation: Option<&jrsonnet_parser::SyntaxErrorExplanation>,
+) -> (usize, usize) {
+	let (start, end) = explanation.map_or((error.location.offset, error.location.offset), |e| {
+		(e.start, e.end)
+	});
+	let start = start.min(code.len());
+	if end > start {
+		return (start, end.min(code.len()));
+	}
+	code[start..].chars().next().map_or_else(
+		// Unexpected end of file, last character is the closest one
+		|| {
+			let last = code[..start].char_indices().next_back();
+			(last.map_or(start, |(i, _)| i), start)
+		},
+		|c| (start, start + c.len_utf8()),
+	)
+}
+
+/// Machine-readable trace, written as single-line JSON object:
+///
+/// ```json
+/// {
+///   "kind": "VariableIsNotDefined",
+///   "message": "variable is not defined: a",
+///   "location": null,
+///   "trace": [{
+///     "file": "example.jsonnet",
+///     "start": {"line": 1, "column": 1, "offset": 0},
+///     "end": {"line": 1, "column": 2, "offset": 1},
+///     "description": "variable <a> access"
+///   }]
+/// }
+/// ```
+///
+/// Lines and columns are 1-based, end position is exclusive.
+/// `location` is only set for syntax errors, frames without source have `file`/`start`/`end` set to null
+#[cfg(feature = "serde_json")]
+pub struct JsonFormat {
+	pub resolver: PathResolver,
+}
+#[cfg(feature = "serde_json")]
+impl TraceFormat for JsonFormat {
+	fn write_trace(
+		&self,
+		out: &mut dyn std::fmt::Write,
+		_s: &State,
+		error: &LocError,
+	) -> Result<(), std::fmt::Error> {
+		use serde_json::{json, Value};
+
+		fn position(location: &CodeLocation) -> Value {
+			json!({
+				"line": location.line,
+				"column": location.column.saturating_sub(1),
+				"offset": location.offset,
+			})
+		}
+
+		let resolve_file = |source: &Source| {
+			source.source_path().path().map_or_else(
+				|| source.source_path().to_string(),
+				|r| self.resolver.resolve(r),
+			)
+		};
+
+		let location = if let Error::ImportSyntaxError { path, error } = error.error() {
+			let explanation = jrsonnet_parser::explain_error(path.code(), error);
+			let (start, end) = syntax_error_span(path.code(), error, explanation.as_ref());
+			let locations = path.map_source_locations(&[start as u32, end as u32]);
+			json!({
+				"file": resolve_file(path),
+				"start": position(&locations[0]),
+				"end": position(&locations[1]),
+			})
+		} else {
+			Value::Null
+		};
+
+		let trace = error
+			.trace()
+			.0
+			.iter()
+			.map(|el| {
+				el.location.as_ref().map_or_else(
+					|| {
+						json!({
+							"file": null,
+							"start": null,
+							"end": null,
+							"description": el.desc,
+						})
+					},
+					|location| {
+						let start_end = location.0.map_source_locations(&[location.1, location.2]);
+						json!({
+							"file": resolve_file(&location.0),
+							"start": position(&start_end[0]),
+							"end": position(&start_end[1]),
+							"description": el.desc,
+						})
+					},
+				)
+			})
+			.collect::<Vec<_>>();
+
+		write!(
+			out,
+			"{}",
+			json!({
+				"kind": error.error().kind(),
+				"message": error.error().to_string(),
+				"location": location,
+				"trace": trace,
+			})
+		)
+	}
+}
+
 /// rustc-like trace displaying
 #[cfg(feature = "explaining-traces")]
 pub struct ExplainingFormat {
@@ -216,22 +340,19 @@ impl TraceFormat for ExplainingFormat {
 		write!(out, "{}", error.error())?;
 		if let Error::ImportSyntaxError { path, error } = error.error() {
 			writeln!(out)?;
-			let offset = error.location.offset;
-			let location = path
-				.map_source_locations(&[offset as u32])
-				.into_iter()
-				.next()
-				.unwrap();
-			let mut end_location = location.clone();
-			end_location.offset += 1;
+			let explanation = jrsonnet_parser::explain_error(path.code(), error);
+			let (start, end) = syntax_error_span(path.code(), error, explanation.as_ref());
+			let locations = path.map_source_locations(&[start as u32, end as u32]);
 
 			self.print_snippet(
 				out,
 				path.code(),
 				path,
-				&location,
-				&end_location,
-				"syntax error",
+				&locations[0],
+				&locations[1],
+				explanation
+					.as_ref()
+					.map_or("syntax error", |e| e.message.as_str()),
 			)?;
 		}
 		let trace = &error.trace();
@@ -10,7 +10,7 @@ use crate::{
 	function::{FuncDesc, FuncVal},
 	throw,
 	typed::CheckType,
-	val::{ArrValue, IndexableVal},
+	val::{ArrValue, IndexableVal, StrValue},
 	ObjValue, ObjValueBuilder, State, Val,
 };
 
@@ -109,6 +109,8 @@ macro_rules! impl_bounded_int {
 						}
 						Ok(Self(n as $ty))
 					}
+					// Only reachable for 64 bit types, smaller integers fail the bounds check
+					Val::Int(n) => Ok(Self(n.to_i64().ok_or(IntegerOverflow)? as $ty)),
 					_ => unreachable!(),
 				}
 			}
@@ -139,6 +141,7 @@ impl Typed for f64 {
 		<Self as Typed>::TYPE.check(s, &value)?;
 		match value {
 			Val::Num(n) => Ok(n),
+			Val::Int(n) => Ok(n.to_f64()),
 			_ => unreachable!(),
 		}
 	}
@@ -156,6 +159,7 @@ impl Typed for PositiveF64 {
 		<Self as Typed>::TYPE.check(s, &value)?;
 		match value {
 			Val::Num(n) => Ok(Self(n)),
+			Val::Int(n) => Ok(Self(n.to_f64())),
 			_ => unreachable!(),
 		}
 	}
@@ -192,6 +196,22 @@ impl Typed for usize {
 impl Typed for IStr {
 	const TYPE: &'static ComplexValType = &ComplexValType::Simple(ValType::Str);
 
+	fn into_untyped(value: Self, _: State) -> Result<Val> {
+		Ok(Val::Str(value.into()))
+	}
+
+	fn from_untyped(value: Val, s: State) -> Result<Self> {
+		<Self as Typed>::TYPE.check(s, &value)?;
+		match value {
+			Val::Str(s) => Ok(s.into_flat()),
+			_ => unreachable!(),
+		}
+	}
+}
+
+impl Typed for StrValue {
+	const TYPE: &'static ComplexValType = &ComplexValType::Simple(ValType::Str);
+
 	fn into_untyped(value: Self, _: State) -> Result<Val> {
 		Ok(Val::Str(value))
 	}
@@ -231,7 +251,7 @@ impl Typed for char {
 	fn from_untyped(value: Val, s: State) -> Result<Self> {
 		<Self as Typed>::TYPE.check(s, &value)?;
 		match value {
-			Val::Str(s) => Ok(s.chars().next().unwrap()),
+			Val::Str(s) => Ok(s.into_flat().chars().next().unwrap()),
 			_ => unreachable!(),
 		}
 	}
@@ -485,7 +505,7 @@ impl Typed for IndexableVal {
 
 	fn into_untyped(value: Self, _: State) -> Result<Val> {
 		match value {
-			IndexableVal::Str(s) => Ok(Val::Str(s)),
+			IndexableVal::Str(s) => Ok(Val::Str(s.into())),
 			IndexableVal::Arr(a) => Ok(Val::Arr(a)),
 		}
 	}
@@ -25,6 +25,9 @@ pub enum TypeError {
 		.2.map(|v|v.to_string()).unwrap_or_default(),
 	)]
 	BoundsFailed(f64, Option<f64>, Option<f64>),
+	#[cfg(feature = "json-schema")]
+	#[error("{0}")]
+	SchemaViolation(String),
 }
 impl From<TypeError> for LocError {
 	fn from(e: TypeError) -> Self {
@@ -34,6 +37,12 @@ impl From<TypeError> for LocError {
 
 #[derive(Debug, Clone, Trace)]
 pub struct TypeLocError(Box<TypeError>, ValuePathStack);
+impl TypeLocError {
+	#[cfg(feature = "json-schema")]
+	pub(crate) fn new(error: TypeError, path: ValuePathStack) -> Self {
+		Self(Box::new(error), path)
+	}
+}
 impl From<TypeError> for TypeLocError {
 	fn from(e: TypeError) -> Self {
 		Self(Box::new(e), ValuePathStack(Vec::new()))
@@ -55,7 +64,7 @@ impl Display for TypeLocError {
 }
 
 #[derive(Debug, Clone, Trace)]
-pub struct TypeLocErrorList(Vec<TypeLocError>);
+pub struct TypeLocErrorList(pub(crate) Vec<TypeLocError>);
 impl Display for TypeLocErrorList {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		use std::fmt::Write;
@@ -118,7 +127,7 @@ impl CheckType for ValType {
 }
 
 #[derive(Clone, Debug, Trace)]
-enum ValuePathItem {
+pub(crate) enum ValuePathItem {
 	Field(#[trace(skip)] Rc<str>),
 	Index(u64),
 }
@@ -132,8 +141,9 @@ impl Display for ValuePathItem {
 	}
 }
 
+/// Path to the checked value, items are stored in reverse order
 #[derive(Clone, Debug, Trace)]
-struct ValuePathStack(Vec<ValuePathItem>);
+pub(crate) struct ValuePathStack(pub(crate) Vec<ValuePathItem>);
 impl Display for ValuePathStack {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		write!(f, "self")?;
@@ -151,15 +161,20 @@ impl CheckType for ComplexValType {
 			Self::Any => Ok(()),
 			Self::Simple(t) => t.check(s, value),
 			Self::Char => match value {
-				Val::Str(s) if s.len() == 1 || s.chars().count() == 1 => Ok(()),
+				Val::Str(s) if s.len() == 1 || s.clone().into_flat().chars().count() == 1 => Ok(()),
 				v => Err(TypeError::ExpectedGot(self.clone(), v.value_type()).into()),
 			},
 			Self::BoundedNumber(from, to) => {
-				if let Val::Num(n) = value {
-					if from.map(|from| from > *n).unwrap_or(false)
-						|| to.map(|to| to < *n).unwrap_or(false)
+				let n = match value {
+					Val::Num(n) => Some(*n),
+					Val::Int(n) => Some(n.to_f64()),
+					_ => None,
+				};
+				if let Some(n) = n {
+					if from.map(|from| from > n).unwrap_or(false)
+						|| to.map(|to| to < n).unwrap_or(false)
 					{
-						return Err(TypeError::BoundsFailed(*n, *from, *to).into());
+						return Err(TypeError::BoundsFailed(n, *from, *to).into());
 					}
 					Ok(())
 				} else {
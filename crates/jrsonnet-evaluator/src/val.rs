@@ -1,4 +1,9 @@
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::{
+	cell::{OnceCell, RefCell},
+	fmt::{Debug, Display},
+	mem,
+	rc::Rc,
+};
 
 use jrsonnet_gcmodule::{Cc, Trace};
 use jrsonnet_interner::{IBytes, IStr};
@@ -13,7 +18,7 @@ use crate::{
 	},
 	throw,
 	typed::BoundedUsize,
-	ObjValue, Result, State, Unbound, WeakObjValue,
+	Int, ObjValue, Result, State, Unbound, WeakObjValue,
 };
 
 pub trait ThunkValue: Trace {
@@ -186,6 +191,144 @@ impl Slice {
 	}
 }
 
+/// Represents a Jsonnet string value.
+///
+/// Concatenation is lazy, so strings built by repeated `+` are not copied and interned on every step.
+/// Concatenated string is flattened into [`IStr`] once, when its content is needed.
+#[derive(Clone)]
+pub enum StrValue {
+	Flat(IStr),
+	/// Concatenation of two strings
+	Tree(Rc<StrTree>),
+}
+
+/// Lazy concatenation of two strings, see [`StrValue::Tree`]
+pub struct StrTree {
+	left: StrValue,
+	right: StrValue,
+	/// Length in bytes
+	len: usize,
+	flat: OnceCell<IStr>,
+}
+
+impl Drop for StrTree {
+	fn drop(&mut self) {
+		// Trees, built by loops, are very deep, recursive drop would overflow the stack
+		let mut stack = Vec::new();
+		let take = |tree: &mut Self, stack: &mut Vec<Rc<Self>>| {
+			for part in [&mut tree.left, &mut tree.right] {
+				if let StrValue::Tree(t) = mem::take(part) {
+					stack.push(t);
+				}
+			}
+		};
+		take(self, &mut stack);
+		while let Some(tree) = stack.pop() {
+			if let Ok(mut tree) = Rc::try_unwrap(tree) {
+				take(&mut tree, &mut stack);
+			}
+		}
+	}
+}
+
+impl StrValue {
+	pub fn concat(a: Self, b: Self) -> Self {
+		if a.is_empty() {
+			return b;
+		}
+		if b.is_empty() {
+			return a;
+		}
+		let len = a.len() + b.len();
+		Self::Tree(Rc::new(StrTree {
+			left: a,
+			right: b,
+			len,
+			flat: OnceCell::new(),
+		}))
+	}
+
+	pub fn into_flat(self) -> IStr {
+		match self {
+			Self::Flat(s) => s,
+			Self::Tree(t) => t
+				.flat
+				.get_or_init(|| {
+					let mut out = String::with_capacity(t.len);
+					let mut stack = vec![&t.right, &t.left];
+					while let Some(part) = stack.pop() {
+						match part {
+							Self::Flat(s) => out.push_str(s),
+							Self::Tree(t) => {
+								if let Some(flat) = t.flat.get() {
+									out.push_str(flat);
+								} else {
+									stack.push(&t.right);
+									stack.push(&t.left);
+								}
+							}
+						}
+					}
+					out.into()
+				})
+				.clone(),
+		}
+	}
+
+	/// Length in bytes
+	pub fn len(&self) -> usize {
+		match self {
+			Self::Flat(s) => s.len(),
+			Self::Tree(t) => t.len,
+		}
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl Default for StrValue {
+	fn default() -> Self {
+		Self::Flat(IStr::empty())
+	}
+}
+impl Trace for StrValue {
+	fn is_type_tracked() -> bool {
+		false
+	}
+}
+impl<T: Into<IStr>> From<T> for StrValue {
+	fn from(value: T) -> Self {
+		Self::Flat(value.into())
+	}
+}
+impl Display for StrValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.clone().into_flat())
+	}
+}
+impl Debug for StrValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:?}", self.clone().into_flat())
+	}
+}
+impl PartialEq for StrValue {
+	fn eq(&self, other: &Self) -> bool {
+		self.len() == other.len() && self.clone().into_flat() == other.clone().into_flat()
+	}
+}
+impl Eq for StrValue {}
+impl PartialOrd for StrValue {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Ord for StrValue {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.clone().into_flat().cmp(&other.clone().into_flat())
+	}
+}
+
 /// Represents a Jsonnet array value.
 #[derive(Debug, Clone, Trace)]
 // may contrain other ArrValue
@@ -553,11 +696,14 @@ pub enum Val {
 	/// Represents a Jsonnet null value.
 	Null,
 	/// Represents a Jsonnet string.
-	Str(IStr),
+	Str(StrValue),
 	/// Represents a Jsonnet number.
 	/// Should be finite, and not NaN
 	/// This restriction isn't enforced by enum, as enum field can't be marked as private
 	Num(f64),
+	/// Represents a Jsonnet number, which is an exact integer out of `f64` safe range.
+	/// Integers in safe range are always represented by `Val::Num`, see [`Int::into_val`]
+	Int(#[trace(skip)] Int),
 	/// Represents a Jsonnet array.
 	Arr(ArrValue),
 	/// Represents a Jsonnet object.
@@ -569,7 +715,7 @@ pub enum Val {
 impl From<IndexableVal> for Val {
 	fn from(v: IndexableVal) -> Self {
 		match v {
-			IndexableVal::Str(s) => Self::Str(s),
+			IndexableVal::Str(s) => Self::Str(s.into()),
 			IndexableVal::Arr(a) => Self::Arr(a),
 		}
 	}
@@ -594,7 +740,7 @@ impl Val {
 	}
 	pub fn as_str(&self) -> Option<IStr> {
 		match self {
-			Self::Str(s) => Some(s.clone()),
+			Self::Str(s) => Some(s.clone().into_flat()),
 			_ => None,
 		}
 	}
@@ -636,7 +782,7 @@ impl Val {
 	pub const fn value_type(&self) -> ValType {
 		match self {
 			Self::Str(..) => ValType::Str,
-			Self::Num(..) => ValType::Num,
+			Self::Num(..) | Self::Int(..) => ValType::Num,
 			Self::Arr(..) => ValType::Arr,
 			Self::Obj(..) => ValType::Obj,
 			Self::Bool(_) => ValType::Bool,
@@ -650,7 +796,7 @@ impl Val {
 			Self::Bool(true) => "true".into(),
 			Self::Bool(false) => "false".into(),
 			Self::Null => "null".into(),
-			Self::Str(s) => s.clone(),
+			Self::Str(s) => s.clone().into_flat(),
 			v => manifest_json_ex(
 				s,
 				v,
@@ -749,7 +895,7 @@ impl Val {
 			)?,
 			ManifestFormat::ToString => self.to_string(s)?,
 			ManifestFormat::String => match self {
-				Self::Str(s) => s.clone(),
+				Self::Str(s) => s.clone().into_flat(),
 				_ => throw!(StringManifestOutputIsNotAString),
 			},
 		})
@@ -825,7 +971,7 @@ impl Val {
 	}
 	pub fn into_indexable(self) -> Result<IndexableVal> {
 		Ok(match self {
-			Val::Str(s) => IndexableVal::Str(s),
+			Val::Str(s) => IndexableVal::Str(s.into_flat()),
 			Val::Arr(arr) => IndexableVal::Arr(arr),
 			_ => throw!(ValueIsNotIndexable(self.value_type())),
 		})
@@ -843,6 +989,8 @@ pub fn primitive_equals(val_a: &Val, val_b: &Val) -> Result<bool> {
 		(Val::Null, Val::Null) => true,
 		(Val::Str(a), Val::Str(b)) => a == b,
 		(Val::Num(a), Val::Num(b)) => (a - b).abs() <= f64::EPSILON,
+		(Val::Int(a), Val::Int(b)) => a == b,
+		(Val::Int(a), Val::Num(b)) | (Val::Num(b), Val::Int(a)) => a.cmp_f64(*b).is_eq(),
 		(Val::Arr(_), Val::Arr(_)) => throw!(RuntimeError(
 			"primitiveEquals operates on primitive types, got array".into(),
 		)),
@@ -322,11 +322,7 @@ fn builtin_inner(attr: BuiltinAttrs, fun: ItemFn) -> syn::Result<TokenStream> {
 			}
 			ArgInfo::Lazy { is_option, .. } => {
 				if *is_option {
-					quote! {if let Some(value) = &parsed[#id] {
-						Some(value.clone())
-					} else {
-						None
-					}}
+					quote! {parsed[#id].clone(),}
 				} else {
 					quote! {
 						parsed[#id].as_ref().expect("args shape is correct").clone(),
@@ -0,0 +1,272 @@
+//! Targeted descriptions of common syntax errors
+//!
+//! Peg only knows the set of tokens expected at the error position, which is often
+//! not helpful for the user, i.e `expected one of "(", ".", "[", "{", "}", <binary op>`
+//! instead of just saying that comma is missing
+
+use jrsonnet_interner::IStr;
+
+use crate::{recovery::enclosing_brackets, source::Source, ParseError, ParserSettings};
+
+pub(crate) const RESERVED: &[&str] = &[
+	"assert",
+	"else",
+	"error",
+	"false",
+	"for",
+	"function",
+	"if",
+	"import",
+	"importstr",
+	"importbin",
+	"in",
+	"local",
+	"null",
+	"tailstrict",
+	"then",
+	"self",
+	"super",
+	"true",
+];
+
+/// Human-readable description of syntax error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxErrorExplanation {
+	pub message: String,
+	/// Byte range of the code, to which error refers
+	pub start: usize,
+	pub end: usize,
+}
+
+fn is_ident_char(c: char) -> bool {
+	c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Checks if text block, starting at the beginning of `code`, has no closing `|||` line
+fn is_unterminated_text_block(code: &str) -> bool {
+	let Some(rest) = code.strip_prefix("|||") else {
+		return false;
+	};
+	!rest
+		.split('\n')
+		.skip(1)
+		.any(|line| line.trim_start_matches([' ', '\t']).starts_with("|||"))
+}
+
+/// Checks if comma would be accepted at `offset`, i.e. the preceding token ends a field, element
+/// or argument, and not some other construct, like `for` specification of object comprehension
+///
+/// Only the innermost brackets around `offset` are re-parsed, not the whole file
+fn accepts_comma(code: &str, offset: usize) -> bool {
+	let Some((start, bracket, end)) = enclosing_brackets(code.as_bytes(), offset) else {
+		return false;
+	};
+	// Brackets of call or index are reattached to some expression, as they can't be parsed on their own
+	let before = code[..start].trim_end();
+	let prev_word = &before[before.trim_end_matches(is_ident_char).len()..];
+	let attached = bracket != b'{'
+		&& if prev_word.is_empty() {
+			before.ends_with([')', ']', '}', '"', '\''])
+		} else {
+			!RESERVED.contains(&prev_word) || matches!(prev_word, "function" | "self" | "super")
+		};
+	let prefix = if attached { "x" } else { "" };
+
+	let fixed = format!("{prefix}{},{}", &code[start..offset], &code[offset..end]);
+	let settings = ParserSettings {
+		file_name: Source::new_virtual("<explain>".into(), IStr::empty()),
+	};
+	crate::parse(&fixed, &settings).map_or_else(
+		|e| e.location.offset > prefix.len() + offset - start,
+		|_| true,
+	)
+}
+
+/// Describes the syntax error, if it matches one of known common mistakes
+pub fn explain_error(code: &str, error: &ParseError) -> Option<SyntaxErrorExplanation> {
+	let offset = error.location.offset.min(code.len());
+	let rest = &code[offset..];
+	let expects = |token: &str| error.expected.tokens().any(|t| t == token);
+	let explanation = |message: String, len: usize| {
+		Some(SyntaxErrorExplanation {
+			message,
+			start: offset,
+			end: offset + len,
+		})
+	};
+
+	if is_unterminated_text_block(rest) {
+		return explanation(
+			"unterminated text block, expected closing `|||` on its own line".into(),
+			3,
+		);
+	}
+
+	if rest.starts_with('=') && !rest.starts_with("==") && expects("\":\"") {
+		return explanation(
+			"expected `:` after object field name, `=` is only used in `local` bindings".into(),
+			1,
+		);
+	}
+	if rest.starts_with(':') && expects("\"=\"") {
+		return explanation(
+			"expected `=` in `local` binding, `:` is only used for object fields".into(),
+			1,
+		);
+	}
+
+	let reserved = |word: &str, hint: bool| {
+		let hint = if hint {
+			format!(", quote it to use as a field name: \"{word}\"")
+		} else {
+			String::new()
+		};
+		format!("`{word}` is a reserved keyword, and can't be used as an identifier{hint}")
+	};
+	let word_len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+	let word = &rest[..word_len];
+	if RESERVED.contains(&word) && expects("<identifier>") {
+		return explanation(reserved(word, expects("<string>")), word_len);
+	}
+	// `{local: 1}` is parsed as object local, and fails after the keyword
+	let before = code[..offset].trim_end();
+	let prev_word = &before[before.trim_end_matches(is_ident_char).len()..];
+	if RESERVED.contains(&prev_word) && rest.starts_with(':') {
+		return Some(SyntaxErrorExplanation {
+			message: reserved(prev_word, true),
+			start: before.len() - prev_word.len(),
+			end: before.len(),
+		});
+	}
+
+	let starts_element = rest
+		.chars()
+		.next()
+		.is_some_and(|c| is_ident_char(c) || c == '"' || c == '\'');
+	let after_whitespace = code[..offset].ends_with(|c: char| c.is_ascii_whitespace());
+	if starts_element && after_whitespace && accepts_comma(code, offset) {
+		let between = if expects("\"}\"") {
+			Some("object fields")
+		} else if expects("\"]\"") {
+			Some("array elements")
+		} else if expects("\")\"") {
+			Some("function arguments")
+		} else {
+			None
+		};
+		if let Some(between) = between {
+			return explanation(format!("missing comma between {between}"), word_len.max(1));
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+pub mod tests {
+	use jrsonnet_interner::IStr;
+
+	use super::explain_error;
+	use crate::{parse, source::Source, ParserSettings};
+
+	fn explain(code: &str) -> Option<(String, usize, usize)> {
+		let settings = ParserSettings {
+			file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+		};
+		let error = parse(code, &settings).expect_err("code has syntax errors");
+		explain_error(code, &error).map(|e| (e.message, e.start, e.end))
+	}
+
+	#[test]
+	fn missing_comma() {
+		assert_eq!(
+			explain("{a: 1\n b: 2}"),
+			Some(("missing comma between object fields".into(), 7, 8))
+		);
+		assert_eq!(
+			explain("[1 2]"),
+			Some(("missing comma between array elements".into(), 3, 4))
+		);
+		assert_eq!(
+			explain("f(a 'b')"),
+			Some(("missing comma between function arguments".into(), 4, 5))
+		);
+		// Only enclosing brackets are checked, and call brackets are kept attached
+		assert_eq!(
+			explain("local f(a b) = 1; f"),
+			Some(("missing comma between function arguments".into(), 10, 11))
+		);
+		assert_eq!(
+			explain("{ a: if true then [1 2] else f(1)[0 1] }"),
+			Some(("missing comma between array elements".into(), 21, 22))
+		);
+		assert_eq!(explain("{ a: std.length(x)[0 1] }"), None);
+		assert_eq!(explain("if (a b) then 1"), None);
+		assert_eq!(
+			explain("// é\n{a: 1 b: 2}"),
+			Some(("missing comma between object fields".into(), 12, 13))
+		);
+		// Comma can't be placed after `for` specification
+		assert_eq!(
+			explain("{ [k]: v for k in [1] for v in [1] local l = 5 }"),
+			None
+		);
+	}
+
+	#[test]
+	fn unterminated_text_block() {
+		assert_eq!(
+			explain("local a = |||\n  abc\n; a"),
+			Some((
+				"unterminated text block, expected closing `|||` on its own line".into(),
+				10,
+				13
+			))
+		);
+	}
+
+	#[test]
+	fn reserved_word() {
+		assert_eq!(
+			explain("local if = 1; if"),
+			Some((
+				"`if` is a reserved keyword, and can't be used as an identifier".into(),
+				6,
+				8
+			))
+		);
+		assert_eq!(
+			explain("{local: 1}"),
+			Some((
+				"`local` is a reserved keyword, and can't be used as an identifier, quote it to use as a field name: \"local\"".into(),
+				1,
+				6
+			))
+		);
+	}
+
+	#[test]
+	fn equals_instead_of_colon() {
+		assert_eq!(
+			explain("{a = 1}"),
+			Some((
+				"expected `:` after object field name, `=` is only used in `local` bindings".into(),
+				3,
+				4
+			))
+		);
+		assert_eq!(
+			explain("local a: 1; a"),
+			Some((
+				"expected `=` in `local` binding, `:` is only used for object fields".into(),
+				7,
+				8
+			))
+		);
+	}
+
+	#[test]
+	fn unknown() {
+		assert_eq!(explain("1 +"), None);
+	}
+}
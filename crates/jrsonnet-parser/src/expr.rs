@@ -15,10 +15,10 @@ use crate::source::Source;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "structdump", derive(Codegen))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub enum FieldName {
 	/// {fixed: 2}
-	Fixed(IStr),
+	Fixed(LocIStr),
 	/// {["dyn"+"amic"]: 3}
 	Dyn(LocExpr),
 }
@@ -48,7 +48,7 @@ pub struct AssertStmt(pub LocExpr, pub Option<LocExpr>);
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub struct FieldMember {
 	pub name: FieldName,
 	pub plus: bool,
@@ -59,7 +59,7 @@ pub struct FieldMember {
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub enum Member {
 	Field(FieldMember),
 	BindStmt(BindSpec),
@@ -161,7 +161,7 @@ impl Display for BinaryOpType {
 /// name, default value
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub struct Param(pub Destruct, pub Option<LocExpr>);
 
 /// Defined function parameters
@@ -179,13 +179,13 @@ impl Deref for ParamsDesc {
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub struct ArgsDesc {
 	pub unnamed: Vec<LocExpr>,
-	pub named: Vec<(IStr, LocExpr)>,
+	pub named: Vec<(LocIStr, LocExpr)>,
 }
 impl ArgsDesc {
-	pub fn new(unnamed: Vec<LocExpr>, named: Vec<(IStr, LocExpr)>) -> Self {
+	pub fn new(unnamed: Vec<LocExpr>, named: Vec<(LocIStr, LocExpr)>) -> Self {
 		Self { unnamed, named }
 	}
 }
@@ -199,11 +199,14 @@ pub enum DestructRest {
 	Drop,
 }
 
+/// Field of object destructuring: field name, pattern (`None` for shorthand `{ name }`) and default value
+pub type DestructField = (IStr, Option<Destruct>, Option<LocExpr>);
+
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Trace)]
 pub enum Destruct {
-	Full(IStr),
+	Full(LocIStr),
 	#[cfg(feature = "exp-destruct")]
 	Skip,
 	#[cfg(feature = "exp-destruct")]
@@ -214,7 +217,7 @@ pub enum Destruct {
 	},
 	#[cfg(feature = "exp-destruct")]
 	Object {
-		fields: Vec<(IStr, Option<Destruct>, Option<LocExpr>)>,
+		fields: Vec<DestructField>,
 		rest: Option<DestructRest>,
 	},
 }
@@ -222,11 +225,40 @@ impl Destruct {
 	/// Name of destructure, used for function parameter names
 	pub fn name(&self) -> Option<IStr> {
 		match self {
-			Self::Full(name) => Some(name.clone()),
+			Self::Full(name) => Some(name.0.clone()),
 			#[cfg(feature = "exp-destruct")]
 			_ => None,
 		}
 	}
+	/// Appends names of all variables bound by this destructure, in order of their declaration
+	pub fn collect_names(&self, out: &mut Vec<IStr>) {
+		match self {
+			Self::Full(name) => out.push(name.0.clone()),
+			#[cfg(feature = "exp-destruct")]
+			Self::Skip => {}
+			#[cfg(feature = "exp-destruct")]
+			Self::Array { start, rest, end } => {
+				for item in start {
+					item.collect_names(out);
+				}
+				if let Some(DestructRest::Keep(name)) = rest {
+					out.push(name.clone());
+				}
+				for item in end {
+					item.collect_names(out);
+				}
+			}
+			#[cfg(feature = "exp-destruct")]
+			Self::Object { fields, rest: _ } => {
+				for (name, into, _default) in fields {
+					match into {
+						Some(into) => into.collect_names(out),
+						None => out.push(name.clone()),
+					}
+				}
+			}
+		}
+	}
 }
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
@@ -238,7 +270,7 @@ pub enum BindSpec {
 		value: LocExpr,
 	},
 	Function {
-		name: IStr,
+		name: LocIStr,
 		params: ParamsDesc,
 		value: LocExpr,
 	},
@@ -246,17 +278,17 @@ pub enum BindSpec {
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub struct IfSpecData(pub LocExpr);
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
-pub struct ForSpecData(pub IStr, pub LocExpr);
+#[derive(Debug, Clone, PartialEq, Trace)]
+pub struct ForSpecData(pub LocIStr, pub LocExpr);
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub enum CompSpec {
 	IfSpec(IfSpecData),
 	ForSpec(ForSpecData),
@@ -264,7 +296,7 @@ pub enum CompSpec {
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub struct ObjComp {
 	pub pre_locals: Vec<BindSpec>,
 	pub key: LocExpr,
@@ -276,7 +308,7 @@ pub struct ObjComp {
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub enum ObjBody {
 	MemberList(Vec<Member>),
 	ObjComp(ObjComp),
@@ -296,17 +328,29 @@ pub enum LiteralType {
 
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub struct SliceDesc {
 	pub start: Option<LocExpr>,
 	pub end: Option<LocExpr>,
 	pub step: Option<LocExpr>,
 }
 
+/// Position of local variable value in evaluation context
+#[cfg_attr(feature = "structdump", derive(Codegen))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Trace)]
+#[trace(skip)]
+pub struct VarSlot {
+	/// How many scopes up from the usage variable is defined
+	pub depth: u32,
+	/// Index of the variable in its scope, in order of declaration
+	pub index: u32,
+}
+
 /// Syntax base
 #[cfg_attr(feature = "structdump", derive(Codegen))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Trace)]
+#[derive(Debug, Clone, PartialEq, Trace)]
 pub enum Expr {
 	Literal(LiteralType),
 
@@ -316,6 +360,8 @@ pub enum Expr {
 	Num(f64),
 	/// Variable name: test
 	Var(IStr),
+	/// Local variable, with position of its value in context computed by [`crate::resolve`]
+	ResolvedVar(IStr, VarSlot),
 
 	/// Array of expressions: [1, 2, "Hello"]
 	Arr(Vec<LocExpr>),
@@ -414,3 +460,23 @@ impl Debug for LocExpr {
 		Ok(())
 	}
 }
+
+/// Holds name of variable, parameter or field, and its location in source file
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "structdump", derive(Codegen))]
+#[derive(Clone, PartialEq, Trace)]
+pub struct LocIStr(pub IStr, pub ExprLocation);
+
+impl Deref for LocIStr {
+	type Target = IStr;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl Debug for LocIStr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:?} from {:?}", self.0, self.1)
+	}
+}
@@ -0,0 +1,441 @@
+//! AST transformation
+//!
+//! [`Folder`] takes nodes by value and returns replacements, by default rebuilding every node
+//! from folded children. Override methods for the nodes of interest, and call corresponding `walk_*`
+//! function from the override to fold children.
+
+use std::rc::Rc;
+
+use crate::expr::{
+	ArgsDesc, AssertStmt, BindSpec, CompSpec, Destruct, DestructField, DestructRest, Expr,
+	FieldMember, FieldName, ForSpecData, IfSpecData, LocExpr, LocIStr, Member, ObjBody, ObjComp,
+	Param, ParamsDesc, SliceDesc,
+};
+
+/// Takes value out of Rc, cloning it only if it is shared
+fn unwrap_rc<T: Clone>(rc: Rc<T>) -> T {
+	Rc::try_unwrap(rc).unwrap_or_else(|rc| (*rc).clone())
+}
+
+/// AST transformer, every method by default folds all children of the node, in source order
+pub trait Folder {
+	fn fold_loc_expr(&mut self, expr: LocExpr) -> LocExpr {
+		walk_loc_expr(self, expr)
+	}
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		walk_expr(self, expr)
+	}
+	fn fold_obj_body(&mut self, body: ObjBody) -> ObjBody {
+		walk_obj_body(self, body)
+	}
+	fn fold_obj_comp(&mut self, comp: ObjComp) -> ObjComp {
+		walk_obj_comp(self, comp)
+	}
+	fn fold_member(&mut self, member: Member) -> Member {
+		walk_member(self, member)
+	}
+	fn fold_field_member(&mut self, field: FieldMember) -> FieldMember {
+		walk_field_member(self, field)
+	}
+	fn fold_field_name(&mut self, name: FieldName) -> FieldName {
+		walk_field_name(self, name)
+	}
+	fn fold_bind_spec(&mut self, bind: BindSpec) -> BindSpec {
+		walk_bind_spec(self, bind)
+	}
+	fn fold_destruct(&mut self, destruct: Destruct) -> Destruct {
+		walk_destruct(self, destruct)
+	}
+	/// Object destructuring field, shorthand fields (`{ name }`) bind variable named after the field
+	fn fold_destruct_field(&mut self, field: DestructField) -> DestructField {
+		walk_destruct_field(self, field)
+	}
+	/// `...rest` or `...` of array or object destructuring
+	fn fold_destruct_rest(&mut self, rest: DestructRest) -> DestructRest {
+		rest
+	}
+	fn fold_params_desc(&mut self, params: ParamsDesc) -> ParamsDesc {
+		walk_params_desc(self, params)
+	}
+	fn fold_param(&mut self, param: Param) -> Param {
+		walk_param(self, param)
+	}
+	fn fold_args_desc(&mut self, args: ArgsDesc) -> ArgsDesc {
+		walk_args_desc(self, args)
+	}
+	fn fold_comp_spec(&mut self, spec: CompSpec) -> CompSpec {
+		walk_comp_spec(self, spec)
+	}
+	fn fold_for_spec(&mut self, spec: ForSpecData) -> ForSpecData {
+		walk_for_spec(self, spec)
+	}
+	fn fold_if_spec(&mut self, spec: IfSpecData) -> IfSpecData {
+		walk_if_spec(self, spec)
+	}
+	fn fold_assert_stmt(&mut self, assert: AssertStmt) -> AssertStmt {
+		walk_assert_stmt(self, assert)
+	}
+	fn fold_slice_desc(&mut self, desc: SliceDesc) -> SliceDesc {
+		walk_slice_desc(self, desc)
+	}
+	/// Names of variables, parameters, fields and named arguments
+	fn fold_loc_istr(&mut self, name: LocIStr) -> LocIStr {
+		name
+	}
+}
+
+pub fn walk_loc_expr<F: Folder + ?Sized>(f: &mut F, expr: LocExpr) -> LocExpr {
+	let LocExpr(inner, location) = expr;
+	LocExpr(Rc::new(f.fold_expr(unwrap_rc(inner))), location)
+}
+
+pub fn walk_expr<F: Folder + ?Sized>(f: &mut F, expr: Expr) -> Expr {
+	match expr {
+		e @ (Expr::Literal(_)
+		| Expr::Str(_)
+		| Expr::Num(_)
+		| Expr::Var(_)
+		| Expr::ResolvedVar(_, _)
+		| Expr::Import(_)
+		| Expr::ImportStr(_)
+		| Expr::ImportBin(_)) => e,
+		Expr::Arr(items) => Expr::Arr(items.into_iter().map(|i| f.fold_loc_expr(i)).collect()),
+		Expr::ArrComp(value, specs) => {
+			let value = f.fold_loc_expr(value);
+			Expr::ArrComp(
+				value,
+				specs.into_iter().map(|s| f.fold_comp_spec(s)).collect(),
+			)
+		}
+		Expr::Obj(body) => Expr::Obj(f.fold_obj_body(body)),
+		Expr::ObjExtend(base, body) => {
+			let base = f.fold_loc_expr(base);
+			Expr::ObjExtend(base, f.fold_obj_body(body))
+		}
+		Expr::Parened(e) => Expr::Parened(f.fold_loc_expr(e)),
+		Expr::UnaryOp(op, e) => Expr::UnaryOp(op, f.fold_loc_expr(e)),
+		Expr::BinaryOp(a, op, b) => {
+			let a = f.fold_loc_expr(a);
+			Expr::BinaryOp(a, op, f.fold_loc_expr(b))
+		}
+		Expr::AssertExpr(assert, rest) => {
+			let assert = f.fold_assert_stmt(assert);
+			Expr::AssertExpr(assert, f.fold_loc_expr(rest))
+		}
+		Expr::LocalExpr(binds, rest) => {
+			let binds = binds.into_iter().map(|b| f.fold_bind_spec(b)).collect();
+			Expr::LocalExpr(binds, f.fold_loc_expr(rest))
+		}
+		Expr::ErrorStmt(e) => Expr::ErrorStmt(f.fold_loc_expr(e)),
+		Expr::Apply(value, args, tailstrict) => {
+			let value = f.fold_loc_expr(value);
+			Expr::Apply(value, f.fold_args_desc(args), tailstrict)
+		}
+		Expr::Index(value, index) => {
+			let value = f.fold_loc_expr(value);
+			Expr::Index(value, f.fold_loc_expr(index))
+		}
+		Expr::Function(params, body) => {
+			let params = f.fold_params_desc(params);
+			Expr::Function(params, f.fold_loc_expr(body))
+		}
+		Expr::IfElse {
+			cond,
+			cond_then,
+			cond_else,
+		} => Expr::IfElse {
+			cond: f.fold_if_spec(cond),
+			cond_then: f.fold_loc_expr(cond_then),
+			cond_else: cond_else.map(|e| f.fold_loc_expr(e)),
+		},
+		Expr::Slice(value, desc) => {
+			let value = f.fold_loc_expr(value);
+			Expr::Slice(value, f.fold_slice_desc(desc))
+		}
+	}
+}
+
+pub fn walk_obj_body<F: Folder + ?Sized>(f: &mut F, body: ObjBody) -> ObjBody {
+	match body {
+		ObjBody::MemberList(members) => {
+			ObjBody::MemberList(members.into_iter().map(|m| f.fold_member(m)).collect())
+		}
+		ObjBody::ObjComp(comp) => ObjBody::ObjComp(f.fold_obj_comp(comp)),
+	}
+}
+
+pub fn walk_obj_comp<F: Folder + ?Sized>(f: &mut F, comp: ObjComp) -> ObjComp {
+	ObjComp {
+		pre_locals: comp
+			.pre_locals
+			.into_iter()
+			.map(|b| f.fold_bind_spec(b))
+			.collect(),
+		key: f.fold_loc_expr(comp.key),
+		plus: comp.plus,
+		value: f.fold_loc_expr(comp.value),
+		post_locals: comp
+			.post_locals
+			.into_iter()
+			.map(|b| f.fold_bind_spec(b))
+			.collect(),
+		compspecs: comp
+			.compspecs
+			.into_iter()
+			.map(|s| f.fold_comp_spec(s))
+			.collect(),
+	}
+}
+
+pub fn walk_member<F: Folder + ?Sized>(f: &mut F, member: Member) -> Member {
+	match member {
+		Member::Field(field) => Member::Field(f.fold_field_member(field)),
+		Member::BindStmt(bind) => Member::BindStmt(f.fold_bind_spec(bind)),
+		Member::AssertStmt(assert) => Member::AssertStmt(f.fold_assert_stmt(assert)),
+	}
+}
+
+pub fn walk_field_member<F: Folder + ?Sized>(f: &mut F, field: FieldMember) -> FieldMember {
+	FieldMember {
+		name: f.fold_field_name(field.name),
+		plus: field.plus,
+		params: field.params.map(|p| f.fold_params_desc(p)),
+		visibility: field.visibility,
+		value: f.fold_loc_expr(field.value),
+	}
+}
+
+pub fn walk_field_name<F: Folder + ?Sized>(f: &mut F, name: FieldName) -> FieldName {
+	match name {
+		FieldName::Fixed(name) => FieldName::Fixed(f.fold_loc_istr(name)),
+		FieldName::Dyn(e) => FieldName::Dyn(f.fold_loc_expr(e)),
+	}
+}
+
+pub fn walk_bind_spec<F: Folder + ?Sized>(f: &mut F, bind: BindSpec) -> BindSpec {
+	match bind {
+		BindSpec::Field { into, value } => BindSpec::Field {
+			into: f.fold_destruct(into),
+			value: f.fold_loc_expr(value),
+		},
+		BindSpec::Function {
+			name,
+			params,
+			value,
+		} => BindSpec::Function {
+			name: f.fold_loc_istr(name),
+			params: f.fold_params_desc(params),
+			value: f.fold_loc_expr(value),
+		},
+	}
+}
+
+#[cfg_attr(not(feature = "exp-destruct"), allow(unused_variables))]
+pub fn walk_destruct<F: Folder + ?Sized>(f: &mut F, destruct: Destruct) -> Destruct {
+	match destruct {
+		Destruct::Full(name) => Destruct::Full(f.fold_loc_istr(name)),
+		#[cfg(feature = "exp-destruct")]
+		Destruct::Skip => Destruct::Skip,
+		#[cfg(feature = "exp-destruct")]
+		Destruct::Array { start, rest, end } => Destruct::Array {
+			start: start.into_iter().map(|d| f.fold_destruct(d)).collect(),
+			rest: rest.map(|r| f.fold_destruct_rest(r)),
+			end: end.into_iter().map(|d| f.fold_destruct(d)).collect(),
+		},
+		#[cfg(feature = "exp-destruct")]
+		Destruct::Object { fields, rest } => Destruct::Object {
+			fields: fields
+				.into_iter()
+				.map(|field| f.fold_destruct_field(field))
+				.collect(),
+			rest: rest.map(|r| f.fold_destruct_rest(r)),
+		},
+	}
+}
+
+pub fn walk_destruct_field<F: Folder + ?Sized>(f: &mut F, field: DestructField) -> DestructField {
+	let (name, into, default) = field;
+	let into = into.map(|d| f.fold_destruct(d));
+	(name, into, default.map(|e| f.fold_loc_expr(e)))
+}
+
+pub fn walk_params_desc<F: Folder + ?Sized>(f: &mut F, params: ParamsDesc) -> ParamsDesc {
+	ParamsDesc(Rc::new(
+		unwrap_rc(params.0)
+			.into_iter()
+			.map(|p| f.fold_param(p))
+			.collect(),
+	))
+}
+
+pub fn walk_param<F: Folder + ?Sized>(f: &mut F, param: Param) -> Param {
+	let into = f.fold_destruct(param.0);
+	Param(into, param.1.map(|e| f.fold_loc_expr(e)))
+}
+
+pub fn walk_args_desc<F: Folder + ?Sized>(f: &mut F, args: ArgsDesc) -> ArgsDesc {
+	ArgsDesc {
+		unnamed: args
+			.unnamed
+			.into_iter()
+			.map(|a| f.fold_loc_expr(a))
+			.collect(),
+		named: args
+			.named
+			.into_iter()
+			.map(|(name, a)| (f.fold_loc_istr(name), f.fold_loc_expr(a)))
+			.collect(),
+	}
+}
+
+pub fn walk_comp_spec<F: Folder + ?Sized>(f: &mut F, spec: CompSpec) -> CompSpec {
+	match spec {
+		CompSpec::IfSpec(spec) => CompSpec::IfSpec(f.fold_if_spec(spec)),
+		CompSpec::ForSpec(spec) => CompSpec::ForSpec(f.fold_for_spec(spec)),
+	}
+}
+
+pub fn walk_for_spec<F: Folder + ?Sized>(f: &mut F, spec: ForSpecData) -> ForSpecData {
+	let var = f.fold_loc_istr(spec.0);
+	ForSpecData(var, f.fold_loc_expr(spec.1))
+}
+
+pub fn walk_if_spec<F: Folder + ?Sized>(f: &mut F, spec: IfSpecData) -> IfSpecData {
+	IfSpecData(f.fold_loc_expr(spec.0))
+}
+
+pub fn walk_assert_stmt<F: Folder + ?Sized>(f: &mut F, assert: AssertStmt) -> AssertStmt {
+	let cond = f.fold_loc_expr(assert.0);
+	AssertStmt(cond, assert.1.map(|e| f.fold_loc_expr(e)))
+}
+
+pub fn walk_slice_desc<F: Folder + ?Sized>(f: &mut F, desc: SliceDesc) -> SliceDesc {
+	SliceDesc {
+		start: desc.start.map(|e| f.fold_loc_expr(e)),
+		end: desc.end.map(|e| f.fold_loc_expr(e)),
+		step: desc.step.map(|e| f.fold_loc_expr(e)),
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use jrsonnet_interner::IStr;
+
+	use super::Folder;
+	use crate::{
+		expr::{Destruct, DestructRest, Expr, ForSpecData, LocIStr},
+		source::Source,
+		visit::{self, Visitor},
+		LocExpr, ParserSettings,
+	};
+
+	fn parse(code: &str) -> LocExpr {
+		crate::parse(
+			code,
+			&ParserSettings {
+				file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+			},
+		)
+		.unwrap()
+	}
+
+	/// Collects referenced variables
+	#[derive(Default)]
+	struct Vars(Vec<String>);
+	impl Visitor for Vars {
+		fn visit_expr(&mut self, expr: &Expr) {
+			if let Expr::Var(name) = expr {
+				self.0.push(name.to_string());
+			}
+			visit::walk_expr(self, expr);
+		}
+	}
+
+	/// Renames variable, both in definitions and usages
+	struct Rename(&'static str, &'static str);
+	impl Rename {
+		fn name(&self, name: IStr) -> IStr {
+			if name == *self.0 {
+				self.1.into()
+			} else {
+				name
+			}
+		}
+	}
+	impl Folder for Rename {
+		fn fold_expr(&mut self, expr: Expr) -> Expr {
+			match expr {
+				Expr::Var(name) => Expr::Var(self.name(name)),
+				expr => super::walk_expr(self, expr),
+			}
+		}
+		fn fold_destruct(&mut self, destruct: Destruct) -> Destruct {
+			match destruct {
+				Destruct::Full(name) => Destruct::Full(LocIStr(self.name(name.0), name.1)),
+				#[allow(unreachable_patterns)]
+				destruct => super::walk_destruct(self, destruct),
+			}
+		}
+		fn fold_destruct_rest(&mut self, rest: DestructRest) -> DestructRest {
+			match rest {
+				DestructRest::Keep(name) => DestructRest::Keep(self.name(name)),
+				DestructRest::Drop => DestructRest::Drop,
+			}
+		}
+		fn fold_for_spec(&mut self, spec: ForSpecData) -> ForSpecData {
+			let spec = super::walk_for_spec(self, spec);
+			ForSpecData(LocIStr(self.name(spec.0 .0), spec.0 .1), spec.1)
+		}
+	}
+
+	const CODE: &str = "
+		local f(a, b = c) = a + b;
+		{
+			[k]: v[1:n:2] for k in ks if p(k, x = y)
+		} + {
+			assert q : r,
+			local l = [z for z in zs],
+			m(o): if s then t else u,
+			[w]:: !ee,
+		}.field(error g)
+	";
+
+	#[test]
+	fn visit_all_vars() {
+		let mut vars = Vars::default();
+		vars.visit_loc_expr(&parse(CODE));
+		assert_eq!(
+			vars.0,
+			[
+				"c", "a", "b", "k", "v", "n", "ks", "p", "k", "y", "q", "r", "z", "zs", "s", "t",
+				"u", "w", "ee", "g"
+			]
+		);
+	}
+
+	#[test]
+	fn fold_rename() {
+		let renamed = Rename("k", "j").fold_loc_expr(parse(CODE));
+		assert_eq!(
+			renamed,
+			parse(
+				&CODE
+					.replace("[k]", "[j]")
+					.replace("k in", "j in")
+					.replace("(k,", "(j,")
+			)
+		);
+		// Identity folder doesn't change anything
+		struct Identity;
+		impl Folder for Identity {}
+		assert_eq!(Identity.fold_loc_expr(parse(CODE)), parse(CODE));
+	}
+
+	#[test]
+	#[cfg(feature = "exp-destruct")]
+	fn fold_rename_rest() {
+		let code = "local [a, ...k] = [], { b, c: [d] = a, ...k } = {}; [a, b, d, k]";
+		let renamed = Rename("k", "j").fold_loc_expr(parse(code));
+		assert_eq!(renamed, parse(&code.replace('k', "j")));
+	}
+}
@@ -3,15 +3,29 @@
 use std::rc::Rc;
 
 use peg::parser;
+mod explain;
 mod expr;
+pub mod fold;
+pub use explain::{explain_error, SyntaxErrorExplanation};
 pub use expr::*;
+pub use fold::Folder;
 pub use jrsonnet_interner::IStr;
 pub use peg;
 mod location;
+mod recovery;
+mod resolve;
 mod source;
+mod tokens;
 mod unescape;
+mod unparse;
+pub mod visit;
 pub use location::CodeLocation;
+pub use recovery::{parse_recovering, RecoveredParse};
+pub use resolve::{resolve, resolve_scoped, UndefinedVariable};
 pub use source::{Source, SourceDirectory, SourceFile, SourcePath, SourcePathT, SourceVirtual};
+pub use tokens::{Token, TokenKind};
+pub use unparse::unparse;
+pub use visit::Visitor;
 
 pub struct ParserSettings {
 	pub file_name: Source,
@@ -35,11 +49,10 @@ parser! {
 		rule eof() = quiet!{![_]} / expected!("<eof>")
 		rule eol() = "\n" / eof()
 
+		rule line_comment() = ("//" / "#") (!eol()[_])*
+		rule block_comment() = "/*" ("\\*/" / "\\\\" / (!("*/")[_]))* "*/"
 		/// Standard C-like comments
-		rule comment()
-			= "//" (!eol()[_])* eol()
-			/ "/*" ("\\*/" / "\\\\" / (!("*/")[_]))* "*/"
-			/ "#" (!eol()[_])* eol()
+		rule comment() = line_comment() eol() / block_comment()
 
 		rule single_whitespace() = quiet!{([' ' | '\r' | '\n' | '\t'] / comment())} / expected!("<whitespace>")
 		rule _() = quiet!{([' ' | '\r' | '\n' | '\t']+) / comment()}* / expected!("<whitespace>")
@@ -54,9 +67,12 @@ parser! {
 		/// Number in scientific notation format
 		rule number() -> f64 = quiet!{a:$(uint_str() ("." uint_str())? (['e'|'E'] (s:['+'|'-'])? uint_str())?) {? a.parse().map_err(|_| "<number>") }} / expected!("<number>")
 
-		/// Reserved word followed by any non-alphanumberic
-		rule reserved() = ("assert" / "else" / "error" / "false" / "for" / "function" / "if" / "import" / "importstr" / "importbin" / "in" / "local" / "null" / "tailstrict" / "then" / "self" / "super" / "true") end_of_ident()
+		/// Reserved word followed by any non-alphanumberic.
+		/// Longer words go first, as choice isn't retried after `end_of_ident` failure
+		rule reserved() = ("assert" / "else" / "error" / "false" / "for" / "function" / "if" / "importstr" / "importbin" / "import" / "in" / "local" / "null" / "tailstrict" / "then" / "self" / "super" / "true") end_of_ident()
 		rule id() -> IStr = v:$(quiet!{ !reserved() alpha() (alpha() / digit())*} / expected!("<identifier>")) { v.into() }
+		rule loc_id(s: &ParserSettings) -> LocIStr
+			= a:position!() n:id() b:position!() { LocIStr(n, ExprLocation(s.file_name.clone(), a as u32, b as u32)) }
 
 		rule keyword(id: &'static str) -> ()
 			= ##parse_string_literal(id) end_of_ident()
@@ -66,8 +82,8 @@ parser! {
 			= params:param(s) ** comma() comma()? { expr::ParamsDesc(Rc::new(params)) }
 			/ { expr::ParamsDesc(Rc::new(Vec::new())) }
 
-		pub rule arg(s: &ParserSettings) -> (Option<IStr>, LocExpr)
-			= quiet! { name:(s:id() _ "=" !['='] _ {s})? expr:expr(s) {(name, expr)} }
+		pub rule arg(s: &ParserSettings) -> (Option<LocIStr>, LocExpr)
+			= quiet! { name:(n:loc_id(s) _ "=" !['='] _ {n})? expr:expr(s) {(name, expr)} }
 			/ expected!("<argument>")
 
 		pub rule args(s: &ParserSettings) -> expr::ArgsDesc
@@ -124,7 +140,7 @@ parser! {
 				#[cfg(not(feature = "exp-destruct"))] Err("experimental destructuring was not enabled")
 			}
 		pub rule destruct(s: &ParserSettings) -> expr::Destruct
-			= v:id() {expr::Destruct::Full(v)}
+			= v:loc_id(s) {expr::Destruct::Full(v)}
 			/ "?" {?
 				#[cfg(feature = "exp-destruct")] return Ok(expr::Destruct::Skip);
 				#[cfg(not(feature = "exp-destruct"))] Err("experimental destructuring was not enabled")
@@ -134,7 +150,7 @@ parser! {
 
 		pub rule bind(s: &ParserSettings) -> expr::BindSpec
 			= into:destruct(s) _ "=" _ expr:expr(s) {expr::BindSpec::Field{into, value: expr}}
-			/ name:id() _ "(" _ params:params(s) _ ")" _ "=" _ expr:expr(s) {expr::BindSpec::Function{name, params, value: expr}}
+			/ name:loc_id(s) _ "(" _ params:params(s) _ ")" _ "=" _ expr:expr(s) {expr::BindSpec::Function{name, params, value: expr}}
 
 		pub rule assertion(s: &ParserSettings) -> expr::AssertStmt
 			= keyword("assert") _ cond:expr(s) msg:(_ ":" _ e:expr(s) {e})? { expr::AssertStmt(cond, msg) }
@@ -158,16 +174,20 @@ parser! {
 			/ "\\u" hex_char() hex_char() hex_char() hex_char()
 			/ "\\x" hex_char() hex_char()
 			/ ['\\'] (quiet! { ['b' | 'f' | 'n' | 'r' | 't' | '"' | '\''] } / expected!("<escape character>"))
+		rule double_quoted() -> &'input str = ['"'] str:$(string_char(<"\"">)*) ['"'] {str}
+		rule single_quoted() -> &'input str = ['\''] str:$(string_char(<"\'">)*) ['\''] {str}
+		rule verbatim_single_quoted() -> &'input str = "@'" str:$(("''" / (!['\''][_]))*) "'" {str}
+		rule verbatim_double_quoted() -> &'input str = "@\"" str:$(("\"\"" / (!['"'][_]))*) "\"" {str}
 		pub rule string() -> String
-			= ['"'] str:$(string_char(<"\"">)*) ['"'] {? unescape::unescape(str).ok_or("<escaped string>")}
-			/ ['\''] str:$(string_char(<"\'">)*) ['\''] {? unescape::unescape(str).ok_or("<escaped string>")}
-			/ quiet!{ "@'" str:$(("''" / (!['\''][_]))*) "'" {str.replace("''", "'")}
-			/ "@\"" str:$(("\"\"" / (!['"'][_]))*) "\"" {str.replace("\"\"", "\"")}
+			= str:double_quoted() {? unescape::unescape(str).ok_or("<escaped string>")}
+			/ str:single_quoted() {? unescape::unescape(str).ok_or("<escaped string>")}
+			/ quiet!{ str:verbatim_single_quoted() {str.replace("''", "'")}
+			/ str:verbatim_double_quoted() {str.replace("\"\"", "\"")}
 			/ string_block() } / expected!("<string>")
 
 		pub rule field_name(s: &ParserSettings) -> expr::FieldName
-			= name:id() {expr::FieldName::Fixed(name)}
-			/ name:string() {expr::FieldName::Fixed(name.into())}
+			= name:loc_id(s) {expr::FieldName::Fixed(name)}
+			/ a:position!() name:string() b:position!() {expr::FieldName::Fixed(LocIStr(name.into(), ExprLocation(s.file_name.clone(), a as u32, b as u32)))}
 			/ "[" _ expr:expr(s) _ "]" {expr::FieldName::Dyn(expr)}
 		pub rule visibility() -> expr::Visibility
 			= ":::" {expr::Visibility::Unhide}
@@ -211,7 +231,7 @@ parser! {
 		pub rule ifspec(s: &ParserSettings) -> IfSpecData
 			= keyword("if") _ expr:expr(s) {IfSpecData(expr)}
 		pub rule forspec(s: &ParserSettings) -> ForSpecData
-			= keyword("for") _ id:id() _ keyword("in") _ cond:expr(s) {ForSpecData(id, cond)}
+			= keyword("for") _ id:loc_id(s) _ keyword("in") _ cond:expr(s) {ForSpecData(id, cond)}
 		pub rule compspec(s: &ParserSettings) -> Vec<expr::CompSpec>
 			= s:(i:ifspec(s) { expr::CompSpec::IfSpec(i) } / f:forspec(s) {expr::CompSpec::ForSpec(f)} ) ** _ {s}
 		pub rule local_expr(s: &ParserSettings) -> Expr
@@ -342,6 +362,28 @@ parser! {
 			}
 
 		pub rule jsonnet(s: &ParserSettings) -> LocExpr = _ e:expr(s) _ {e}
+
+		rule token_kind() -> TokenKind
+			= [' ' | '\r' | '\n' | '\t']+ {TokenKind::Whitespace}
+			/ line_comment() {TokenKind::LineComment}
+			/ block_comment() {TokenKind::BlockComment}
+			/ double_quoted() {TokenKind::DoubleQuotedString}
+			/ single_quoted() {TokenKind::SingleQuotedString}
+			/ verbatim_double_quoted() {TokenKind::VerbatimDoubleQuotedString}
+			/ verbatim_single_quoted() {TokenKind::VerbatimSingleQuotedString}
+			/ string_block() {TokenKind::TextBlock}
+			/ number() {TokenKind::Number}
+			/ (reserved() / "$") {TokenKind::Keyword}
+			/ id() {TokenKind::Identifier}
+			/ (
+				"..." / ":::" / "::" / "<<" / ">>" / "<=" / ">=" / "==" / "!=" / "&&" / "||"
+				/ ['+' | '-' | '*' | '/' | '%' | '!' | '~' | '&' | '|' | '^' | '<' | '>' | '=' | ':' | '?']
+			) {TokenKind::Operator}
+			/ ['{' | '}' | '[' | ']' | '(' | ')' | ',' | ';' | '.'] {TokenKind::Punctuation}
+			/ [_] {TokenKind::Unknown}
+		rule token() -> Token
+			= start:position!() kind:token_kind() end:position!() {Token { kind, span: start..end }}
+		pub rule tokens() -> Vec<Token> = tokens:token()* {tokens}
 	}
 }
 
@@ -349,6 +391,12 @@ pub type ParseError = peg::error::ParseError<peg::str::LineCol>;
 pub fn parse(str: &str, settings: &ParserSettings) -> Result<LocExpr, ParseError> {
 	jsonnet_parser::jsonnet(str, settings)
 }
+/// Splits code into tokens, using the same rules as [`parse`]
+///
+/// Every byte of code is covered by some token, characters which can't start any token are reported as [`TokenKind::Unknown`]
+pub fn tokenize(str: &str) -> Vec<Token> {
+	jsonnet_parser::tokens(str).expect("every character is a token")
+}
 /// Used for importstr values
 pub fn string_to_expr(str: IStr, settings: &ParserSettings) -> LocExpr {
 	let len = str.len();
@@ -390,6 +438,18 @@ pub mod tests {
 			)
 		};
 	}
+	macro_rules! li {
+		($name:expr, $from:expr, $to:expr$(,)?) => {
+			LocIStr(
+				$name.into(),
+				ExprLocation(
+					Source::new_virtual("<test>".into(), IStr::empty()),
+					$from,
+					$to,
+				),
+			)
+		};
+	}
 
 	#[test]
 	fn multiline_string() {
@@ -623,7 +683,7 @@ pub mod tests {
 						16
 					),
 					vec![CompSpec::ForSpec(ForSpecData(
-						"x".into(),
+						li!("x", 21, 22),
 						el!(Var("arr".into()), 26, 29)
 					))]
 				),
@@ -638,6 +698,13 @@ pub mod tests {
 		use Expr::*;
 		assert_eq!(parse!("null"), el!(Literal(LiteralType::Null), 0, 4));
 		assert_eq!(parse!("nulla"), el!(Var("nulla".into()), 0, 5));
+		assert_eq!(parse!("imports"), el!(Var("imports".into()), 0, 7));
+		let settings = ParserSettings {
+			file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+		};
+		for keyword in ["import", "importstr", "importbin"] {
+			assert!(parse(&format!("local {keyword} = 1; 2"), &settings).is_err());
+		}
 	}
 
 	#[test]
@@ -734,11 +801,11 @@ pub mod tests {
 							el!(Obj(ObjBody::MemberList(vec![])), 0, 2),
 							ObjBody::MemberList(vec![
 								Member::BindStmt(BindSpec::Field {
-									into: Destruct::Full("x".into()),
+									into: Destruct::Full(li!("x", 11, 12)),
 									value: el!(Num(1.0), 15, 16)
 								}),
 								Member::Field(FieldMember {
-									name: FieldName::Fixed("x".into()),
+									name: FieldName::Fixed(li!("x", 18, 19)),
 									plus: false,
 									params: None,
 									visibility: Visibility::Normal,
@@ -757,4 +824,34 @@ pub mod tests {
 			),
 		);
 	}
+
+	#[test]
+	fn identifier_spans() {
+		use crate::visit::Visitor;
+
+		struct Names<'c>(&'c str, Vec<(String, &'c str)>);
+		impl<'c> Visitor for Names<'c> {
+			fn visit_loc_istr(&mut self, name: &LocIStr) {
+				let code = &self.0[name.1 .1 as usize..name.1 .2 as usize];
+				self.1.push((name.0.to_string(), code));
+			}
+		}
+
+		let code = "local f(a, b=1) = a; {x: f(b = 2, a = 1), 'y z': [v for v in []]}";
+		let mut names = Names(code, vec![]);
+		names.visit_loc_expr(&parse!(code));
+		assert_eq!(
+			names.1,
+			vec![
+				("f".to_owned(), "f"),
+				("a".to_owned(), "a"),
+				("b".to_owned(), "b"),
+				("x".to_owned(), "x"),
+				("b".to_owned(), "b"),
+				("a".to_owned(), "a"),
+				("y z".to_owned(), "'y z'"),
+				("v".to_owned(), "v"),
+			]
+		);
+	}
 }
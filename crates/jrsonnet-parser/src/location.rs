@@ -23,6 +23,9 @@ pub fn location_to_offset(mut file: &str, mut line: usize, column: usize) -> Opt
 	Some(offset)
 }
 
+/// Maps byte offsets, as reported by parser, to locations
+///
+/// Columns and offsets of returned locations are counted in chars
 #[allow(clippy::module_name_repetitions)]
 pub fn offset_to_location(file: &str, offsets: &[u32]) -> Vec<CodeLocation> {
 	if offsets.is_empty() {
@@ -52,14 +55,15 @@ pub fn offset_to_location(file: &str, offsets: &[u32]) -> Vec<CodeLocation> {
 	];
 	let mut with_no_known_line_ending = vec![];
 	let mut this_line_offset = 0;
-	for (pos, ch) in file
-		.chars()
+	let file_end = file.chars().count();
+	for (pos, (byte_pos, ch)) in file
+		.char_indices()
 		.enumerate()
-		.chain(std::iter::once((file.len(), ' ')))
+		.chain(std::iter::once((file_end, (file.len(), ' '))))
 	{
 		column += 1;
 		match offset_map.last() {
-			Some(x) if x.0 == pos as u32 => {
+			Some(x) if x.0 == byte_pos as u32 => {
 				let out_idx = x.1;
 				with_no_known_line_ending.push(out_idx);
 				out[out_idx].offset = pos;
@@ -79,12 +83,11 @@ pub fn offset_to_location(file: &str, offsets: &[u32]) -> Vec<CodeLocation> {
 			}
 			this_line_offset = pos + 1;
 
-			if pos == max_offset as usize + 1 {
+			if byte_pos == max_offset as usize + 1 {
 				break;
 			}
 		}
 	}
-	let file_end = file.chars().count();
 	for idx in with_no_known_line_ending {
 		out[idx].line_end_offset = file_end;
 	}
@@ -121,4 +124,29 @@ pub mod tests {
 			]
 		)
 	}
+
+	#[test]
+	fn non_ascii() {
+		// "é" is 2 bytes long
+		let file = "\"é\"\n é + x";
+		assert_eq!(
+			offset_to_location(file, &[6, 10]),
+			vec![
+				CodeLocation {
+					offset: 5,
+					line: 2,
+					column: 3,
+					line_start_offset: 4,
+					line_end_offset: 10,
+				},
+				CodeLocation {
+					offset: 8,
+					line: 2,
+					column: 6,
+					line_start_offset: 4,
+					line_end_offset: 10,
+				}
+			]
+		);
+	}
 }
@@ -0,0 +1,329 @@
+//! Syntax error recovery
+//!
+//! Peg grammar stops at the first error, so recovery is implemented by re-parsing: statement containing
+//! the error (delimited by `,`, `;` and brackets) is blanked out, and the code is parsed again.
+//! Blanking keeps byte offsets intact, so locations in partial AST still point into the original code.
+
+use peg::Parse;
+
+use crate::{jsonnet_parser, LocExpr, ParseError, ParserSettings};
+
+/// Recovery stops after this many errors
+const MAX_ERRORS: usize = 100;
+
+/// Result of [`parse_recovering`]
+#[derive(Debug)]
+pub struct RecoveredParse {
+	/// Partial AST, statements containing syntax errors are skipped.
+	/// `None` if nothing could be recovered
+	pub expr: Option<LocExpr>,
+	/// All syntax errors, ordered by location
+	pub errors: Vec<ParseError>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Delimiter {
+	Open(u8),
+	Close,
+	Separator,
+}
+
+/// Finds brackets and separators, skipping strings and comments
+fn delimiters(code: &[u8]) -> Vec<(usize, Delimiter)> {
+	fn find(code: &[u8], from: usize, needle: &[u8]) -> usize {
+		code[from.min(code.len())..]
+			.windows(needle.len())
+			.position(|w| w == needle)
+			.map_or(code.len(), |p| from + p + needle.len())
+	}
+	let mut out = Vec::new();
+	let mut i = 0;
+	while i < code.len() {
+		match code[i] {
+			b'#' => i = find(code, i, b"\n"),
+			b'/' if code.get(i + 1) == Some(&b'/') => i = find(code, i, b"\n"),
+			b'/' if code.get(i + 1) == Some(&b'*') => i = find(code, i + 2, b"*/"),
+			b'|' if code[i..].starts_with(b"|||") => {
+				// Block ends with the line, starting with `|||`
+				let mut line = find(code, i + 3, b"\n");
+				i = code.len();
+				while line < code.len() {
+					let indent = code[line..]
+						.iter()
+						.take_while(|c| matches!(c, b' ' | b'\t'))
+						.count();
+					if code[line + indent..].starts_with(b"|||") {
+						i = line + indent + 3;
+						break;
+					}
+					line = find(code, line, b"\n");
+				}
+			}
+			q @ (b'"' | b'\'') => {
+				i += 1;
+				while i < code.len() && code[i] != q {
+					i += if code[i] == b'\\' { 2 } else { 1 };
+				}
+				i += 1;
+			}
+			b'@' if matches!(code.get(i + 1), Some(b'"' | b'\'')) => {
+				let q = code[i + 1];
+				i += 2;
+				loop {
+					match code.get(i) {
+						None => break,
+						Some(&c) if c == q && code.get(i + 1) == Some(&q) => i += 2,
+						Some(&c) if c == q => {
+							i += 1;
+							break;
+						}
+						Some(_) => i += 1,
+					}
+				}
+			}
+			c @ (b'{' | b'[' | b'(') => {
+				out.push((i, Delimiter::Open(c)));
+				i += 1;
+			}
+			b'}' | b']' | b')' => {
+				out.push((i, Delimiter::Close));
+				i += 1;
+			}
+			b',' | b';' => {
+				out.push((i, Delimiter::Separator));
+				i += 1;
+			}
+			_ => i += 1,
+		}
+	}
+	out
+}
+
+/// Byte range of the statement, containing `pos`.
+/// Trailing separator is included, enclosing brackets are not
+fn statement_span(delimiters: &[(usize, Delimiter)], len: usize, pos: usize) -> (usize, usize) {
+	let split = delimiters.partition_point(|(off, _)| *off < pos);
+
+	let mut start = 0;
+	let mut depth = 0;
+	for (off, delimiter) in delimiters[..split].iter().rev() {
+		match delimiter {
+			Delimiter::Close => depth += 1,
+			Delimiter::Open(_) if depth > 0 => depth -= 1,
+			Delimiter::Open(_) | Delimiter::Separator if depth == 0 => {
+				start = off + 1;
+				break;
+			}
+			_ => {}
+		}
+	}
+
+	let mut end = len;
+	let mut depth = 0;
+	for (off, delimiter) in &delimiters[split..] {
+		match delimiter {
+			Delimiter::Open(_) => depth += 1,
+			Delimiter::Close if depth == 0 => {
+				end = *off;
+				break;
+			}
+			Delimiter::Close => depth -= 1,
+			Delimiter::Separator if depth == 0 => {
+				end = off + 1;
+				break;
+			}
+			Delimiter::Separator => {}
+		}
+	}
+	(start, end)
+}
+
+/// Byte range of the innermost brackets, containing `pos`, brackets included, together with the
+/// opening bracket. Unclosed brackets span to the end of code
+pub fn enclosing_brackets(code: &[u8], pos: usize) -> Option<(usize, u8, usize)> {
+	let delimiters = delimiters(code);
+	let split = delimiters.partition_point(|(off, _)| *off < pos);
+
+	let mut stack = Vec::new();
+	for (off, delimiter) in &delimiters[..split] {
+		match delimiter {
+			Delimiter::Open(c) => stack.push((*off, *c)),
+			Delimiter::Close => {
+				stack.pop();
+			}
+			Delimiter::Separator => {}
+		}
+	}
+	let (start, bracket) = stack.pop()?;
+
+	let mut end = code.len();
+	let mut depth = 0;
+	for (off, delimiter) in &delimiters[split..] {
+		match delimiter {
+			Delimiter::Open(_) => depth += 1,
+			Delimiter::Close if depth == 0 => {
+				end = off + 1;
+				break;
+			}
+			Delimiter::Close => depth -= 1,
+			Delimiter::Separator => {}
+		}
+	}
+	Some((start, bracket, end))
+}
+
+/// Closing brackets for all brackets, left open at the end of code
+fn unclosed(delimiters: &[(usize, Delimiter)]) -> Vec<u8> {
+	let mut stack = Vec::new();
+	for (_, delimiter) in delimiters {
+		match delimiter {
+			Delimiter::Open(c) => stack.push(*c),
+			Delimiter::Close => {
+				stack.pop();
+			}
+			Delimiter::Separator => {}
+		}
+	}
+	stack
+		.into_iter()
+		.rev()
+		.map(|c| match c {
+			b'{' => b'}',
+			b'[' => b']',
+			_ => b')',
+		})
+		.collect()
+}
+
+/// Parses code, recovering from syntax errors at statement boundaries
+///
+/// Unlike [`crate::parse`], which stops at the first error, reports all syntax errors found in code,
+/// together with AST for the parts of code without errors
+pub fn parse_recovering(code: &str, settings: &ParserSettings) -> RecoveredParse {
+	let mut current = code.as_bytes().to_vec();
+	let mut errors: Vec<ParseError> = Vec::new();
+	loop {
+		let str = std::str::from_utf8(&current).expect("only ascii is replaced");
+		let error = match jsonnet_parser::jsonnet(str, settings) {
+			Ok(expr) => {
+				errors.sort_by_key(|e| e.location.offset);
+				return RecoveredParse {
+					expr: Some(expr),
+					errors,
+				};
+			}
+			Err(e) => e,
+		};
+		let offset = error.location.offset;
+		if !errors.iter().any(|e| e.location.offset == offset) {
+			let mut original = offset.min(code.len());
+			while !code.is_char_boundary(original) {
+				original -= 1;
+			}
+			errors.push(ParseError {
+				location: code.position_repr(original),
+				expected: error.expected,
+			});
+		}
+		if errors.len() >= MAX_ERRORS {
+			break;
+		}
+
+		let delimiters = delimiters(&current);
+		let closers = unclosed(&delimiters);
+		if offset >= current.len() && !closers.is_empty() {
+			current.extend(closers);
+			continue;
+		}
+		let (start, end) = statement_span(&delimiters, current.len(), offset);
+		let mut changed = false;
+		for c in &mut current[start..end] {
+			if !matches!(c, b' ' | b'\n') {
+				*c = b' ';
+				changed = true;
+			}
+		}
+		if !changed {
+			break;
+		}
+	}
+	errors.sort_by_key(|e| e.location.offset);
+	RecoveredParse { expr: None, errors }
+}
+
+#[cfg(test)]
+pub mod tests {
+	use jrsonnet_interner::IStr;
+
+	use super::parse_recovering;
+	use crate::{parse, source::Source, ParserSettings};
+
+	fn recover(code: &str) -> (Option<String>, Vec<(usize, usize)>) {
+		let settings = ParserSettings {
+			file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+		};
+		let parsed = parse_recovering(code, &settings);
+		(
+			parsed.expr.map(|e| format!("{:?}", e.0)),
+			parsed
+				.errors
+				.iter()
+				.map(|e| (e.location.line, e.location.column))
+				.collect(),
+		)
+	}
+	fn parsed(code: &str) -> Option<String> {
+		let settings = ParserSettings {
+			file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+		};
+		Some(format!("{:?}", parse(code, &settings).unwrap().0))
+	}
+
+	#[test]
+	fn valid_code() {
+		assert_eq!(recover("{a: [1, 2]}"), (parsed("{a: [1, 2]}"), vec![]));
+	}
+
+	#[test]
+	fn object_members() {
+		assert_eq!(
+			recover("{\n  a: 1 +,\n  b: 2,\n  c: ,\n  d: 'x' }"),
+			(
+				parsed("{\n         \n  b: 2,\n      \n  d: 'x' }"),
+				vec![(2, 9), (4, 6)]
+			)
+		);
+	}
+
+	#[test]
+	fn locals_and_args() {
+		assert_eq!(
+			recover("local a = ; local b = 1; f(b, 1 2, 3)"),
+			(
+				parsed("            local b = 1; f(b,      3)"),
+				vec![(1, 11), (1, 33)]
+			)
+		);
+	}
+
+	#[test]
+	fn unclosed_brackets() {
+		assert_eq!(recover("{a: [1, 2"), (parsed("{a: [1, 2]}"), vec![(1, 10)]));
+	}
+
+	#[test]
+	fn delimiters_in_strings_and_comments() {
+		assert_eq!(
+			recover("[',', \"}\", /* ] */ |||\n  ;\n|||, # ,\n  1 +]"),
+			(
+				parsed("[',', \"}\", /* ] */ |||\n  ;\n|||, # ,\n     ]"),
+				vec![(4, 6)]
+			)
+		);
+	}
+
+	#[test]
+	fn nothing_to_recover() {
+		assert_eq!(recover("1 +"), (None, vec![(1, 4)]));
+	}
+}
@@ -0,0 +1,433 @@
+//! Lexical scope resolution
+//!
+//! Replaces every [`Expr::Var`] referencing local variable with [`Expr::ResolvedVar`], which points
+//! directly at the slot of variable value, so evaluator doesn't need to look it up by name.
+//!
+//! Scope is created for every construct, which defines at least one variable: `local` expression,
+//! function parameters, object locals (shared by all object members), and every `for` of comprehension.
+//! Variables are placed in scope in order of declaration, destructuring patterns are flattened left to right.
+//! References to globals passed to [`resolve`] are kept as [`Expr::Var`], while [`resolve_scoped`] treats
+//! globals as outer scopes of the code, and resolves them to slots too.
+
+use std::rc::Rc;
+
+use jrsonnet_interner::IStr;
+
+use crate::{
+	expr::{
+		BindSpec, CompSpec, Expr, ExprLocation, FieldMember, ForSpecData, LocExpr, Member, ObjBody,
+		ObjComp, ParamsDesc, VarSlot,
+	},
+	fold::{self, Folder},
+};
+
+/// Variable, which is neither defined in enclosing scopes, nor is global
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndefinedVariable {
+	pub name: IStr,
+	pub location: ExprLocation,
+	/// Variables, visible at the usage location, innermost first
+	pub visible: Vec<IStr>,
+}
+
+fn bind_names<'b>(binds: impl IntoIterator<Item = &'b BindSpec>) -> Vec<IStr> {
+	let mut out = Vec::new();
+	for bind in binds {
+		match bind {
+			BindSpec::Field { into, .. } => into.collect_names(&mut out),
+			BindSpec::Function { name, .. } => out.push(name.0.clone()),
+		}
+	}
+	out
+}
+
+struct Resolver<'g> {
+	/// Looked up by name
+	globals: &'g [IStr],
+	/// Scopes, enclosing the code, innermost first, empty ones included
+	global_scopes: &'g [Vec<IStr>],
+	/// Only non-empty scopes are kept, as evaluator doesn't create empty ones
+	scopes: Vec<Vec<IStr>>,
+	undefined: Vec<UndefinedVariable>,
+}
+
+impl Resolver<'_> {
+	fn with_scope<T>(&mut self, names: Vec<IStr>, f: impl FnOnce(&mut Self) -> T) -> T {
+		if names.is_empty() {
+			return f(self);
+		}
+		self.scopes.push(names);
+		let out = f(self);
+		self.scopes.pop();
+		out
+	}
+
+	fn var(&mut self, name: IStr, location: &ExprLocation) -> Expr {
+		for (depth, scope) in self.scopes.iter().rev().enumerate() {
+			if let Some(index) = scope.iter().position(|n| *n == name) {
+				return Expr::ResolvedVar(
+					name,
+					VarSlot {
+						depth: depth as u32,
+						index: index as u32,
+					},
+				);
+			}
+		}
+		for (layer, scope) in self.global_scopes.iter().enumerate() {
+			if let Some(index) = scope.iter().position(|n| *n == name) {
+				return Expr::ResolvedVar(
+					name,
+					VarSlot {
+						depth: (self.scopes.len() + layer) as u32,
+						index: index as u32,
+					},
+				);
+			}
+		}
+		if !self.globals.contains(&name) {
+			self.undefined.push(UndefinedVariable {
+				name: name.clone(),
+				location: location.clone(),
+				visible: self
+					.scopes
+					.iter()
+					.rev()
+					.flatten()
+					.chain(self.global_scopes.iter().flatten())
+					.chain(self.globals)
+					.cloned()
+					.collect(),
+			});
+		}
+		Expr::Var(name)
+	}
+
+	fn function(&mut self, params: ParamsDesc, body: LocExpr) -> (ParamsDesc, LocExpr) {
+		let mut names = Vec::new();
+		for param in params.iter() {
+			param.0.collect_names(&mut names);
+		}
+		self.with_scope(names, |r| {
+			(r.fold_params_desc(params), r.fold_loc_expr(body))
+		})
+	}
+
+	/// Scopes, created by `for` specs are left on stack, returns number of them
+	fn comp_specs(&mut self, specs: Vec<CompSpec>) -> (Vec<CompSpec>, usize) {
+		let mut pushed = 0;
+		let specs = specs
+			.into_iter()
+			.map(|spec| match spec {
+				CompSpec::IfSpec(spec) => CompSpec::IfSpec(self.fold_if_spec(spec)),
+				CompSpec::ForSpec(ForSpecData(var, over)) => {
+					let over = self.fold_loc_expr(over);
+					self.scopes.push(vec![var.0.clone()]);
+					pushed += 1;
+					CompSpec::ForSpec(ForSpecData(var, over))
+				}
+			})
+			.collect();
+		(specs, pushed)
+	}
+
+	fn pop_scopes(&mut self, count: usize) {
+		self.scopes.truncate(self.scopes.len() - count);
+	}
+}
+
+impl Folder for Resolver<'_> {
+	fn fold_loc_expr(&mut self, expr: LocExpr) -> LocExpr {
+		if let Expr::Var(name) = &*expr.0 {
+			let resolved = self.var(name.clone(), &expr.1);
+			return LocExpr(Rc::new(resolved), expr.1);
+		}
+		fold::walk_loc_expr(self, expr)
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		match expr {
+			Expr::LocalExpr(binds, body) => {
+				let names = bind_names(&binds);
+				self.with_scope(names, |r| {
+					let binds = binds.into_iter().map(|b| r.fold_bind_spec(b)).collect();
+					Expr::LocalExpr(binds, r.fold_loc_expr(body))
+				})
+			}
+			Expr::Function(params, body) => {
+				let (params, body) = self.function(params, body);
+				Expr::Function(params, body)
+			}
+			Expr::ArrComp(value, specs) => {
+				let (specs, pushed) = self.comp_specs(specs);
+				let value = self.fold_loc_expr(value);
+				self.pop_scopes(pushed);
+				Expr::ArrComp(value, specs)
+			}
+			expr => fold::walk_expr(self, expr),
+		}
+	}
+
+	fn fold_obj_body(&mut self, body: ObjBody) -> ObjBody {
+		let members = match body {
+			ObjBody::MemberList(members) => members,
+			ObjBody::ObjComp(comp) => return ObjBody::ObjComp(self.fold_obj_comp(comp)),
+		};
+		// Field names are evaluated outside of the object
+		let members: Vec<Member> = members
+			.into_iter()
+			.map(|member| match member {
+				Member::Field(mut field) => {
+					field.name = self.fold_field_name(field.name);
+					Member::Field(field)
+				}
+				member => member,
+			})
+			.collect();
+		let names = bind_names(members.iter().filter_map(|m| match m {
+			Member::BindStmt(bind) => Some(bind),
+			_ => None,
+		}));
+		self.with_scope(names, |r| {
+			ObjBody::MemberList(
+				members
+					.into_iter()
+					.map(|member| match member {
+						Member::Field(field) => {
+							let (params, value) = match field.params {
+								Some(params) => {
+									let (params, value) = r.function(params, field.value);
+									(Some(params), value)
+								}
+								None => (None, r.fold_loc_expr(field.value)),
+							};
+							Member::Field(FieldMember {
+								params,
+								value,
+								..field
+							})
+						}
+						Member::BindStmt(bind) => Member::BindStmt(r.fold_bind_spec(bind)),
+						Member::AssertStmt(assert) => {
+							Member::AssertStmt(r.fold_assert_stmt(assert))
+						}
+					})
+					.collect(),
+			)
+		})
+	}
+
+	fn fold_obj_comp(&mut self, comp: ObjComp) -> ObjComp {
+		let (compspecs, pushed) = self.comp_specs(comp.compspecs);
+		let key = self.fold_loc_expr(comp.key);
+		let names = bind_names(comp.pre_locals.iter().chain(&comp.post_locals));
+		let comp = self.with_scope(names, |r| ObjComp {
+			pre_locals: comp
+				.pre_locals
+				.into_iter()
+				.map(|b| r.fold_bind_spec(b))
+				.collect(),
+			key,
+			plus: comp.plus,
+			value: r.fold_loc_expr(comp.value),
+			post_locals: comp
+				.post_locals
+				.into_iter()
+				.map(|b| r.fold_bind_spec(b))
+				.collect(),
+			compspecs,
+		});
+		self.pop_scopes(pushed);
+		comp
+	}
+
+	fn fold_bind_spec(&mut self, bind: BindSpec) -> BindSpec {
+		match bind {
+			BindSpec::Function {
+				name,
+				params,
+				value,
+			} => {
+				let (params, value) = self.function(params, value);
+				BindSpec::Function {
+					name,
+					params,
+					value,
+				}
+			}
+			bind => fold::walk_bind_spec(self, bind),
+		}
+	}
+}
+
+/// Resolves local variables to their slots, see [module documentation](self)
+///
+/// Fails with every variable, which is neither local nor one of `globals`
+pub fn resolve(expr: LocExpr, globals: &[IStr]) -> Result<LocExpr, Vec<UndefinedVariable>> {
+	run(
+		Resolver {
+			globals,
+			global_scopes: &[],
+			scopes: Vec::new(),
+			undefined: Vec::new(),
+		},
+		expr,
+	)
+}
+
+/// Resolves both local variables and globals to their slots
+///
+/// `scopes` are the scopes code will be evaluated in, innermost first. Empty scopes should be included,
+/// as every one of them is counted in slot depth.
+pub fn resolve_scoped(
+	expr: LocExpr,
+	scopes: &[Vec<IStr>],
+) -> Result<LocExpr, Vec<UndefinedVariable>> {
+	run(
+		Resolver {
+			globals: &[],
+			global_scopes: scopes,
+			scopes: Vec::new(),
+			undefined: Vec::new(),
+		},
+		expr,
+	)
+}
+
+fn run(mut resolver: Resolver<'_>, expr: LocExpr) -> Result<LocExpr, Vec<UndefinedVariable>> {
+	let expr = resolver.fold_loc_expr(expr);
+	if resolver.undefined.is_empty() {
+		Ok(expr)
+	} else {
+		Err(resolver.undefined)
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use jrsonnet_interner::IStr;
+
+	use super::{resolve, resolve_scoped};
+	use crate::{
+		expr::{Expr, VarSlot},
+		source::Source,
+		visit::{self, Visitor},
+		ParserSettings,
+	};
+
+	/// Collects variable references as `name@depth:index`, or just `name` for globals
+	#[derive(Default)]
+	struct Vars(Vec<String>);
+	impl Visitor for Vars {
+		fn visit_expr(&mut self, expr: &Expr) {
+			match expr {
+				Expr::Var(name) => self.0.push(name.to_string()),
+				Expr::ResolvedVar(name, VarSlot { depth, index }) => {
+					self.0.push(format!("{name}@{depth}:{index}"));
+				}
+				_ => {}
+			}
+			visit::walk_expr(self, expr);
+		}
+	}
+
+	fn resolved(code: &str) -> Vec<String> {
+		let expr = crate::parse(
+			code,
+			&ParserSettings {
+				file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+			},
+		)
+		.unwrap();
+		let expr = resolve(expr, &["std".into()]).expect("no undefined variables");
+		let mut vars = Vars::default();
+		vars.visit_loc_expr(&expr);
+		vars.0
+	}
+
+	#[test]
+	fn locals() {
+		assert_eq!(
+			resolved("local a = 1, b = a; local c = b; [a, b, c, std]"),
+			["a@0:0", "b@1:1", "a@1:0", "b@1:1", "c@0:0", "std"]
+		);
+		assert_eq!(
+			resolved("local f(x, y=x) = f(y, x); f"),
+			["x@0:0", "f@1:0", "y@0:1", "x@0:0", "f@0:0"]
+		);
+		// Shadowing
+		assert_eq!(resolved("local a = 1; local a = 2; a"), ["a@0:0"]);
+	}
+
+	#[test]
+	fn objects() {
+		assert_eq!(
+			resolved("local x = 1; { [x]: l, local l = x, m(p):: p + l, assert l }"),
+			["x@0:0", "l@0:0", "x@1:0", "p@0:0", "l@1:0", "l@0:0"]
+		);
+		// Object without locals doesn't create scope
+		assert_eq!(resolved("local x = 1; { a: x }"), ["x@0:0"]);
+	}
+
+	#[test]
+	fn comprehensions() {
+		assert_eq!(
+			resolved("local xs = []; [[x, y] for x in xs if x for y in [x, xs]]"),
+			["x@1:0", "y@0:0", "xs@0:0", "x@0:0", "x@0:0", "xs@1:0"]
+		);
+		assert_eq!(
+			resolved("{ local a = k, [k]: a + b, local b = 1, for k in std }"),
+			["k@1:0", "k@0:0", "a@0:0", "b@0:1", "std"]
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "exp-destruct")]
+	fn destructuring() {
+		assert_eq!(
+			resolved("local [a, ?, ...r, { b, c: [d] = a }] = [], e = 1; [a, b, d, e, r]"),
+			["a@0:0", "a@0:0", "b@0:2", "d@0:3", "e@0:4", "r@0:1"]
+		);
+	}
+
+	#[test]
+	fn scoped_globals() {
+		let expr = crate::parse(
+			"local a = 1; [a, std, b, function(std) std]",
+			&ParserSettings {
+				file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+			},
+		)
+		.unwrap();
+		let expr = resolve_scoped(
+			expr,
+			&[vec!["b".into()], vec![], vec!["x".into(), "std".into()]],
+		)
+		.expect("no undefined variables");
+		let mut vars = Vars::default();
+		vars.visit_loc_expr(&expr);
+		assert_eq!(vars.0, ["a@0:0", "std@3:1", "b@1:0", "std@0:0"]);
+	}
+
+	#[test]
+	fn undefined() {
+		let expr = crate::parse(
+			"local a = 1; { b: a + c, d: function(e) e + f }",
+			&ParserSettings {
+				file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+			},
+		)
+		.unwrap();
+		let undefined = resolve(expr, &["std".into()]).unwrap_err();
+		assert_eq!(
+			undefined
+				.iter()
+				.map(|v| (v.name.to_string(), v.location.1, v.visible.clone()))
+				.collect::<Vec<_>>(),
+			[
+				("c".into(), 22, vec!["a".into(), "std".into()]),
+				("f".into(), 44, vec!["e".into(), "a".into(), "std".into()]),
+			]
+		);
+	}
+}
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+/// Kind of lexical token, see [`crate::tokenize`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+	/// Spaces, tabs and newlines
+	Whitespace,
+	/// `// comment` or `# comment`, not including the line ending
+	LineComment,
+	/// `/* comment */`
+	BlockComment,
+	/// Reserved words: `local`, `function`, `if`, `self`, `true`, ..., and `$`
+	Keyword,
+	Identifier,
+	Number,
+	/// `"string"`
+	DoubleQuotedString,
+	/// `'string'`
+	SingleQuotedString,
+	/// `@"string"`
+	VerbatimDoubleQuotedString,
+	/// `@'string'`
+	VerbatimSingleQuotedString,
+	/// `|||` text block
+	TextBlock,
+	/// Binary and unary operators, `=`, `:`, `::`, `:::` and `...`
+	Operator,
+	/// Brackets, `,`, `;` and `.`
+	Punctuation,
+	/// Character, which can't start any token, i.e unterminated string quote
+	Unknown,
+}
+
+/// Token with its byte span in source code
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Token {
+	pub kind: TokenKind,
+	pub span: Range<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::TokenKind::{self, *};
+	use crate::tokenize;
+
+	fn kinds(code: &str) -> Vec<(TokenKind, &str)> {
+		tokenize(code)
+			.into_iter()
+			.filter(|t| t.kind != Whitespace)
+			.map(|t| (t.kind, &code[t.span]))
+			.collect()
+	}
+
+	#[test]
+	fn basic() {
+		assert_eq!(
+			kinds("local a = std.length([1.5e3]); // done\n$.b"),
+			vec![
+				(Keyword, "local"),
+				(Identifier, "a"),
+				(Operator, "="),
+				(Identifier, "std"),
+				(Punctuation, "."),
+				(Identifier, "length"),
+				(Punctuation, "("),
+				(Punctuation, "["),
+				(Number, "1.5e3"),
+				(Punctuation, "]"),
+				(Punctuation, ")"),
+				(Punctuation, ";"),
+				(LineComment, "// done"),
+				(Keyword, "$"),
+				(Punctuation, "."),
+				(Identifier, "b"),
+			]
+		);
+	}
+
+	#[test]
+	fn keywords_and_identifiers() {
+		assert_eq!(
+			kinds("iff if importstr imports self_ null"),
+			vec![
+				(Identifier, "iff"),
+				(Keyword, "if"),
+				(Keyword, "importstr"),
+				(Identifier, "imports"),
+				(Identifier, "self_"),
+				(Keyword, "null"),
+			]
+		);
+	}
+
+	#[test]
+	fn strings() {
+		assert_eq!(
+			kinds("'a\\'' \"b\" @'c''' @\"d\\\" |||\n  e\n||| /* f */"),
+			vec![
+				(SingleQuotedString, "'a\\''"),
+				(DoubleQuotedString, "\"b\""),
+				(VerbatimSingleQuotedString, "@'c'''"),
+				(VerbatimDoubleQuotedString, "@\"d\\\""),
+				(TextBlock, "|||\n  e\n|||"),
+				(BlockComment, "/* f */"),
+			]
+		);
+	}
+
+	#[test]
+	fn operators() {
+		assert_eq!(
+			kinds("a::: b <= c || !d ... {x+: 1}"),
+			vec![
+				(Identifier, "a"),
+				(Operator, ":::"),
+				(Identifier, "b"),
+				(Operator, "<="),
+				(Identifier, "c"),
+				(Operator, "||"),
+				(Operator, "!"),
+				(Identifier, "d"),
+				(Operator, "..."),
+				(Punctuation, "{"),
+				(Identifier, "x"),
+				(Operator, "+"),
+				(Operator, ":"),
+				(Number, "1"),
+				(Punctuation, "}"),
+			]
+		);
+	}
+
+	#[test]
+	fn covers_whole_input() {
+		let code = "local x = \"unterminated\n# c";
+		let tokens = tokenize(code);
+		let mut offset = 0;
+		for token in &tokens {
+			assert_eq!(token.span.start, offset);
+			offset = token.span.end;
+		}
+		assert_eq!(offset, code.len());
+		assert!(tokens.iter().any(|t| t.kind == Unknown));
+	}
+}
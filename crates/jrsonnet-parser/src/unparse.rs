@@ -0,0 +1,775 @@
+//! Rendering of AST back to jsonnet source
+//!
+//! Output is deterministic, and parses back to the same AST (ignoring locations). The only exception
+//! is parentheses: if AST was built by hand, [`Expr::Parened`] is added where it is required for the
+//! code to be parsed the same way, i.e `(a + b) * c`.
+//!
+//! Jsonnet has no literals for negative and non-finite numbers, negative numbers are printed as unary
+//! minus applied to the positive number, and non-finite numbers have no representation at all.
+
+use std::fmt::Write;
+
+use crate::{
+	explain::RESERVED,
+	expr::{
+		ArgsDesc, AssertStmt, BinaryOpType, BindSpec, CompSpec, Destruct, Expr, FieldMember,
+		FieldName, LiteralType, LocExpr, Member, ObjBody, ObjComp, ParamsDesc, Visibility,
+	},
+};
+
+const INDENT: &str = "  ";
+
+/// Expressions, extending as far right as possible (`local`, `if`, `function`, ...),
+/// need to be parenthesized when used as operands
+const PREC_OPEN: u8 = 0;
+const PREC_UNARY: u8 = 11;
+const PREC_POSTFIX: u8 = 12;
+const PREC_ATOM: u8 = 13;
+
+fn binary_precedence(op: BinaryOpType) -> u8 {
+	use BinaryOpType::*;
+	match op {
+		Or => 1,
+		And => 2,
+		BitOr => 3,
+		BitXor => 4,
+		BitAnd => 5,
+		Eq | Neq => 6,
+		Lt | Gt | Lte | Gte | In => 7,
+		Lhs | Rhs => 8,
+		Add | Sub => 9,
+		Mul | Div | Mod => 10,
+	}
+}
+
+fn precedence(expr: &Expr) -> u8 {
+	match expr {
+		Expr::Num(n) if n.is_sign_negative() => PREC_UNARY,
+		Expr::Literal(_)
+		| Expr::Str(_)
+		| Expr::Num(_)
+		| Expr::Var(_)
+		| Expr::ResolvedVar(_, _)
+		| Expr::Arr(_)
+		| Expr::ArrComp(_, _)
+		| Expr::Obj(_)
+		| Expr::Parened(_)
+		| Expr::Import(_)
+		| Expr::ImportStr(_)
+		| Expr::ImportBin(_) => PREC_ATOM,
+		Expr::UnaryOp(_, _) => PREC_UNARY,
+		Expr::BinaryOp(_, op, _) => binary_precedence(*op),
+		Expr::Apply(_, _, _) | Expr::Index(_, _) | Expr::Slice(_, _) | Expr::ObjExtend(_, _) => {
+			PREC_POSTFIX
+		}
+		Expr::AssertExpr(_, _)
+		| Expr::LocalExpr(_, _)
+		| Expr::ErrorStmt(_)
+		| Expr::Function(_, _)
+		| Expr::IfElse { .. } => PREC_OPEN,
+	}
+}
+
+/// Does expression end with `if` without `else`, which would take the `else` of enclosing `if`
+fn has_dangling_if(expr: &Expr) -> bool {
+	match expr {
+		Expr::IfElse {
+			cond_else: None, ..
+		} => true,
+		Expr::IfElse {
+			cond_else: Some(body),
+			..
+		}
+		| Expr::AssertExpr(_, body)
+		| Expr::LocalExpr(_, body)
+		| Expr::ErrorStmt(body)
+		| Expr::Function(_, body) => has_dangling_if(&body.0),
+		_ => false,
+	}
+}
+
+fn is_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	chars
+		.next()
+		.is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+		&& !RESERVED.contains(&name)
+}
+
+/// Can string be written as `|||` text block
+fn fits_text_block(str: &str) -> bool {
+	str.ends_with('\n')
+		&& str.matches('\n').count() > 1
+		&& !str.chars().any(|c| c.is_control() && c != '\n' && c != '\t')
+		// Text block indentation is taken from the first line, so it can't start with whitespace
+		&& str
+			.split('\n')
+			.find(|l| !l.is_empty())
+			.is_some_and(|l| !l.starts_with([' ', '\t']))
+}
+
+struct Printer {
+	out: String,
+	indent: usize,
+}
+
+impl Printer {
+	fn newline(&mut self) {
+		self.out.push('\n');
+		for _ in 0..self.indent {
+			self.out.push_str(INDENT);
+		}
+	}
+
+	fn comma_separated<T>(&mut self, items: &[T], mut f: impl FnMut(&mut Self, &T)) {
+		for (i, item) in items.iter().enumerate() {
+			if i != 0 {
+				self.out.push_str(", ");
+			}
+			f(self, item);
+		}
+	}
+
+	fn string(&mut self, str: &str, allow_block: bool) {
+		if allow_block && fits_text_block(str) {
+			self.out.push_str("|||\n");
+			for line in str.split_inclusive('\n') {
+				if line != "\n" {
+					for _ in 0..=self.indent {
+						self.out.push_str(INDENT);
+					}
+				}
+				self.out.push_str(line);
+			}
+			for _ in 0..self.indent {
+				self.out.push_str(INDENT);
+			}
+			self.out.push_str("|||");
+			return;
+		}
+		let quote = if str.contains('"') && !str.contains('\'') {
+			'\''
+		} else {
+			'"'
+		};
+		if str.contains('\\') && !str.chars().any(char::is_control) {
+			self.out.push('@');
+			self.out.push(quote);
+			for c in str.chars() {
+				if c == quote {
+					self.out.push(quote);
+				}
+				self.out.push(c);
+			}
+			self.out.push(quote);
+			return;
+		}
+		self.out.push(quote);
+		for c in str.chars() {
+			match c {
+				'\\' => self.out.push_str("\\\\"),
+				'\n' => self.out.push_str("\\n"),
+				'\r' => self.out.push_str("\\r"),
+				'\t' => self.out.push_str("\\t"),
+				'\u{8}' => self.out.push_str("\\b"),
+				'\u{c}' => self.out.push_str("\\f"),
+				c if c == quote => {
+					self.out.push('\\');
+					self.out.push(c);
+				}
+				c if c.is_control() => {
+					for unit in c.encode_utf16(&mut [0; 2]) {
+						write!(self.out, "\\u{unit:04x}").expect("string write is infallible");
+					}
+				}
+				c => self.out.push(c),
+			}
+		}
+		self.out.push(quote);
+	}
+
+	fn number(&mut self, n: f64) {
+		let abs = n.abs();
+		if n.is_sign_negative() {
+			self.out.push('-');
+		}
+		if abs != 0.0 && !(1e-5..1e16).contains(&abs) {
+			write!(self.out, "{abs:e}")
+		} else {
+			write!(self.out, "{abs}")
+		}
+		.expect("string write is infallible");
+	}
+
+	fn operand(&mut self, expr: &LocExpr, min_precedence: u8) {
+		if precedence(&expr.0) < min_precedence {
+			self.out.push('(');
+			self.expr(expr);
+			self.out.push(')');
+		} else {
+			self.expr(expr);
+		}
+	}
+
+	fn expr(&mut self, expr: &LocExpr) {
+		match &*expr.0 {
+			Expr::Literal(literal) => self.out.push_str(match literal {
+				LiteralType::This => "self",
+				LiteralType::Super => "super",
+				LiteralType::Dollar => "$",
+				LiteralType::Null => "null",
+				LiteralType::True => "true",
+				LiteralType::False => "false",
+			}),
+			Expr::Str(str) => self.string(str, true),
+			Expr::Num(n) => self.number(*n),
+			Expr::Var(name) | Expr::ResolvedVar(name, _) => self.out.push_str(name),
+			Expr::Arr(items) => {
+				self.out.push('[');
+				self.comma_separated(items, Self::expr);
+				self.out.push(']');
+			}
+			Expr::ArrComp(value, specs) => {
+				self.out.push('[');
+				self.expr(value);
+				for spec in specs {
+					self.out.push(' ');
+					self.comp_spec(spec);
+				}
+				self.out.push(']');
+			}
+			Expr::Obj(body) => self.obj_body(body),
+			Expr::ObjExtend(base, body) => {
+				self.operand(base, PREC_POSTFIX);
+				self.out.push(' ');
+				self.obj_body(body);
+			}
+			Expr::Parened(inner) => {
+				self.out.push('(');
+				self.expr(inner);
+				self.out.push(')');
+			}
+			Expr::UnaryOp(op, value) => {
+				write!(self.out, "{op}").expect("string write is infallible");
+				self.operand(value, PREC_UNARY);
+			}
+			Expr::BinaryOp(a, op, b) => {
+				let precedence = binary_precedence(*op);
+				// `^` is right associative
+				let (min_a, min_b) = if *op == BinaryOpType::BitXor {
+					(precedence + 1, precedence)
+				} else {
+					(precedence, precedence + 1)
+				};
+				self.operand(a, min_a);
+				write!(self.out, " {op} ").expect("string write is infallible");
+				self.operand(b, min_b);
+			}
+			Expr::AssertExpr(assert, rest) => {
+				self.assert_stmt(assert);
+				self.out.push(';');
+				self.newline();
+				self.expr(rest);
+			}
+			Expr::LocalExpr(binds, rest) => {
+				self.out.push_str("local ");
+				self.comma_separated(binds, Self::bind_spec);
+				self.out.push(';');
+				self.newline();
+				self.expr(rest);
+			}
+			Expr::Import(path) => {
+				self.out.push_str("import ");
+				self.string(path, false);
+			}
+			Expr::ImportStr(path) => {
+				self.out.push_str("importstr ");
+				self.string(path, false);
+			}
+			Expr::ImportBin(path) => {
+				self.out.push_str("importbin ");
+				self.string(path, false);
+			}
+			Expr::ErrorStmt(value) => {
+				self.out.push_str("error ");
+				self.expr(value);
+			}
+			Expr::Apply(value, args, tailstrict) => {
+				self.operand(value, PREC_POSTFIX);
+				self.args_desc(args);
+				if *tailstrict {
+					self.out.push_str(" tailstrict");
+				}
+			}
+			Expr::Index(value, index) => {
+				self.operand(value, PREC_POSTFIX);
+				match &*index.0 {
+					Expr::Str(name) if is_identifier(name) => {
+						self.out.push('.');
+						self.out.push_str(name);
+					}
+					_ => {
+						self.out.push('[');
+						self.expr(index);
+						self.out.push(']');
+					}
+				}
+			}
+			Expr::Function(params, body) => {
+				self.out.push_str("function");
+				self.params_desc(params);
+				self.out.push(' ');
+				self.expr(body);
+			}
+			Expr::IfElse {
+				cond,
+				cond_then,
+				cond_else,
+			} => {
+				self.out.push_str("if ");
+				self.expr(&cond.0);
+				self.out.push_str(" then ");
+				if cond_else.is_some() && has_dangling_if(&cond_then.0) {
+					self.out.push('(');
+					self.expr(cond_then);
+					self.out.push(')');
+				} else {
+					self.expr(cond_then);
+				}
+				if let Some(cond_else) = cond_else {
+					self.out.push_str(" else ");
+					self.expr(cond_else);
+				}
+			}
+			Expr::Slice(value, desc) => {
+				self.operand(value, PREC_POSTFIX);
+				self.out.push('[');
+				if let Some(start) = &desc.start {
+					self.expr(start);
+				}
+				self.out.push(':');
+				if let Some(end) = &desc.end {
+					self.expr(end);
+				}
+				if let Some(step) = &desc.step {
+					self.out.push(':');
+					self.expr(step);
+				}
+				self.out.push(']');
+			}
+		}
+	}
+
+	fn obj_body(&mut self, body: &ObjBody) {
+		match body {
+			ObjBody::MemberList(members) if members.is_empty() => self.out.push_str("{}"),
+			ObjBody::MemberList(members) => {
+				self.out.push('{');
+				self.indent += 1;
+				for member in members {
+					self.newline();
+					self.member(member);
+					self.out.push(',');
+				}
+				self.indent -= 1;
+				self.newline();
+				self.out.push('}');
+			}
+			ObjBody::ObjComp(comp) => self.obj_comp(comp),
+		}
+	}
+
+	fn obj_comp(&mut self, comp: &ObjComp) {
+		self.out.push('{');
+		self.indent += 1;
+		for bind in &comp.pre_locals {
+			self.newline();
+			self.out.push_str("local ");
+			self.bind_spec(bind);
+			self.out.push(',');
+		}
+		self.newline();
+		self.out.push('[');
+		self.expr(&comp.key);
+		self.out.push(']');
+		if comp.plus {
+			self.out.push('+');
+		}
+		self.out.push_str(": ");
+		self.expr(&comp.value);
+		self.out.push(',');
+		for bind in &comp.post_locals {
+			self.newline();
+			self.out.push_str("local ");
+			self.bind_spec(bind);
+			self.out.push(',');
+		}
+		for spec in &comp.compspecs {
+			self.newline();
+			self.comp_spec(spec);
+		}
+		self.indent -= 1;
+		self.newline();
+		self.out.push('}');
+	}
+
+	fn member(&mut self, member: &Member) {
+		match member {
+			Member::Field(field) => self.field_member(field),
+			Member::BindStmt(bind) => {
+				self.out.push_str("local ");
+				self.bind_spec(bind);
+			}
+			Member::AssertStmt(assert) => self.assert_stmt(assert),
+		}
+	}
+
+	fn field_member(&mut self, field: &FieldMember) {
+		match &field.name {
+			FieldName::Fixed(name) if is_identifier(name) => self.out.push_str(name),
+			FieldName::Fixed(name) => self.string(name, false),
+			FieldName::Dyn(name) => {
+				self.out.push('[');
+				self.expr(name);
+				self.out.push(']');
+			}
+		}
+		if let Some(params) = &field.params {
+			self.params_desc(params);
+		}
+		if field.plus {
+			self.out.push('+');
+		}
+		self.out.push_str(match field.visibility {
+			Visibility::Normal => ": ",
+			Visibility::Hidden => ":: ",
+			Visibility::Unhide => "::: ",
+		});
+		self.expr(&field.value);
+	}
+
+	fn bind_spec(&mut self, bind: &BindSpec) {
+		match bind {
+			BindSpec::Field { into, value } => {
+				self.destruct(into);
+				self.out.push_str(" = ");
+				self.expr(value);
+			}
+			BindSpec::Function {
+				name,
+				params,
+				value,
+			} => {
+				self.out.push_str(name);
+				self.params_desc(params);
+				self.out.push_str(" = ");
+				self.expr(value);
+			}
+		}
+	}
+
+	fn destruct(&mut self, destruct: &Destruct) {
+		#[cfg(feature = "exp-destruct")]
+		fn rest(p: &mut Printer, rest: &crate::expr::DestructRest) {
+			p.out.push_str("...");
+			if let crate::expr::DestructRest::Keep(name) = rest {
+				p.out.push_str(name);
+			}
+		}
+		match destruct {
+			Destruct::Full(name) => self.out.push_str(name),
+			#[cfg(feature = "exp-destruct")]
+			Destruct::Skip => self.out.push('?'),
+			#[cfg(feature = "exp-destruct")]
+			Destruct::Array {
+				start,
+				rest: rest_into,
+				end,
+			} => {
+				self.out.push('[');
+				self.comma_separated(start, Self::destruct);
+				// Rest and following elements are always preceded by comma, even if there is no elements before
+				if rest_into.is_some() || !end.is_empty() {
+					self.out.push_str(", ");
+					if let Some(rest_into) = rest_into {
+						rest(self, rest_into);
+					}
+				}
+				if !end.is_empty() {
+					self.out.push_str(", ");
+					self.comma_separated(end, Self::destruct);
+				}
+				self.out.push(']');
+			}
+			#[cfg(feature = "exp-destruct")]
+			Destruct::Object {
+				fields,
+				rest: rest_into,
+			} => {
+				self.out.push('{');
+				self.comma_separated(fields, |p, (name, into, default)| {
+					p.out.push_str(name);
+					if let Some(into) = into {
+						p.out.push_str(": ");
+						p.destruct(into);
+					}
+					if let Some(default) = default {
+						p.out.push_str(" = ");
+						p.expr(default);
+					}
+				});
+				if let Some(rest_into) = rest_into {
+					self.out.push_str(", ");
+					rest(self, rest_into);
+				}
+				self.out.push('}');
+			}
+		}
+	}
+
+	fn params_desc(&mut self, params: &ParamsDesc) {
+		self.out.push('(');
+		self.comma_separated(params, |p, param| {
+			p.destruct(&param.0);
+			if let Some(default) = &param.1 {
+				p.out.push('=');
+				p.expr(default);
+			}
+		});
+		self.out.push(')');
+	}
+
+	fn args_desc(&mut self, args: &ArgsDesc) {
+		self.out.push('(');
+		self.comma_separated(&args.unnamed, Self::expr);
+		if !args.unnamed.is_empty() && !args.named.is_empty() {
+			self.out.push_str(", ");
+		}
+		self.comma_separated(&args.named, |p, (name, value)| {
+			p.out.push_str(name);
+			p.out.push('=');
+			p.expr(value);
+		});
+		self.out.push(')');
+	}
+
+	fn comp_spec(&mut self, spec: &CompSpec) {
+		match spec {
+			CompSpec::IfSpec(spec) => {
+				self.out.push_str("if ");
+				self.expr(&spec.0);
+			}
+			CompSpec::ForSpec(spec) => {
+				self.out.push_str("for ");
+				self.out.push_str(&spec.0);
+				self.out.push_str(" in ");
+				self.expr(&spec.1);
+			}
+		}
+	}
+
+	fn assert_stmt(&mut self, assert: &AssertStmt) {
+		self.out.push_str("assert ");
+		self.expr(&assert.0);
+		if let Some(message) = &assert.1 {
+			self.out.push_str(" : ");
+			self.expr(message);
+		}
+	}
+}
+
+/// Renders expression as jsonnet source code
+pub fn unparse(expr: &LocExpr) -> String {
+	let mut printer = Printer {
+		out: String::new(),
+		indent: 0,
+	};
+	printer.expr(expr);
+	printer.out
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::rc::Rc;
+
+	use jrsonnet_interner::IStr;
+
+	use super::unparse;
+	use crate::{
+		expr::{BinaryOpType, Expr, ExprLocation, LocExpr, LocIStr},
+		fold::{self, Folder},
+		source::Source,
+		ParserSettings,
+	};
+
+	fn source() -> Source {
+		Source::new_virtual("<test>".into(), IStr::empty())
+	}
+
+	fn parse(code: &str) -> LocExpr {
+		crate::parse(
+			code,
+			&ParserSettings {
+				file_name: source(),
+			},
+		)
+		.unwrap_or_else(|e| panic!("{e}\n{code}"))
+	}
+
+	/// Resets locations, and optionally removes parentheses
+	struct Normalize {
+		keep_parens: bool,
+	}
+	impl Folder for Normalize {
+		fn fold_loc_expr(&mut self, expr: LocExpr) -> LocExpr {
+			if !self.keep_parens {
+				if let Expr::Parened(inner) = &*expr.0 {
+					return self.fold_loc_expr(inner.clone());
+				}
+			}
+			let expr = fold::walk_loc_expr(self, expr);
+			LocExpr(expr.0, ExprLocation(source(), 0, 0))
+		}
+		fn fold_loc_istr(&mut self, name: LocIStr) -> LocIStr {
+			LocIStr(name.0, ExprLocation(source(), 0, 0))
+		}
+	}
+
+	fn el(expr: Expr) -> LocExpr {
+		LocExpr(Rc::new(expr), ExprLocation(source(), 0, 0))
+	}
+
+	/// Checks that parsing unparsed code gives the same AST
+	fn roundtrip(code: &str) -> String {
+		let parsed = parse(code);
+		let unparsed = unparse(&parsed);
+		let mut normalize = Normalize { keep_parens: true };
+		assert_eq!(
+			normalize.fold_loc_expr(parse(&unparsed)),
+			normalize.fold_loc_expr(parsed),
+			"{unparsed}"
+		);
+		assert_eq!(unparse(&parse(&unparsed)), unparsed);
+		unparsed
+	}
+
+	#[test]
+	fn formatting() {
+		assert_eq!(
+			roundtrip("local a=1,f(x,y=2)=x;{a:a,'b c'::f(1,y=3),[a]+:::[1,2],g(x)::x,local l=a,assert a:'msg'}"),
+			"local a = 1, f(x, y=2) = x;\n{\n  a: a,\n  \"b c\":: f(1, y=3),\n  [a]+::: [1, 2],\n  g(x):: x,\n  local l = a,\n  assert a : \"msg\",\n}"
+		);
+		assert_eq!(
+			roundtrip("{local a = 1, [k]+: v, local b = 2, for k in ks if k != a}"),
+			"{\n  local a = 1,\n  [k]+: v,\n  local b = 2,\n  for k in ks\n  if k != a\n}"
+		);
+	}
+
+	#[test]
+	fn all_syntax() {
+		roundtrip(
+			r#"
+			local std2 = import "std2.libsonnet", txt = importstr 'a.txt', bin = importbin "a.bin";
+			assert std2 != null : "missing";
+			{
+				a: [x * 2 for x in [1, 2, 3] if x > 1 for y in [x]],
+				b: self.a[1:][::2][:1:] + super.b,
+				c: $.d.e["not id"]["if"],
+				d: { e: { "not id": { "if": 1 } } } { f+: null },
+				g: function(x, y=-1) if x then y else if !y then error "no" else ~x,
+				h: std.map(function(a) a, [true, false]) tailstrict,
+				i: if a then b,
+				j: 1 << 2 >> 3 & 4 | 5 ^ 6 ^ 7 && 8 || 9 in 10,
+				k: (1 + 2) * (3 - (4 - 5)) / 6 % 7,
+				l: -(1 + 2) + -3 - (-4),
+				m: 1.5e300 + 0.001 + 123456789 + 1e-7,
+				n: (local x = 1; x) + (function(x) x)(1),
+			}
+			"#,
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "exp-destruct")]
+	fn destruct() {
+		roundtrip("local [a, ?, ...r, b] = [1], {c, d: [e] = 1, ...} = {}, [f, ...] = []; a");
+	}
+
+	#[test]
+	fn strings() {
+		assert_eq!(roundtrip(r#"'a"b'"#), r#"'a"b'"#);
+		assert_eq!(roundtrip(r#""a'b\"c""#), r#""a'b\"c""#);
+		assert_eq!(roundtrip(r#""C:\\dir""#), r#"@"C:\dir""#);
+		assert_eq!(roundtrip(r#""\\\"""#), r#"@'\"'"#);
+		assert_eq!(roundtrip(r#""tab\t\u0001\r\n""#), r#""tab\t\u0001\r\n""#);
+		assert_eq!(
+			roundtrip("{a: |||\n    line\n\n      indented\n|||}"),
+			"{\n  a: |||\n    line\n\n      indented\n  |||,\n}"
+		);
+		// First line decides indentation of text block
+		assert_eq!(roundtrip(r#""  a\nb\n""#), r#""  a\nb\n""#);
+	}
+
+	#[test]
+	fn parenthesizes() {
+		use BinaryOpType::*;
+		let var = |name: &str| el(Expr::Var(name.into()));
+		let bin = |a, op, b| el(Expr::BinaryOp(a, op, b));
+		let cases = [
+			(
+				bin(bin(var("a"), Add, var("b")), Mul, var("c")),
+				"(a + b) * c",
+			),
+			(
+				bin(var("a"), Sub, bin(var("b"), Sub, var("c"))),
+				"a - (b - c)",
+			),
+			(
+				bin(bin(var("a"), Sub, var("b")), Sub, var("c")),
+				"a - b - c",
+			),
+			(
+				bin(bin(var("a"), BitXor, var("b")), BitXor, var("c")),
+				"(a ^ b) ^ c",
+			),
+			(
+				el(Expr::Index(
+					el(Expr::LocalExpr(
+						vec![crate::BindSpec::Field {
+							into: crate::Destruct::Full(LocIStr(
+								"x".into(),
+								ExprLocation(source(), 0, 0),
+							)),
+							value: var("a"),
+						}],
+						var("x"),
+					)),
+					el(Expr::Str("b".into())),
+				)),
+				"(local x = a;\nx).b",
+			),
+			(
+				el(Expr::IfElse {
+					cond: crate::IfSpecData(var("a")),
+					cond_then: el(Expr::IfElse {
+						cond: crate::IfSpecData(var("b")),
+						cond_then: var("c"),
+						cond_else: None,
+					}),
+					cond_else: Some(var("d")),
+				}),
+				"if a then (if b then c) else d",
+			),
+		];
+		for (expr, expected) in cases {
+			let unparsed = unparse(&expr);
+			assert_eq!(unparsed, expected);
+			let mut normalize = Normalize { keep_parens: false };
+			assert_eq!(
+				normalize.fold_loc_expr(parse(&unparsed)),
+				normalize.fold_loc_expr(expr)
+			);
+		}
+	}
+}
@@ -0,0 +1,365 @@
+//! Read-only AST traversal
+//!
+//! Override [`Visitor`] methods for the nodes of interest, and call corresponding `walk_*` function
+//! from the override to continue traversal into children.
+
+use crate::expr::{
+	ArgsDesc, AssertStmt, BindSpec, CompSpec, Destruct, DestructField, DestructRest, Expr,
+	FieldMember, FieldName, ForSpecData, IfSpecData, LocExpr, LocIStr, Member, ObjBody, ObjComp,
+	Param, ParamsDesc, SliceDesc,
+};
+
+/// AST visitor, every method by default visits all children of the node, in source order
+pub trait Visitor {
+	fn visit_loc_expr(&mut self, expr: &LocExpr) {
+		walk_loc_expr(self, expr);
+	}
+	fn visit_expr(&mut self, expr: &Expr) {
+		walk_expr(self, expr);
+	}
+	fn visit_obj_body(&mut self, body: &ObjBody) {
+		walk_obj_body(self, body);
+	}
+	fn visit_obj_comp(&mut self, comp: &ObjComp) {
+		walk_obj_comp(self, comp);
+	}
+	fn visit_member(&mut self, member: &Member) {
+		walk_member(self, member);
+	}
+	fn visit_field_member(&mut self, field: &FieldMember) {
+		walk_field_member(self, field);
+	}
+	fn visit_field_name(&mut self, name: &FieldName) {
+		walk_field_name(self, name);
+	}
+	fn visit_bind_spec(&mut self, bind: &BindSpec) {
+		walk_bind_spec(self, bind);
+	}
+	fn visit_destruct(&mut self, destruct: &Destruct) {
+		walk_destruct(self, destruct);
+	}
+	/// Object destructuring field, shorthand fields (`{ name }`) bind variable named after the field
+	fn visit_destruct_field(&mut self, field: &DestructField) {
+		walk_destruct_field(self, field);
+	}
+	/// `...rest` or `...` of array or object destructuring
+	fn visit_destruct_rest(&mut self, _rest: &DestructRest) {}
+	fn visit_params_desc(&mut self, params: &ParamsDesc) {
+		walk_params_desc(self, params);
+	}
+	fn visit_param(&mut self, param: &Param) {
+		walk_param(self, param);
+	}
+	fn visit_args_desc(&mut self, args: &ArgsDesc) {
+		walk_args_desc(self, args);
+	}
+	fn visit_comp_spec(&mut self, spec: &CompSpec) {
+		walk_comp_spec(self, spec);
+	}
+	fn visit_for_spec(&mut self, spec: &ForSpecData) {
+		walk_for_spec(self, spec);
+	}
+	fn visit_if_spec(&mut self, spec: &IfSpecData) {
+		walk_if_spec(self, spec);
+	}
+	fn visit_assert_stmt(&mut self, assert: &AssertStmt) {
+		walk_assert_stmt(self, assert);
+	}
+	fn visit_slice_desc(&mut self, desc: &SliceDesc) {
+		walk_slice_desc(self, desc);
+	}
+	/// Names of variables, parameters, fields and named arguments
+	fn visit_loc_istr(&mut self, _name: &LocIStr) {}
+}
+
+pub fn walk_loc_expr<V: Visitor + ?Sized>(v: &mut V, expr: &LocExpr) {
+	v.visit_expr(&expr.0);
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(v: &mut V, expr: &Expr) {
+	match expr {
+		Expr::Literal(_)
+		| Expr::Str(_)
+		| Expr::Num(_)
+		| Expr::Var(_)
+		| Expr::ResolvedVar(_, _)
+		| Expr::Import(_)
+		| Expr::ImportStr(_)
+		| Expr::ImportBin(_) => {}
+		Expr::Arr(items) => {
+			for item in items {
+				v.visit_loc_expr(item);
+			}
+		}
+		Expr::ArrComp(value, specs) => {
+			v.visit_loc_expr(value);
+			for spec in specs {
+				v.visit_comp_spec(spec);
+			}
+		}
+		Expr::Obj(body) => v.visit_obj_body(body),
+		Expr::ObjExtend(base, body) => {
+			v.visit_loc_expr(base);
+			v.visit_obj_body(body);
+		}
+		Expr::Parened(e) | Expr::UnaryOp(_, e) | Expr::ErrorStmt(e) => v.visit_loc_expr(e),
+		Expr::BinaryOp(a, _, b) | Expr::Index(a, b) => {
+			v.visit_loc_expr(a);
+			v.visit_loc_expr(b);
+		}
+		Expr::AssertExpr(assert, rest) => {
+			v.visit_assert_stmt(assert);
+			v.visit_loc_expr(rest);
+		}
+		Expr::LocalExpr(binds, rest) => {
+			for bind in binds {
+				v.visit_bind_spec(bind);
+			}
+			v.visit_loc_expr(rest);
+		}
+		Expr::Apply(value, args, _tailstrict) => {
+			v.visit_loc_expr(value);
+			v.visit_args_desc(args);
+		}
+		Expr::Function(params, body) => {
+			v.visit_params_desc(params);
+			v.visit_loc_expr(body);
+		}
+		Expr::IfElse {
+			cond,
+			cond_then,
+			cond_else,
+		} => {
+			v.visit_if_spec(cond);
+			v.visit_loc_expr(cond_then);
+			if let Some(cond_else) = cond_else {
+				v.visit_loc_expr(cond_else);
+			}
+		}
+		Expr::Slice(value, desc) => {
+			v.visit_loc_expr(value);
+			v.visit_slice_desc(desc);
+		}
+	}
+}
+
+pub fn walk_obj_body<V: Visitor + ?Sized>(v: &mut V, body: &ObjBody) {
+	match body {
+		ObjBody::MemberList(members) => {
+			for member in members {
+				v.visit_member(member);
+			}
+		}
+		ObjBody::ObjComp(comp) => v.visit_obj_comp(comp),
+	}
+}
+
+pub fn walk_obj_comp<V: Visitor + ?Sized>(v: &mut V, comp: &ObjComp) {
+	for bind in &comp.pre_locals {
+		v.visit_bind_spec(bind);
+	}
+	v.visit_loc_expr(&comp.key);
+	v.visit_loc_expr(&comp.value);
+	for bind in &comp.post_locals {
+		v.visit_bind_spec(bind);
+	}
+	for spec in &comp.compspecs {
+		v.visit_comp_spec(spec);
+	}
+}
+
+pub fn walk_member<V: Visitor + ?Sized>(v: &mut V, member: &Member) {
+	match member {
+		Member::Field(field) => v.visit_field_member(field),
+		Member::BindStmt(bind) => v.visit_bind_spec(bind),
+		Member::AssertStmt(assert) => v.visit_assert_stmt(assert),
+	}
+}
+
+pub fn walk_field_member<V: Visitor + ?Sized>(v: &mut V, field: &FieldMember) {
+	v.visit_field_name(&field.name);
+	if let Some(params) = &field.params {
+		v.visit_params_desc(params);
+	}
+	v.visit_loc_expr(&field.value);
+}
+
+pub fn walk_field_name<V: Visitor + ?Sized>(v: &mut V, name: &FieldName) {
+	match name {
+		FieldName::Fixed(name) => v.visit_loc_istr(name),
+		FieldName::Dyn(e) => v.visit_loc_expr(e),
+	}
+}
+
+pub fn walk_bind_spec<V: Visitor + ?Sized>(v: &mut V, bind: &BindSpec) {
+	match bind {
+		BindSpec::Field { into, value } => {
+			v.visit_destruct(into);
+			v.visit_loc_expr(value);
+		}
+		BindSpec::Function {
+			name,
+			params,
+			value,
+		} => {
+			v.visit_loc_istr(name);
+			v.visit_params_desc(params);
+			v.visit_loc_expr(value);
+		}
+	}
+}
+
+#[cfg_attr(not(feature = "exp-destruct"), allow(unused_variables))]
+pub fn walk_destruct<V: Visitor + ?Sized>(v: &mut V, destruct: &Destruct) {
+	match destruct {
+		Destruct::Full(name) => v.visit_loc_istr(name),
+		#[cfg(feature = "exp-destruct")]
+		Destruct::Skip => {}
+		#[cfg(feature = "exp-destruct")]
+		Destruct::Array { start, rest, end } => {
+			for item in start {
+				v.visit_destruct(item);
+			}
+			if let Some(rest) = rest {
+				v.visit_destruct_rest(rest);
+			}
+			for item in end {
+				v.visit_destruct(item);
+			}
+		}
+		#[cfg(feature = "exp-destruct")]
+		Destruct::Object { fields, rest } => {
+			for field in fields {
+				v.visit_destruct_field(field);
+			}
+			if let Some(rest) = rest {
+				v.visit_destruct_rest(rest);
+			}
+		}
+	}
+}
+
+pub fn walk_destruct_field<V: Visitor + ?Sized>(v: &mut V, field: &DestructField) {
+	let (_name, into, default) = field;
+	if let Some(into) = into {
+		v.visit_destruct(into);
+	}
+	if let Some(default) = default {
+		v.visit_loc_expr(default);
+	}
+}
+
+pub fn walk_params_desc<V: Visitor + ?Sized>(v: &mut V, params: &ParamsDesc) {
+	for param in params.iter() {
+		v.visit_param(param);
+	}
+}
+
+pub fn walk_param<V: Visitor + ?Sized>(v: &mut V, param: &Param) {
+	v.visit_destruct(&param.0);
+	if let Some(default) = &param.1 {
+		v.visit_loc_expr(default);
+	}
+}
+
+pub fn walk_args_desc<V: Visitor + ?Sized>(v: &mut V, args: &ArgsDesc) {
+	for arg in &args.unnamed {
+		v.visit_loc_expr(arg);
+	}
+	for (name, arg) in &args.named {
+		v.visit_loc_istr(name);
+		v.visit_loc_expr(arg);
+	}
+}
+
+pub fn walk_comp_spec<V: Visitor + ?Sized>(v: &mut V, spec: &CompSpec) {
+	match spec {
+		CompSpec::IfSpec(spec) => v.visit_if_spec(spec),
+		CompSpec::ForSpec(spec) => v.visit_for_spec(spec),
+	}
+}
+
+pub fn walk_for_spec<V: Visitor + ?Sized>(v: &mut V, spec: &ForSpecData) {
+	v.visit_loc_istr(&spec.0);
+	v.visit_loc_expr(&spec.1);
+}
+
+pub fn walk_if_spec<V: Visitor + ?Sized>(v: &mut V, spec: &IfSpecData) {
+	v.visit_loc_expr(&spec.0);
+}
+
+pub fn walk_assert_stmt<V: Visitor + ?Sized>(v: &mut V, assert: &AssertStmt) {
+	v.visit_loc_expr(&assert.0);
+	if let Some(message) = &assert.1 {
+		v.visit_loc_expr(message);
+	}
+}
+
+pub fn walk_slice_desc<V: Visitor + ?Sized>(v: &mut V, desc: &SliceDesc) {
+	for part in [&desc.start, &desc.end, &desc.step].into_iter().flatten() {
+		v.visit_loc_expr(part);
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use jrsonnet_interner::IStr;
+
+	use super::Visitor;
+	use crate::{
+		expr::{Destruct, DestructField, DestructRest},
+		source::Source,
+		LocExpr, ParserSettings,
+	};
+
+	fn parse(code: &str) -> LocExpr {
+		crate::parse(
+			code,
+			&ParserSettings {
+				file_name: Source::new_virtual("<test>".into(), IStr::empty()),
+			},
+		)
+		.unwrap()
+	}
+
+	/// Collects names bound by destructuring, including the ones without location
+	#[derive(Default)]
+	struct Bound(Vec<String>);
+	impl Visitor for Bound {
+		fn visit_destruct(&mut self, destruct: &Destruct) {
+			if let Some(name) = destruct.name() {
+				self.0.push(name.to_string());
+			}
+			super::walk_destruct(self, destruct);
+		}
+		fn visit_destruct_field(&mut self, field: &DestructField) {
+			if field.1.is_none() {
+				self.0.push(field.0.to_string());
+			}
+			super::walk_destruct_field(self, field);
+		}
+		fn visit_destruct_rest(&mut self, rest: &DestructRest) {
+			match rest {
+				DestructRest::Keep(name) => self.0.push(format!("...{name}")),
+				DestructRest::Drop => self.0.push("...".into()),
+			}
+		}
+	}
+
+	#[test]
+	fn visit_bound_names() {
+		let mut bound = Bound::default();
+		bound.visit_loc_expr(&parse("local a = 1, f(b, c = 2) = b; a"));
+		assert_eq!(bound.0, ["a", "b", "c"]);
+	}
+
+	#[test]
+	#[cfg(feature = "exp-destruct")]
+	fn visit_destruct_hooks() {
+		let mut bound = Bound::default();
+		bound.visit_loc_expr(&parse(
+			"local [a, ?, ...r, { b, c: [d] = a, ... }, e] = [], { f, ...g } = {}; a",
+		));
+		assert_eq!(bound.0, ["a", "...r", "b", "d", "...", "e", "f", "...g"]);
+	}
+}
@@ -1,6 +1,6 @@
 use std::{env, fs::File, io::Write, path::Path};
 
-use jrsonnet_parser::{parse, ParserSettings, Source};
+use jrsonnet_parser::{parse, resolve, ParserSettings, Source};
 use structdump::CodegenResult;
 
 fn main() {
@@ -14,6 +14,7 @@ fn main() {
 		},
 	)
 	.expect("parse");
+	let parsed = resolve(parsed, &[]).expect("stdlib has no free variables");
 
 	let mut out = CodegenResult::default();
 
@@ -41,7 +41,7 @@ pub fn builtin_flatmap(s: State, func: FuncVal, arr: IndexableVal) -> Result<Ind
 			let mut out = String::new();
 			for c in str.chars() {
 				match func.evaluate_simple(s.clone(), &(c.to_string(),))? {
-					Val::Str(o) => out.push_str(&o),
+					Val::Str(o) => out.push_str(&o.into_flat()),
 					Val::Null => continue,
 					_ => throw_runtime!("in std.join all items should be strings"),
 				};
@@ -145,7 +145,7 @@ pub fn builtin_join(s: State, sep: IndexableVal, arr: ArrValue) -> Result<Indexa
 						out += &sep;
 					}
 					first = false;
-					out += &item;
+					out += &item.into_flat();
 				} else if matches!(item, Val::Null) {
 					continue;
 				} else {
@@ -214,3 +214,12 @@ pub fn builtin_count(s: State, arr: Vec<Any>, v: Any) -> Result<usize> {
 	}
 	Ok(count)
 }
+
+#[builtin]
+pub fn builtin_flatten_arrays(arrs: Vec<ArrValue>) -> Result<ArrValue> {
+	let mut out = Vec::with_capacity(arrs.iter().map(ArrValue::len).sum());
+	for arr in &arrs {
+		out.extend(arr.iter_lazy());
+	}
+	Ok(out.into())
+}
@@ -90,12 +90,13 @@ pub fn stdlib_expr() -> LocExpr {
 
 	#[cfg(not(feature = "codegenerated-stdlib"))]
 	{
-		jrsonnet_parser::parse(
+		let parsed = jrsonnet_parser::parse(
 			STDLIB_STR,
 			&ParserSettings {
 				file_name: Source::new_virtual(Cow::Borrowed("<std>"), STDLIB_STR.into()),
 			},
 		)
-		.unwrap()
+		.unwrap();
+		jrsonnet_parser::resolve(parsed, &[]).expect("stdlib has no free variables")
 	}
 }
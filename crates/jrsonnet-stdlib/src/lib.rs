@@ -29,6 +29,8 @@ mod operator;
 pub use operator::*;
 mod sort;
 pub use sort::*;
+mod sets;
+pub use sets::*;
 mod hash;
 pub use hash::*;
 mod encoding;
@@ -76,6 +78,7 @@ pub fn stdlib_uncached(s: State, settings: Rc<RefCell<Settings>>) -> ObjValue {
 		("all", builtin_all::INST),
 		("member", builtin_member::INST),
 		("count", builtin_count::INST),
+		("flattenArrays", builtin_flatten_arrays::INST),
 		// Math
 		("modulo", builtin_modulo::INST),
 		("floor", builtin_floor::INST),
@@ -99,6 +102,12 @@ pub fn stdlib_uncached(s: State, settings: Rc<RefCell<Settings>>) -> ObjValue {
 		("format", builtin_format::INST),
 		// Sort
 		("sort", builtin_sort::INST),
+		("uniq", builtin_uniq::INST),
+		("set", builtin_set::INST),
+		("setMember", builtin_set_member::INST),
+		("setUnion", builtin_set_union::INST),
+		("setInter", builtin_set_inter::INST),
+		("setDiff", builtin_set_diff::INST),
 		// Hash
 		("md5", builtin_md5::INST),
 		// Encoding
@@ -110,13 +119,23 @@ pub fn stdlib_uncached(s: State, settings: Rc<RefCell<Settings>>) -> ObjValue {
 		// Objects
 		("objectFieldsEx", builtin_object_fields_ex::INST),
 		("objectHasEx", builtin_object_has_ex::INST),
+		("objectFields", builtin_object_fields::INST),
+		("objectValues", builtin_object_values::INST),
+		("get", builtin_get::INST),
+		("mapWithKey", builtin_map_with_key::INST),
+		("mergePatch", builtin_merge_patch::INST),
+		("prune", builtin_prune::INST),
 		// Manifest
 		("escapeStringJson", builtin_escape_string_json::INST),
 		("manifestJsonEx", builtin_manifest_json_ex::INST),
 		("manifestYamlDoc", builtin_manifest_yaml_doc::INST),
+		("manifestYamlStream", builtin_manifest_yaml_stream::INST),
 		// Parsing
 		("parseJson", builtin_parse_json::INST),
 		("parseYaml", builtin_parse_yaml::INST),
+		("parseInt", builtin_parse_int::INST),
+		("parseOctal", builtin_parse_octal::INST),
+		("parseHex", builtin_parse_hex::INST),
 		// Misc
 		("codepoint", builtin_codepoint::INST),
 		("substr", builtin_substr::INST),
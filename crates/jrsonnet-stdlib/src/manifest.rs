@@ -6,6 +6,7 @@ use jrsonnet_evaluator::{
 		ManifestYamlOptions,
 	},
 	typed::Any,
+	val::ArrValue,
 	IStr, State,
 };
 
@@ -63,3 +64,38 @@ pub fn builtin_manifest_yaml_doc(
 		},
 	)
 }
+
+#[builtin]
+pub fn builtin_manifest_yaml_stream(
+	s: State,
+	value: ArrValue,
+	indent_array_in_object: Option<bool>,
+	c_document_end: Option<bool>,
+	quote_keys: Option<bool>,
+	#[cfg(feature = "exp-preserve-order")] preserve_order: Option<bool>,
+) -> Result<String> {
+	let options = ManifestYamlOptions {
+		padding: "  ",
+		arr_element_padding: if indent_array_in_object.unwrap_or(false) {
+			"  "
+		} else {
+			""
+		},
+		quote_keys: quote_keys.unwrap_or(true),
+		#[cfg(feature = "exp-preserve-order")]
+		preserve_order: preserve_order.unwrap_or(false),
+	};
+	let mut out = String::from("---\n");
+	for (i, item) in value.iter(s.clone()).enumerate() {
+		if i != 0 {
+			out.push_str("\n---\n");
+		}
+		out.push_str(&manifest_yaml_ex(s.clone(), &item?, &options)?);
+	}
+	out.push_str(if c_document_end.unwrap_or(true) {
+		"\n...\n"
+	} else {
+		"\n"
+	});
+	Ok(out)
+}
@@ -1,7 +1,12 @@
 use jrsonnet_evaluator::{
-	error::Result, function::builtin, typed::VecVal, val::Val, IStr, ObjValue,
+	error::Result,
+	function::{builtin, FuncVal},
+	tb, throw_runtime,
+	typed::{Any, VecVal},
+	val::{ArrValue, ThunkValue},
+	IStr, MaybeUnbound, ObjValue, ObjValueBuilder, State, Thunk, Val,
 };
-use jrsonnet_gcmodule::Cc;
+use jrsonnet_gcmodule::{Cc, Trace};
 
 #[builtin]
 pub fn builtin_object_fields_ex(
@@ -17,7 +22,9 @@ pub fn builtin_object_fields_ex(
 		preserve_order,
 	);
 	Ok(VecVal(Cc::new(
-		out.into_iter().map(Val::Str).collect::<Vec<_>>(),
+		out.into_iter()
+			.map(|key| Val::Str(key.into()))
+			.collect::<Vec<_>>(),
 	)))
 }
 
@@ -25,3 +32,209 @@ pub fn builtin_object_fields_ex(
 pub fn builtin_object_has_ex(obj: ObjValue, f: IStr, inc_hidden: bool) -> Result<bool> {
 	Ok(obj.has_field_ex(f, inc_hidden))
 }
+
+/// Sorted visible fields
+fn visible_fields(obj: &ObjValue) -> Vec<IStr> {
+	obj.fields(
+		#[cfg(feature = "exp-preserve-order")]
+		false,
+	)
+}
+
+/// Lazy `obj[key]`, key should exist in object
+#[derive(Trace)]
+struct FieldThunk {
+	obj: ObjValue,
+	key: IStr,
+}
+impl ThunkValue for FieldThunk {
+	type Output = Val;
+	fn get(self: Box<Self>, s: State) -> Result<Val> {
+		Ok(self.obj.get(s, self.key)?.expect("field exists"))
+	}
+}
+fn field_thunk(obj: &ObjValue, key: IStr) -> Thunk<Val> {
+	Thunk::new(tb!(FieldThunk {
+		obj: obj.clone(),
+		key
+	}))
+}
+
+#[builtin]
+pub fn builtin_object_fields(obj: ObjValue) -> Result<VecVal> {
+	Ok(VecVal(Cc::new(
+		visible_fields(&obj)
+			.into_iter()
+			.map(|key| Val::Str(key.into()))
+			.collect::<Vec<_>>(),
+	)))
+}
+
+/// Fields are evaluated eagerly, as in `[o[k] for k in std.objectFields(o)]`
+#[builtin]
+pub fn builtin_object_values(s: State, obj: ObjValue) -> Result<ArrValue> {
+	let mut out = Vec::new();
+	for key in visible_fields(&obj) {
+		out.push(obj.get(s.clone(), key)?.expect("field exists"));
+	}
+	Ok(out.into())
+}
+
+#[builtin]
+pub fn builtin_get(
+	s: State,
+	o: ObjValue,
+	f: IStr,
+	default: Option<Thunk<Val>>,
+	inc_hidden: Option<bool>,
+) -> Result<Any> {
+	if o.has_field_ex(f.clone(), inc_hidden.unwrap_or(true)) {
+		return Ok(Any(o.get(s, f)?.expect("field exists")));
+	}
+	Ok(Any(match default {
+		Some(default) => default.evaluate(s)?,
+		None => Val::Null,
+	}))
+}
+
+#[builtin]
+pub fn builtin_map_with_key(s: State, func: Any, obj: Any) -> Result<ObjValue> {
+	#[derive(Trace)]
+	struct MappedField {
+		func: FuncVal,
+		obj: ObjValue,
+		key: IStr,
+	}
+	impl ThunkValue for MappedField {
+		type Output = Val;
+		fn get(self: Box<Self>, s: State) -> Result<Val> {
+			let value = self.obj.get(s.clone(), self.key.clone())?;
+			self.func
+				.evaluate_simple(s, &(self.key, Any(value.expect("field exists"))))
+		}
+	}
+
+	let Val::Func(func) = func.0 else {
+		throw_runtime!(
+			"std.mapWithKey first param must be function, got {}",
+			func.0.value_type()
+		)
+	};
+	let Val::Obj(obj) = obj.0 else {
+		throw_runtime!(
+			"std.mapWithKey second param must be object, got {}",
+			obj.0.value_type()
+		)
+	};
+	let fields = visible_fields(&obj);
+	let mut out = ObjValueBuilder::with_capacity(fields.len());
+	for key in fields {
+		out.member(key.clone()).binding(
+			s.clone(),
+			MaybeUnbound::Bound(Thunk::new(tb!(MappedField {
+				func: func.clone(),
+				obj: obj.clone(),
+				key,
+			}))),
+		)?;
+	}
+	Ok(out.build())
+}
+
+fn merge_patch(s: State, target: &Thunk<Val>, patch: Val) -> Result<Val> {
+	#[derive(Trace)]
+	struct MergedField {
+		target: Thunk<Val>,
+		patch: Val,
+	}
+	impl ThunkValue for MergedField {
+		type Output = Val;
+		fn get(self: Box<Self>, s: State) -> Result<Val> {
+			merge_patch(s, &self.target, self.patch)
+		}
+	}
+
+	let Val::Obj(patch) = patch else {
+		return Ok(patch);
+	};
+	let target = match target.evaluate(s.clone())? {
+		Val::Obj(target) => target,
+		_ => ObjValue::new_empty(),
+	};
+	let mut out = ObjValueBuilder::new();
+	for key in visible_fields(&target) {
+		if !patch.has_field(key.clone()) {
+			out.member(key.clone())
+				.binding(s.clone(), MaybeUnbound::Bound(field_thunk(&target, key)))?;
+		}
+	}
+	for key in visible_fields(&patch) {
+		let value = patch.get(s.clone(), key.clone())?.expect("field exists");
+		// Null removes field from target
+		if matches!(value, Val::Null) {
+			continue;
+		}
+		let target = if target.has_field(key.clone()) {
+			field_thunk(&target, key.clone())
+		} else {
+			Thunk::evaluated(Val::Null)
+		};
+		out.member(key).binding(
+			s.clone(),
+			MaybeUnbound::Bound(Thunk::new(tb!(MergedField {
+				target,
+				patch: value,
+			}))),
+		)?;
+	}
+	Ok(Val::Obj(out.build()))
+}
+
+#[builtin]
+pub fn builtin_merge_patch(s: State, target: Thunk<Val>, patch: Any) -> Result<Any> {
+	merge_patch(s, &target, patch.0).map(Any)
+}
+
+/// Empty arrays, objects and nulls are removed by `std.prune`
+///
+/// Only called on pruned values, which have no hidden fields
+fn is_content(v: &Val) -> bool {
+	match v {
+		Val::Null => false,
+		Val::Arr(a) => !a.is_empty(),
+		Val::Obj(o) => !o.is_empty(),
+		_ => true,
+	}
+}
+
+fn prune(s: State, value: Val) -> Result<Val> {
+	Ok(match value {
+		Val::Arr(arr) => {
+			let mut out = Vec::new();
+			for item in arr.iter(s.clone()) {
+				let item = prune(s.clone(), item?)?;
+				if is_content(&item) {
+					out.push(item);
+				}
+			}
+			Val::Arr(out.into())
+		}
+		Val::Obj(obj) => {
+			let mut out = ObjValueBuilder::new();
+			for key in visible_fields(&obj) {
+				let value = obj.get(s.clone(), key.clone())?.expect("field exists");
+				let value = prune(s.clone(), value)?;
+				if is_content(&value) {
+					out.member(key).value(s.clone(), value)?;
+				}
+			}
+			Val::Obj(out.build())
+		}
+		v => v,
+	})
+}
+
+#[builtin]
+pub fn builtin_prune(s: State, a: Any) -> Result<Any> {
+	prune(s, a.0).map(Any)
+}
@@ -18,7 +18,7 @@ pub fn builtin_mod(s: State, a: Either![f64, IStr], b: Any) -> Result<Any> {
 		s,
 		&match a {
 			A(v) => Val::Num(v),
-			B(s) => Val::Str(s),
+			B(s) => Val::Str(s.into()),
 		},
 		&b.0,
 	)?))
@@ -1,11 +1,66 @@
 use jrsonnet_evaluator::{
 	error::{Error::RuntimeError, Result},
 	function::builtin,
+	throw,
 	typed::{Any, Typed},
-	IStr, State, Val,
+	IStr, Int, State, Val,
 };
 use serde::Deserialize;
 
+/// Parses integer exactly, result is [`Val::Int`], if it is out of `f64` safe integer range,
+/// and [`Val::Num`] if it is out of [`Int`] range
+fn parse_int(str: &str, radix: u32, signed: bool) -> Result<Any> {
+	let digits = if signed {
+		str.strip_prefix('-').unwrap_or(str)
+	} else {
+		str
+	};
+	if !digits.chars().all(|c| c.is_digit(radix)) {
+		throw!(RuntimeError(
+			format!("{digits} is not a base {radix} integer").into()
+		))
+	}
+	if let Some(int) = Int::from_str_radix(str, radix)? {
+		return Ok(Any(int.into_val()));
+	}
+	// Out of integer range, precision is lost
+	if radix == 10 {
+		return Ok(Any(Val::Num(str.parse().expect("digits are checked"))));
+	}
+	let value = digits.chars().fold(0.0, |acc, c| {
+		acc * f64::from(radix) + f64::from(c.to_digit(radix).expect("digits are checked"))
+	});
+	Ok(Any(Val::Num(if digits.len() == str.len() {
+		value
+	} else {
+		-value
+	})))
+}
+
+#[builtin]
+pub fn builtin_parse_int(str: IStr) -> Result<Any> {
+	if str.is_empty() || &*str == "-" {
+		throw!(RuntimeError(format!("Not an integer: \"{str}\"").into()))
+	}
+	parse_int(&str, 10, true)
+}
+
+#[builtin]
+pub fn builtin_parse_octal(str: IStr) -> Result<Any> {
+	if str.is_empty() {
+		throw!(RuntimeError("Not an octal number: \"\"".into()))
+	}
+	parse_int(&str, 8, false)
+}
+
+#[builtin]
+pub fn builtin_parse_hex(str: IStr) -> Result<Any> {
+	if str.is_empty() {
+		throw!(RuntimeError("Not hexadecimal: \"\"".into()))
+	}
+	parse_int(&str, 16, false)
+}
+
 #[builtin]
 pub fn builtin_parse_json(st: State, s: IStr) -> Result<Any> {
 	use serde_json::Value;
@@ -0,0 +1,192 @@
+use std::cmp::Ordering;
+
+use jrsonnet_evaluator::{
+	error::Result,
+	function::{builtin, FuncVal},
+	operator::evaluate_compare_op,
+	parser::BinaryOpType,
+	typed::Any,
+	val::{equals, ArrValue},
+	State, Val,
+};
+use jrsonnet_gcmodule::Cc;
+
+use crate::sort::sort_keyed;
+
+/// Pairs array elements with their keys, `key_f` is called once per element
+fn keyed(s: State, arr: &ArrValue, key_f: &FuncVal) -> Result<Vec<(Val, Val)>> {
+	let mut out = Vec::with_capacity(arr.len());
+	for value in arr.iter(s.clone()) {
+		let value = value?;
+		let key = key(s.clone(), &value, key_f)?;
+		out.push((value, key));
+	}
+	Ok(out)
+}
+
+fn key(s: State, value: &Val, key_f: &FuncVal) -> Result<Val> {
+	if key_f.is_identity() {
+		Ok(value.clone())
+	} else {
+		key_f.evaluate_simple(s, &(Any(value.clone()),))
+	}
+}
+
+/// Same ordering, as `==` and `<` operators of keys
+fn cmp_keys(s: State, a: &Val, b: &Val) -> Result<Ordering> {
+	if equals(s.clone(), a, b)? {
+		return Ok(Ordering::Equal);
+	}
+	evaluate_compare_op(s, a, BinaryOpType::Lt, b)
+}
+
+fn uniq_keyed(s: State, vk: Vec<(Val, Val)>) -> Result<Vec<Val>> {
+	let mut out = Vec::with_capacity(vk.len());
+	let mut last_key = None;
+	for (value, key) in vk {
+		if let Some(last_key) = &last_key {
+			if equals(s.clone(), last_key, &key)? {
+				continue;
+			}
+		}
+		out.push(value);
+		last_key = Some(key);
+	}
+	Ok(out)
+}
+
+fn values(vk: &[(Val, Val)]) -> impl Iterator<Item = Val> + '_ {
+	vk.iter().map(|(v, _)| v.clone())
+}
+
+#[builtin]
+#[allow(non_snake_case)]
+pub fn builtin_uniq(s: State, arr: ArrValue, keyF: Option<FuncVal>) -> Result<ArrValue> {
+	if arr.len() <= 1 {
+		return Ok(arr);
+	}
+	let vk = keyed(s.clone(), &arr, &keyF.unwrap_or_else(FuncVal::identity))?;
+	Ok(ArrValue::Eager(Cc::new(uniq_keyed(s, vk)?)))
+}
+
+#[builtin]
+#[allow(non_snake_case)]
+pub fn builtin_set(s: State, arr: ArrValue, keyF: Option<FuncVal>) -> Result<ArrValue> {
+	if arr.len() <= 1 {
+		return Ok(arr);
+	}
+	let mut vk = keyed(s.clone(), &arr, &keyF.unwrap_or_else(FuncVal::identity))?;
+	sort_keyed(&mut vk)?;
+	Ok(ArrValue::Eager(Cc::new(uniq_keyed(s, vk)?)))
+}
+
+#[builtin]
+#[allow(non_snake_case)]
+pub fn builtin_set_member(s: State, x: Any, arr: ArrValue, keyF: Option<FuncVal>) -> Result<bool> {
+	let key_f = keyF.unwrap_or_else(FuncVal::identity);
+	let x = key(s.clone(), &x.0, &key_f)?;
+	let (mut from, mut to) = (0, arr.len());
+	while from < to {
+		let mid = (from + to) / 2;
+		let item = arr.get(s.clone(), mid)?.expect("index is in bounds");
+		match cmp_keys(s.clone(), &x, &key(s.clone(), &item, &key_f)?)? {
+			Ordering::Less => to = mid,
+			Ordering::Equal => return Ok(true),
+			Ordering::Greater => from = mid + 1,
+		}
+	}
+	Ok(false)
+}
+
+#[builtin]
+#[allow(non_snake_case)]
+pub fn builtin_set_union(
+	s: State,
+	a: ArrValue,
+	b: ArrValue,
+	keyF: Option<FuncVal>,
+) -> Result<ArrValue> {
+	let key_f = keyF.unwrap_or_else(FuncVal::identity);
+	let a = keyed(s.clone(), &a, &key_f)?;
+	let b = keyed(s.clone(), &b, &key_f)?;
+	let mut out = Vec::with_capacity(a.len() + b.len());
+	let (mut i, mut j) = (0, 0);
+	while i < a.len() && j < b.len() {
+		// On equal keys, value from `a` wins
+		match cmp_keys(s.clone(), &a[i].1, &b[j].1)? {
+			Ordering::Equal => {
+				out.push(a[i].0.clone());
+				i += 1;
+				j += 1;
+			}
+			Ordering::Less => {
+				out.push(a[i].0.clone());
+				i += 1;
+			}
+			Ordering::Greater => {
+				out.push(b[j].0.clone());
+				j += 1;
+			}
+		}
+	}
+	out.extend(values(&a[i..]));
+	out.extend(values(&b[j..]));
+	Ok(ArrValue::Eager(Cc::new(out)))
+}
+
+#[builtin]
+#[allow(non_snake_case)]
+pub fn builtin_set_inter(
+	s: State,
+	a: ArrValue,
+	b: ArrValue,
+	keyF: Option<FuncVal>,
+) -> Result<ArrValue> {
+	let key_f = keyF.unwrap_or_else(FuncVal::identity);
+	let a = keyed(s.clone(), &a, &key_f)?;
+	let b = keyed(s.clone(), &b, &key_f)?;
+	let mut out = Vec::new();
+	let (mut i, mut j) = (0, 0);
+	while i < a.len() && j < b.len() {
+		match cmp_keys(s.clone(), &a[i].1, &b[j].1)? {
+			Ordering::Equal => {
+				out.push(a[i].0.clone());
+				i += 1;
+				j += 1;
+			}
+			Ordering::Less => i += 1,
+			Ordering::Greater => j += 1,
+		}
+	}
+	Ok(ArrValue::Eager(Cc::new(out)))
+}
+
+#[builtin]
+#[allow(non_snake_case)]
+pub fn builtin_set_diff(
+	s: State,
+	a: ArrValue,
+	b: ArrValue,
+	keyF: Option<FuncVal>,
+) -> Result<ArrValue> {
+	let key_f = keyF.unwrap_or_else(FuncVal::identity);
+	let a = keyed(s.clone(), &a, &key_f)?;
+	let b = keyed(s.clone(), &b, &key_f)?;
+	let mut out = Vec::with_capacity(a.len());
+	let (mut i, mut j) = (0, 0);
+	while i < a.len() && j < b.len() {
+		match cmp_keys(s.clone(), &a[i].1, &b[j].1)? {
+			Ordering::Equal => {
+				i += 1;
+				j += 1;
+			}
+			Ordering::Less => {
+				out.push(a[i].0.clone());
+				i += 1;
+			}
+			Ordering::Greater => j += 1,
+		}
+	}
+	out.extend(values(&a[i..]));
+	Ok(ArrValue::Eager(Cc::new(out)))
+}
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use jrsonnet_evaluator::{
 	error::Result,
 	function::{builtin, FuncVal},
@@ -15,17 +17,13 @@ enum SortKeyType {
 	Unknown,
 }
 
-#[derive(PartialEq)]
-struct NonNaNf64(f64);
-impl PartialOrd for NonNaNf64 {
-	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-		self.0.partial_cmp(&other.0)
-	}
-}
-impl Eq for NonNaNf64 {}
-impl Ord for NonNaNf64 {
-	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-		self.partial_cmp(other).expect("non nan")
+fn cmp_numbers(a: &Val, b: &Val) -> Ordering {
+	match (a, b) {
+		(Val::Num(a), Val::Num(b)) => a.partial_cmp(b).expect("non nan"),
+		(Val::Int(a), Val::Int(b)) => a.cmp(b),
+		(Val::Int(a), Val::Num(b)) => a.cmp_f64(*b),
+		(Val::Num(a), Val::Int(b)) => b.cmp_f64(*a).reverse(),
+		_ => unreachable!(),
 	}
 }
 
@@ -38,9 +36,10 @@ fn get_sort_type<T>(
 		let i = key_getter(i);
 		match (i, sort_type) {
 			(Val::Str(_), SortKeyType::Unknown) => sort_type = SortKeyType::String,
-			(Val::Num(_), SortKeyType::Unknown) => sort_type = SortKeyType::Number,
-			(Val::Str(_), SortKeyType::String) | (Val::Num(_), SortKeyType::Number) => {}
-			(Val::Str(_) | Val::Num(_), _) => {
+			(Val::Num(_) | Val::Int(_), SortKeyType::Unknown) => sort_type = SortKeyType::Number,
+			(Val::Str(_), SortKeyType::String)
+			| (Val::Num(_) | Val::Int(_), SortKeyType::Number) => {}
+			(Val::Str(_) | Val::Num(_) | Val::Int(_), _) => {
 				throw_runtime!("sort elements should have the same types")
 			}
 			_ => throw_runtime!("sort key should either be a string or a number"),
@@ -59,10 +58,7 @@ pub fn sort(s: State, values: Cc<Vec<Val>>, key_getter: FuncVal) -> Result<Cc<Ve
 		let mut values = (*values).clone();
 		let sort_type = get_sort_type(&mut values, |k| k)?;
 		match sort_type {
-			SortKeyType::Number => values.sort_unstable_by_key(|v| match v {
-				Val::Num(n) => NonNaNf64(*n),
-				_ => unreachable!(),
-			}),
+			SortKeyType::Number => values.sort_unstable_by(cmp_numbers),
 			SortKeyType::String => values.sort_unstable_by_key(|v| match v {
 				Val::Str(s) => s.clone(),
 				_ => unreachable!(),
@@ -79,22 +75,28 @@ pub fn sort(s: State, values: Cc<Vec<Val>>, key_getter: FuncVal) -> Result<Cc<Ve
 				key_getter.evaluate_simple(s.clone(), &(Any(value.clone()),))?,
 			));
 		}
-		let sort_type = get_sort_type(&mut vk, |v| &mut v.1)?;
-		match sort_type {
-			SortKeyType::Number => vk.sort_by_key(|v| match v.1 {
-				Val::Num(n) => NonNaNf64(n),
-				_ => unreachable!(),
-			}),
-			SortKeyType::String => vk.sort_by_key(|v| match &v.1 {
-				Val::Str(s) => s.clone(),
-				_ => unreachable!(),
-			}),
-			SortKeyType::Unknown => unreachable!(),
-		};
+		sort_keyed(&mut vk)?;
 		Ok(Cc::new(vk.into_iter().map(|v| v.0).collect()))
 	}
 }
 
+/// Stable sort of (value, key) pairs by key
+pub fn sort_keyed(vk: &mut [(Val, Val)]) -> Result<()> {
+	if vk.len() <= 1 {
+		return Ok(());
+	}
+	let sort_type = get_sort_type(vk, |v| &mut v.1)?;
+	match sort_type {
+		SortKeyType::Number => vk.sort_by(|a, b| cmp_numbers(&a.1, &b.1)),
+		SortKeyType::String => vk.sort_by_key(|v| match &v.1 {
+			Val::Str(s) => s.clone(),
+			_ => unreachable!(),
+		}),
+		SortKeyType::Unknown => unreachable!(),
+	};
+	Ok(())
+}
+
 #[builtin]
 #[allow(non_snake_case)]
 pub fn builtin_sort(s: State, arr: ArrValue, keyF: Option<FuncVal>) -> Result<ArrValue> {
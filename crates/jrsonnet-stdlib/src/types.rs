@@ -11,7 +11,7 @@ pub fn builtin_is_string(x: Any) -> Result<bool> {
 }
 #[builtin]
 pub fn builtin_is_number(x: Any) -> Result<bool> {
-	Ok(matches!(x.0, Val::Num(_)))
+	Ok(matches!(x.0, Val::Num(_) | Val::Int(_)))
 }
 #[builtin]
 pub fn builtin_is_boolean(x: Any) -> Result<bool> {
@@ -0,0 +1,100 @@
+use std::{env, fs};
+
+use jrsonnet_evaluator::{error::Result, FileImportResolver, ManifestFormat, State, Val};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+const CODE: &str = "local fib(n) =
+  if n <= 1 then n
+  else fib(n - 1) + fib(n - 2);
+{
+  a: fib(4),
+  b: [x * 2 for x in [1, 2]],
+  c: if self.a > 0 then error 'positive' else null,
+}
+";
+
+fn collected(s: &State, values: Vec<Result<Val>>) -> Vec<String> {
+	values
+		.into_iter()
+		.map(|v| match v.and_then(|v| s.manifest(v)) {
+			Ok(v) => v.to_string(),
+			Err(e) => s.stringify_err(&e).lines().next().unwrap_or("").to_owned(),
+		})
+		.collect()
+}
+
+#[test]
+fn records_values() -> Result<()> {
+	let path = env::temp_dir().join(format!(
+		"jrsonnet-breakpoint-{}.jsonnet",
+		std::process::id()
+	));
+	fs::write(&path, CODE).expect("temp file is writable");
+
+	let s = State::default();
+	s.with_stdlib();
+	s.set_import_resolver(Box::new(FileImportResolver::default()));
+	s.set_manifest_format(ManifestFormat::ToString);
+
+	let resolved = s.resolve(&path)?;
+	let whole_if = s.add_breakpoint(resolved.clone(), 2, None)?;
+	let else_branch = s.add_breakpoint(resolved.clone(), 3, None)?;
+	let multiplication = s.add_breakpoint(resolved.clone(), 6, Some(7))?;
+	let error = s.add_breakpoint(resolved.clone(), 7, Some(25))?;
+	ensure!(s.add_breakpoint(resolved, 2, Some(100)).is_err());
+
+	let v = s.import(&path)?;
+	let e = s.manifest(v).unwrap_err();
+	fs::remove_file(&path).expect("temp file is removable");
+	ensure!(s.stringify_err(&e).contains("positive"));
+
+	// Every recursive call is recorded, nested expressions at the same line are not
+	ensure_eq!(
+		collected(&s, whole_if.take_collected()),
+		["1", "0", "1", "1", "2", "1", "0", "1", "3"]
+	);
+	ensure_eq!(
+		collected(&s, else_branch.take_collected()),
+		["1", "2", "1", "3"]
+	);
+	ensure_eq!(collected(&s, multiplication.take_collected()), ["2", "4"]);
+	ensure_eq!(
+		collected(&s, error.take_collected()),
+		["runtime error: positive"]
+	);
+	Ok(())
+}
+
+#[test]
+fn overlapping_breakpoints() -> Result<()> {
+	let path = env::temp_dir().join(format!(
+		"jrsonnet-breakpoint-overlap-{}.jsonnet",
+		std::process::id()
+	));
+	fs::write(&path, CODE).expect("temp file is writable");
+
+	let s = State::default();
+	s.with_stdlib();
+	s.set_import_resolver(Box::new(FileImportResolver::default()));
+	s.set_manifest_format(ManifestFormat::ToString);
+
+	let resolved = s.resolve(&path)?;
+	let line = s.add_breakpoint(resolved.clone(), 6, None)?;
+	let multiplication = s.add_breakpoint(resolved.clone(), 6, Some(7))?;
+	let same_multiplication = s.add_breakpoint(resolved, 6, Some(7))?;
+
+	let v = s.evaluate_snippet("snip", format!("(import {path:?}).b"))?;
+	s.manifest(v)?;
+	fs::remove_file(&path).expect("temp file is removable");
+
+	// Every breakpoint records values independently
+	ensure_eq!(collected(&s, line.take_collected()), ["[2, 4]"]);
+	ensure_eq!(collected(&s, multiplication.take_collected()), ["2", "4"]);
+	ensure_eq!(
+		collected(&s, same_multiplication.take_collected()),
+		["2", "4"]
+	);
+	Ok(())
+}
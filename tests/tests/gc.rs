@@ -0,0 +1,29 @@
+use jrsonnet_evaluator::{error::Result, State, Val};
+use jrsonnet_gcmodule::{collect_thread_cycles, count_thread_tracked};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+/// Every iteration leaves an unreachable cycle between function context and object
+const CYCLIC_GARBAGE: &str = "
+	std.foldl(function(acc, i) acc + (local o = { v: i, self_ref: o }; o.v), std.range(1, 20000), 0)
+";
+
+fn tracked_after_evaluation(gc_min_objects: Option<usize>) -> Result<usize> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_gc_min_objects(gc_min_objects);
+	collect_thread_cycles();
+	let before = count_thread_tracked();
+	let v = s.evaluate_snippet("snip", CYCLIC_GARBAGE)?;
+	ensure_val_eq!(s, v, Val::Num(200_010_000.0));
+	Ok(count_thread_tracked() - before)
+}
+
+#[test]
+fn collects_cycles_during_evaluation() -> Result<()> {
+	let leaked = tracked_after_evaluation(None)?;
+	let collected = tracked_after_evaluation(Some(1000))?;
+	ensure!(collected * 4 < leaked);
+	Ok(())
+}
@@ -36,6 +36,10 @@ fn run(root: &Path, file: &Path) -> String {
 	}
 }
 
+/// Results of these files depend on integers being limited to `i64`
+#[cfg(feature = "exp-bigint")]
+const I64_ONLY: &[&str] = &["int64_shift.jsonnet"];
+
 #[test]
 fn test() -> io::Result<()> {
 	let mut root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -46,6 +50,10 @@ fn test() -> io::Result<()> {
 		if !entry.path().extension().map_or(false, |e| e == "jsonnet") {
 			continue;
 		}
+		#[cfg(feature = "exp-bigint")]
+		if I64_ONLY.iter().any(|name| entry.file_name() == *name) {
+			continue;
+		}
 
 		let result = run(&root, &entry.path());
 
@@ -0,0 +1,195 @@
+use jrsonnet_evaluator::{error::Result, State};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+fn manifest(s: &State, code: &str) -> Result<String> {
+	let v = s.evaluate_snippet("snip", code)?;
+	Ok(s.manifest(v)?.to_string())
+}
+
+#[test]
+fn bitwise_is_exact() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	ensure_eq!(manifest(&s, "1 << 40")?, "1099511627776");
+	ensure_eq!(manifest(&s, "1 << 62")?, "4611686018427387904");
+	ensure_eq!(manifest(&s, "(1 << 62) + 1")?, "4611686018427387905");
+	ensure_eq!(manifest(&s, "(1 << 53) | 1")?, "9007199254740993");
+	ensure_eq!(manifest(&s, "((1 << 62) + 3) & 7")?, "3");
+	ensure_eq!(manifest(&s, "((1 << 62) + 5) >> 1")?, "2305843009213693954");
+	ensure_eq!(manifest(&s, "~(1 << 62)")?, "-4611686018427387905");
+	ensure_eq!(manifest(&s, "-(1 << 62) - 1")?, "-4611686018427387905");
+	ensure_eq!(manifest(&s, "-8 >> 1")?, "-4");
+	ensure_eq!(manifest(&s, "5.7 & 3")?, "1");
+	Ok(())
+}
+
+#[test]
+fn parse_is_exact() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	ensure_eq!(
+		manifest(&s, "std.parseInt('9007199254740993')")?,
+		"9007199254740993"
+	);
+	ensure_eq!(
+		manifest(&s, "std.parseInt('-9223372036854775808')")?,
+		"-9223372036854775808"
+	);
+	ensure_eq!(
+		manifest(&s, "std.parseHex('7fffffffffffffff')")?,
+		"9223372036854775807"
+	);
+	ensure_eq!(manifest(&s, "std.parseOctal('777')")?, "511");
+	ensure_eq!(manifest(&s, "std.parseHex('fF')")?, "255");
+	ensure_eq!(manifest(&s, "std.parseInt('-12')")?, "-12");
+	Ok(())
+}
+
+#[test]
+fn decimal_format_is_exact() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	for (code, result) in [
+		("std.format('%d', (1 << 62) + 1)", "4611686018427387905"),
+		("std.format('%d', -(1 << 62) - 1)", "-4611686018427387905"),
+		("std.format('%+d', (1 << 62) + 1)", "+4611686018427387905"),
+		(
+			"std.format('%022d', -(1 << 62) - 1)",
+			"-004611686018427387905",
+		),
+		("std.format('%.20d', (1 << 62) + 1)", "04611686018427387905"),
+		("'%d' % ((1 << 62) + 3)", "4611686018427387907"),
+	] {
+		ensure_eq!((code, manifest(&s, code)?), (code, format!("\"{result}\"")));
+	}
+	Ok(())
+}
+
+#[test]
+fn mixes_with_numbers() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	for code in [
+		"std.type(1 << 60) == 'number'",
+		"std.isNumber(1 << 60)",
+		"(1 << 60) == std.pow(2, 60)",
+		"(1 << 60) + 1 != std.pow(2, 60)",
+		"(1 << 60) + 1 > std.pow(2, 60)",
+		"(1 << 60) < 1e300",
+		"(1 << 60) > 0.5",
+		"(1 << 60) / 2 == 1 << 59",
+		"((1 << 60) + 1) % 2 == 1",
+		"(1 << 60) - (1 << 60) == 0",
+		"std.sort([1 << 60, 1, (1 << 60) - 1]) == [1, (1 << 60) - 1, 1 << 60]",
+		"'' + ((1 << 60) + 1) == '1152921504606846977'",
+	] {
+		ensure_eq!((code, manifest(&s, code)?), (code, "true".to_owned()));
+	}
+	Ok(())
+}
+
+#[test]
+fn invalid_operations() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	let e = s.evaluate_snippet("snip", "1 << -1").unwrap_err();
+	ensure!(s.stringify_err(&e).contains("shift by negative exponent"));
+	let e = s.evaluate_snippet("snip", "(1 << 60) % 0").unwrap_err();
+	ensure!(s.stringify_err(&e).contains("attempted to divide by zero"));
+	let e = s
+		.evaluate_snippet("snip", "std.parseInt('+1')")
+		.unwrap_err();
+	ensure!(s.stringify_err(&e).contains("+1 is not a base 10 integer"));
+	let e = s.evaluate_snippet("snip", "std.parseHex('g')").unwrap_err();
+	ensure!(s.stringify_err(&e).contains("g is not a base 16 integer"));
+	let e = s.evaluate_snippet("snip", "[1, 2][1 << 62]").unwrap_err();
+	ensure!(s
+		.stringify_err(&e)
+		.contains("array out of bounds: 4611686018427387904 is not within [0,2)"));
+	let e = s.evaluate_snippet("snip", "'abc'[1 << 62]").unwrap_err();
+	ensure!(s
+		.stringify_err(&e)
+		.contains("string out of bounds: 4611686018427387904 is not within [0,3)"));
+	Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "exp-bigint"))]
+fn left_shift_wraps() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	for (code, result) in [
+		("1 << 63", "-9223372036854775808"),
+		("(1 << 62) << 2", "0"),
+		("3 << 63", "-9223372036854775808"),
+		("-1 << 70", "0"),
+		("1 << 64", "0"),
+		("(1 << 62) << 1 >> 62", "-2"),
+	] {
+		ensure_eq!((code, manifest(&s, code)?), (code, result.to_owned()));
+	}
+
+	// Floats out of integer range can't be used in bitwise operations
+	let e = s.evaluate_snippet("snip", "~((1 << 62) * 4)").unwrap_err();
+	ensure!(s.stringify_err(&e).starts_with("integer overflow"));
+	Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "exp-bigint"))]
+fn arithmetic_overflow_is_inexact() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	for (code, result) in [
+		("(1 << 62) * 4", "18446744073709552000"),
+		("(1 << 62) + (1 << 62)", "9223372036854776000"),
+		(
+			"-(1 << 62) - (1 << 62) - (1 << 62)",
+			"-13835058055282164000",
+		),
+		("-(-(1 << 62) * 2)", "9223372036854776000"),
+		("((1 << 62) * 4) / 4", "4611686018427388000"),
+		("std.parseInt('9223372036854775808')", "9223372036854776000"),
+		(
+			"std.parseInt('-99999999999999999999')",
+			"-100000000000000000000",
+		),
+		(
+			"std.parseHex('ffffffffffffffffff')",
+			"4722366482869645000000",
+		),
+		(
+			"std.parseOctal('7777777777777777777777')",
+			"73786976294838210000",
+		),
+	] {
+		ensure_eq!((code, manifest(&s, code)?), (code, result.to_owned()));
+	}
+	Ok(())
+}
+
+#[test]
+#[cfg(feature = "exp-bigint")]
+fn arbitrary_precision() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	ensure_eq!(manifest(&s, "1 << 64")?, "18446744073709551616");
+	ensure_eq!(manifest(&s, "(1 << 62) * 4 + 1")?, "18446744073709551617");
+	ensure_eq!(
+		manifest(&s, "std.parseInt('-99999999999999999999')")?,
+		"-99999999999999999999"
+	);
+	ensure_eq!(manifest(&s, "~(1 << 100) & 255")?, "255");
+	ensure_eq!(manifest(&s, "-(1 << 100) >> 98")?, "-4");
+	Ok(())
+}
@@ -0,0 +1,104 @@
+use std::{any::Any, cell::RefCell, collections::HashMap, path::Path, rc::Rc};
+
+use jrsonnet_evaluator::{
+	error::{Error, Result},
+	parser::{SourcePath, SourceVirtual},
+	throw, ImportResolver, State, Thunk, Val,
+};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+/// Serves files from shared in-memory map, which may be modified between evaluations
+#[derive(Clone, Default)]
+struct MemoryResolver(Rc<RefCell<HashMap<String, String>>>);
+impl MemoryResolver {
+	fn set(&self, name: &str, code: &str) {
+		self.0.borrow_mut().insert(name.to_owned(), code.to_owned());
+	}
+}
+impl ImportResolver for MemoryResolver {
+	fn resolve_from(&self, _from: &SourcePath, path: &str) -> Result<SourcePath> {
+		self.resolve(Path::new(path))
+	}
+	fn resolve(&self, path: &Path) -> Result<SourcePath> {
+		let path = path.to_str().expect("utf8");
+		if !self.0.borrow().contains_key(path) {
+			throw!(Error::AbsoluteImportFileNotFound(path.into()))
+		}
+		Ok(SourcePath::new(SourceVirtual(path.into())))
+	}
+	fn load_file_contents(&self, resolved: &SourcePath) -> Result<Vec<u8>> {
+		let name = resolved.to_string();
+		Ok(self.0.borrow()[&name].as_bytes().to_vec())
+	}
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+}
+
+fn setup() -> (State, MemoryResolver) {
+	let s = State::default();
+	s.with_stdlib();
+	let resolver = MemoryResolver::default();
+	s.set_import_resolver(Box::new(resolver.clone()));
+	(s, resolver)
+}
+
+#[test]
+fn cached_until_invalidated() -> Result<()> {
+	let (s, files) = setup();
+	files.set("a.jsonnet", "1");
+
+	ensure_val_eq!(s, s.import("a.jsonnet")?, Val::Num(1.0));
+	files.set("a.jsonnet", "2");
+	ensure_val_eq!(s, s.import("a.jsonnet")?, Val::Num(1.0));
+
+	ensure!(s.invalidate(&s.resolve("a.jsonnet")?));
+	ensure_val_eq!(s, s.import("a.jsonnet")?, Val::Num(2.0));
+
+	Ok(())
+}
+
+#[test]
+fn importers_are_invalidated() -> Result<()> {
+	let (s, files) = setup();
+	files.set("main.jsonnet", "(import 'mid.jsonnet') + 1");
+	files.set("mid.jsonnet", "(import 'leaf.jsonnet') * 10");
+	files.set("leaf.jsonnet", "1");
+	files.set("unrelated.jsonnet", "std.length(importstr 'leaf.jsonnet')");
+
+	ensure_val_eq!(s, s.import("main.jsonnet")?, Val::Num(11.0));
+	ensure_val_eq!(s, s.import("unrelated.jsonnet")?, Val::Num(1.0));
+
+	files.set("leaf.jsonnet", "22");
+	ensure!(s.invalidate(&s.resolve("leaf.jsonnet")?));
+	ensure_val_eq!(s, s.import("main.jsonnet")?, Val::Num(221.0));
+	ensure_val_eq!(s, s.import("unrelated.jsonnet")?, Val::Num(2.0));
+
+	Ok(())
+}
+
+#[test]
+fn invalidate_unknown() -> Result<()> {
+	let (s, files) = setup();
+	files.set("a.jsonnet", "1");
+
+	ensure!(!s.invalidate(&s.resolve("a.jsonnet")?));
+
+	Ok(())
+}
+
+#[test]
+fn failed_file_is_resolved_again_for_new_globals() -> Result<()> {
+	let (s, files) = setup();
+	files.set("a.jsonnet", "std.length(import 'b.jsonnet')");
+	ensure!(s.import("a.jsonnet").is_err());
+
+	// Globals are added as another scope, so `std` slot is changed
+	s.add_global("x".into(), Thunk::evaluated(Val::Null));
+	files.set("b.jsonnet", "'abc'");
+	ensure_val_eq!(s, s.import("a.jsonnet")?, Val::Num(3.0));
+
+	Ok(())
+}
@@ -0,0 +1,57 @@
+use jrsonnet_evaluator::{error::Result, throw_runtime, JsonSchema, State};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+const SCHEMA: &str = r#"{
+	"type": "object",
+	"properties": {
+		"name": {"type": "string"},
+		"ports": {"type": "array", "items": {"type": "integer", "minimum": 1}},
+		"labels": {"type": "object", "additionalProperties": {"type": "string"}}
+	},
+	"required": ["name"]
+}"#;
+
+#[test]
+fn schema_valid() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	let schema = JsonSchema::parse(SCHEMA)?;
+
+	let v = s.evaluate_snippet("snip".to_owned(), "{name: 'a', ports: [80, 443]}")?;
+	s.validate_schema(v, &schema)?;
+
+	Ok(())
+}
+
+#[test]
+fn schema_violations() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	let schema = JsonSchema::parse(SCHEMA)?;
+
+	let v = s.evaluate_snippet(
+		"snip".to_owned(),
+		"{ports: [80, 0], labels: {'0': 1, a: 'b'}}",
+	)?;
+	let e = match s.validate_schema(v, &schema) {
+		Ok(_) => throw_runtime!("validation should fail"),
+		Err(e) => e,
+	};
+	let e = e.error().to_string();
+	ensure!(e.starts_with("schema validation failed:\n"));
+	ensure!(e.contains("  - 1 is not of type \"string\" at self.\"labels\".\"0\""));
+	ensure!(e.contains("  - 0 is less than the minimum of 1 at self.\"ports\"[1]"));
+	ensure!(e.contains("  - \"name\" is a required property"));
+
+	Ok(())
+}
+
+#[test]
+fn invalid_schema() -> Result<()> {
+	ensure!(JsonSchema::parse(r#"{"type": 1}"#).is_err());
+	ensure!(JsonSchema::parse("{").is_err());
+
+	Ok(())
+}
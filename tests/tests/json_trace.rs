@@ -0,0 +1,109 @@
+use jrsonnet_evaluator::{
+	error::Result,
+	throw_runtime,
+	trace::{ExplainingFormat, JsonFormat, PathResolver},
+	State,
+};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+#[test]
+fn json_trace() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_trace_format(Box::new(JsonFormat {
+		resolver: PathResolver::Absolute,
+	}));
+
+	let e = match s.evaluate_snippet("snip".to_owned(), "local a = 1;\nerror 'fail'") {
+		Ok(_) => throw_runtime!("evaluation should fail"),
+		Err(e) => e,
+	};
+	ensure_eq!(
+		s.stringify_err(&e),
+		concat!(
+			r#"{"kind":"RuntimeError","location":null,"message":"runtime error: fail","trace":["#,
+			r#"{"description":"error statement","end":{"column":13,"line":2,"offset":25},"#,
+			r#""file":"snip","start":{"column":1,"line":2,"offset":13}}]}"#,
+		)
+	);
+
+	Ok(())
+}
+
+#[test]
+fn json_trace_syntax_error() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_trace_format(Box::new(JsonFormat {
+		resolver: PathResolver::Absolute,
+	}));
+
+	for (code, location) in [
+		(
+			"{a: 1 b: 2}",
+			r#""location":{"end":{"column":8,"line":1,"offset":7},"file":"snip","start":{"column":7,"line":1,"offset":6}}"#,
+		),
+		// Positions are counted in chars, not bytes
+		(
+			"// é\n{a: 1 b: 2}",
+			r#""location":{"end":{"column":8,"line":2,"offset":12},"file":"snip","start":{"column":7,"line":2,"offset":11}}"#,
+		),
+		// Unexpected end of file
+		(
+			"{a: 1",
+			r#""location":{"end":{"column":6,"line":1,"offset":5},"file":"snip","start":{"column":5,"line":1,"offset":4}}"#,
+		),
+	] {
+		let e = match s.evaluate_snippet("snip".to_owned(), code) {
+			Ok(_) => throw_runtime!("parsing should fail"),
+			Err(e) => e,
+		};
+		let e = s.stringify_err(&e);
+		ensure!(e.starts_with(&format!(r#"{{"kind":"ImportSyntaxError",{location}"#)));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn explaining_syntax_error_after_non_ascii() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_trace_format(Box::new(ExplainingFormat {
+		resolver: PathResolver::Absolute,
+	}));
+
+	let e = match s.evaluate_snippet("snip".to_owned(), "// é\n{a: 1 b: 2}") {
+		Ok(_) => throw_runtime!("parsing should fail"),
+		Err(e) => e,
+	};
+	ensure!(s.stringify_err(&e).contains("snip:2:7"));
+
+	Ok(())
+}
+
+#[test]
+fn error_kind() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	for (code, kind) in [
+		("error 'fail'", "RuntimeError"),
+		("a", "VariableIsNotDefined"),
+		("{a: 1}.b", "NoSuchField"),
+		("[1][2]", "ArrayBoundsError"),
+		("1 + {}", "BinaryOperatorDoesNotOperateOnValues"),
+		("assert false; 1", "AssertionFailed"),
+		("{a: 1", "ImportSyntaxError"),
+	] {
+		let e = match s.evaluate_snippet("snip".to_owned(), code) {
+			Ok(_) => throw_runtime!("evaluation should fail"),
+			Err(e) => e,
+		};
+		ensure_eq!((code, e.error().kind()), (code, kind));
+	}
+
+	Ok(())
+}
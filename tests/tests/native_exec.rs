@@ -0,0 +1,137 @@
+#![cfg(unix)]
+
+use std::time::{Duration, Instant};
+
+use jrsonnet_cli::native_exec::ExecNative;
+use jrsonnet_evaluator::{error::Result, function::builtin::Builtin, tb, State, Val};
+use jrsonnet_gcmodule::Cc;
+use jrsonnet_stdlib::{ContextInitializer, StateExt};
+
+mod common;
+
+/// Replies with request params as result
+const ECHO: &str = r#"sed -u 's/^{"jsonrpc":"2.0","id":\([0-9]*\),"method":"[^"]*","params":\(.*\)}$/{"jsonrpc":"2.0","id":\1,"result":\2}/'"#;
+const FAIL: &str = r#"sed -u 's/^.*"id":\([0-9]*\).*$/{"jsonrpc":"2.0","id":\1,"error":{"code":1,"message":"no such secret"}}/'"#;
+
+fn setup(natives: &[(&str, &str)]) -> State {
+	setup_natives(
+		natives
+			.iter()
+			.map(|(name, command)| ExecNative::new(*name, *command)),
+	)
+}
+
+fn setup_natives(natives: impl IntoIterator<Item = ExecNative>) -> State {
+	let s = State::default();
+	s.with_stdlib();
+	{
+		let ctx = s.context_initializer();
+		let ctx = ctx
+			.as_any()
+			.downcast_ref::<ContextInitializer>()
+			.expect("stdlib context initializer");
+		for native in natives {
+			ctx.add_native(native.name().into(), Cc::new(tb!(native)));
+		}
+	}
+	s
+}
+
+#[test]
+fn positional_and_named() -> Result<()> {
+	let s = setup(&[("echo", ECHO)]);
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('echo')(1, 'a', { b: [null, true] })")?,
+		s.evaluate_snippet("snippet", "[1, 'a', { b: [null, true] }]")?
+	);
+	// Same process is reused for subsequent calls
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('echo')(key = 'vault/token')")?,
+		s.evaluate_snippet("snippet", "{ key: 'vault/token' }")?
+	);
+	ensure!(s
+		.evaluate_snippet("snippet", "std.native('echo')(1, key = 2)")
+		.is_err());
+	Ok(())
+}
+
+#[test]
+fn errors() -> Result<()> {
+	let s = setup(&[("lookup", FAIL), ("exited", "true")]);
+	let err = s
+		.evaluate_snippet("snippet", "std.native('lookup')('vault/missing')")
+		.expect_err("error response");
+	ensure!(err.error().to_string().contains("lookup: no such secret"));
+	let err = s
+		.evaluate_snippet("snippet", "std.native('exited')()")
+		.expect_err("process exited");
+	ensure!(err
+		.error()
+		.to_string()
+		.contains("exited: failed to communicate with `true`"));
+	Ok(())
+}
+
+#[test]
+fn returned_value() -> Result<()> {
+	let s = setup(&[("echo", ECHO)]);
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('echo')(1, 2)[1] + 1")?,
+		Val::Num(3.0)
+	);
+	Ok(())
+}
+
+#[test]
+fn response_timeout() -> Result<()> {
+	let s = setup_natives([
+		ExecNative::new("slow", "read line; sleep 30").with_timeout(Duration::from_millis(200))
+	]);
+	let start = Instant::now();
+	let err = s
+		.evaluate_snippet("snippet", "std.native('slow')()")
+		.expect_err("timed out");
+	ensure!(err.error().to_string().contains("no response in"));
+	ensure!(start.elapsed() < Duration::from_secs(10));
+	Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn shutdown_kills_process_group() -> Result<()> {
+	let pid_file = std::env::temp_dir().join(format!("jrsonnet-exec-{}.pid", std::process::id()));
+	// Doesn't exit on closed stdin, and leaves a grandchild
+	let command = format!(
+		r#"sleep 30 & echo $! > {}; read line; echo '{{"jsonrpc":"2.0","id":1,"result":1}}'; wait"#,
+		pid_file.display()
+	);
+	let s = setup(&[("stuck", &command)]);
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('stuck')()")?,
+		Val::Num(1.0)
+	);
+	let grandchild = std::fs::read_to_string(&pid_file).expect("pid written");
+	let _ = std::fs::remove_file(&pid_file);
+	let grandchild = format!("/proc/{}/stat", grandchild.trim());
+	// Killed process may stay zombie, if it isn't reaped by init
+	let alive = || {
+		std::fs::read_to_string(&grandchild)
+			.is_ok_and(|stat| !stat.rsplit(')').next().unwrap_or("").starts_with(" Z"))
+	};
+	ensure!(alive());
+
+	let start = Instant::now();
+	drop(s);
+	// Context initializer is a part of reference cycle
+	jrsonnet_gcmodule::collect_thread_cycles();
+	ensure!(start.elapsed() < Duration::from_secs(10));
+	while alive() && start.elapsed() < Duration::from_secs(10) {
+		std::thread::sleep(Duration::from_millis(10));
+	}
+	ensure!(!alive());
+	Ok(())
+}
@@ -0,0 +1,194 @@
+use std::{
+	ffi::{c_int, c_void, CStr},
+	ptr, slice, str,
+};
+
+use jrsonnet_cli::plugin::{
+	init_plugin, PluginInit, PluginKind, PluginParam, PluginRegister, PluginValue,
+};
+use jrsonnet_evaluator::{error::Result, State, Val};
+use jrsonnet_stdlib::{ContextInitializer, StateExt};
+
+mod common;
+
+unsafe fn arg_str(value: &PluginValue) -> &str {
+	str::from_utf8(slice::from_raw_parts(value.string.cast(), value.string_len)).unwrap()
+}
+unsafe fn set_str(out: *mut PluginValue, kind: PluginKind, value: &'static str) {
+	(*out).kind = kind;
+	(*out).string = value.as_ptr().cast();
+	(*out).string_len = value.len();
+}
+
+unsafe extern "C" fn checksum(
+	_ctx: *mut c_void,
+	args: *const PluginValue,
+	args_len: usize,
+	out: *mut PluginValue,
+) -> c_int {
+	let args = slice::from_raw_parts(args, args_len);
+	let sum: u32 = arg_str(&args[0]).bytes().map(u32::from).sum();
+	(*out).kind = PluginKind::NUMBER;
+	(*out).number = f64::from(sum) % args[1].number;
+	0
+}
+
+/// Returns json argument as is, pointing into argument buffer
+unsafe extern "C" fn identity(
+	_ctx: *mut c_void,
+	args: *const PluginValue,
+	_args_len: usize,
+	out: *mut PluginValue,
+) -> c_int {
+	*out = ptr::read(args);
+	0
+}
+
+unsafe extern "C" fn lookup(
+	ctx: *mut c_void,
+	args: *const PluginValue,
+	_args_len: usize,
+	out: *mut PluginValue,
+) -> c_int {
+	let prefix = *ctx.cast::<&'static str>();
+	match arg_str(&*args).strip_prefix(prefix) {
+		Some("token") => {
+			set_str(out, PluginKind::STRING, "hunter2");
+			0
+		}
+		_ => {
+			set_str(out, PluginKind::STRING, "no such secret");
+			1
+		}
+	}
+}
+
+static PREFIX: &str = "vault/";
+
+unsafe extern "C" fn plugin_init(registrar: *mut c_void, register: PluginRegister) -> c_int {
+	fn param(name: &'static CStr, kind: PluginKind) -> PluginParam {
+		PluginParam {
+			name: name.as_ptr(),
+			kind,
+		}
+	}
+	let checksum_params = [
+		param(c"data", PluginKind::STRING),
+		param(c"modulo", PluginKind::NUMBER),
+	];
+	let identity_params = [param(c"value", PluginKind::JSON)];
+	let lookup_params = [param(c"key", PluginKind::STRING)];
+	register(
+		registrar,
+		c"checksum".as_ptr(),
+		checksum_params.as_ptr(),
+		checksum_params.len(),
+		checksum,
+		ptr::null_mut(),
+	) | register(
+		registrar,
+		c"identity".as_ptr(),
+		identity_params.as_ptr(),
+		identity_params.len(),
+		identity,
+		ptr::null_mut(),
+	) | register(
+		registrar,
+		c"lookup".as_ptr(),
+		lookup_params.as_ptr(),
+		lookup_params.len(),
+		lookup,
+		ptr::addr_of!(PREFIX) as *mut c_void,
+	)
+}
+
+unsafe extern "C" fn invalid_plugin_init(
+	registrar: *mut c_void,
+	register: PluginRegister,
+) -> c_int {
+	let params = [PluginParam {
+		name: c"value".as_ptr(),
+		kind: PluginKind::NULL,
+	}];
+	register(
+		registrar,
+		c"invalid".as_ptr(),
+		params.as_ptr(),
+		params.len(),
+		identity,
+		ptr::null_mut(),
+	)
+}
+
+fn load(s: &State, plugin: PluginInit) -> Result<()> {
+	let ctx = s.context_initializer();
+	let ctx = ctx
+		.as_any()
+		.downcast_ref::<ContextInitializer>()
+		.expect("stdlib context initializer");
+	unsafe { init_plugin(ctx, plugin) }
+}
+
+fn setup() -> Result<State> {
+	let s = State::default();
+	s.with_stdlib();
+	load(&s, plugin_init)?;
+	Ok(s)
+}
+
+#[test]
+fn typed_params() -> Result<()> {
+	let s = setup()?;
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('checksum')('abc', 10)")?,
+		Val::Num(4.0)
+	);
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('checksum')(modulo = 7, data = 'a')")?,
+		Val::Num(6.0)
+	);
+	ensure!(s
+		.evaluate_snippet("snippet", "std.native('checksum')(1, 10)")
+		.is_err());
+	Ok(())
+}
+
+#[test]
+fn json_values() -> Result<()> {
+	let s = setup()?;
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet(
+			"snippet",
+			"std.native('identity')({ a: [1, 'b', null], c: { d: true } })"
+		)?,
+		s.evaluate_snippet("snippet", "{ a: [1, 'b', null], c: { d: true } }")?
+	);
+	Ok(())
+}
+
+#[test]
+fn errors() -> Result<()> {
+	let s = setup()?;
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("snippet", "std.native('lookup')('vault/token')")?,
+		Val::Str("hunter2".into())
+	);
+	let err = s
+		.evaluate_snippet("snippet", "std.native('lookup')('vault/missing')")
+		.expect_err("lookup should fail");
+	ensure!(err.error().to_string().contains("lookup: no such secret"));
+	Ok(())
+}
+
+#[test]
+fn invalid_registration() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	let err = load(&s, invalid_plugin_init).expect_err("null param kind is not allowed");
+	ensure!(err.error().to_string().contains("unsupported kind"));
+	Ok(())
+}
@@ -0,0 +1,94 @@
+use jrsonnet_evaluator::{
+	error::Result,
+	optimize,
+	parser::{parse, unparse, ParserSettings, Source},
+	State, Val,
+};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+fn optimized(s: &State, code: &str) -> String {
+	let source = Source::new_virtual("<test>".into(), code.into());
+	let parsed = parse(
+		code,
+		&ParserSettings {
+			file_name: source.clone(),
+		},
+	)
+	.expect("parse");
+	let ctx = s.create_default_context(source);
+	unparse(&optimize(s.clone(), &ctx, parsed))
+}
+
+#[test]
+fn constants_are_folded() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	ensure_eq!(optimized(&s, "1 + 2 * (3 - 1)"), "5");
+	ensure_eq!(optimized(&s, "'a' + 'b' + 1"), r#""ab1""#);
+	ensure_eq!(optimized(&s, "!(1 < 2)"), "false");
+	ensure_eq!(optimized(&s, "if 'a' == 'a' then x else y"), "x");
+	ensure_eq!(optimized(&s, "if false then x"), "null");
+	ensure_eq!(optimized(&s, "std.length('abc') + std.max(1, 2)"), "5");
+	ensure_eq!(optimized(&s, "std.join(',', ['a', 'b'])"), r#""a,b""#);
+	Ok(())
+}
+
+#[test]
+fn failing_expressions_are_kept() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	ensure_eq!(optimized(&s, "1 / 0"), "1 / 0");
+	ensure_eq!(optimized(&s, "'a' - 1"), r#""a" - 1"#);
+	ensure_eq!(optimized(&s, "std.length(1)"), "std.length(1)");
+	// Arbitrary `std` functions are not called
+	ensure_eq!(optimized(&s, "std.trace('a', 1)"), r#"std.trace("a", 1)"#);
+	Ok(())
+}
+
+#[test]
+fn single_use_locals_are_inlined() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	ensure_eq!(optimized(&s, "local a = 1; a + 2"), "3");
+	ensure_eq!(
+		optimized(&s, "local a = 1, b = 2; local c = 3; [a + b, c, c]"),
+		"local c = 3;\n[3, c, c]",
+	);
+	// Non-literal values are kept, to keep them in stack trace of errors
+	ensure_eq!(optimized(&s, "local a = x + 1; a"), "local a = x + 1;\na");
+	// Inner `a` may refer to the other variable
+	ensure_eq!(
+		optimized(&s, "local a = 1; local f(a) = a; f(a)"),
+		"local a = 1;\nlocal f(a) = a;\nf(a)"
+	);
+	// Calls to `std` are not evaluated, when it may be shadowed
+	ensure_eq!(
+		optimized(&s, "local std = {length(v): 0}; std.length('a')"),
+		"local std = {\n  length(v): 0,\n};\nstd.length(\"a\")"
+	);
+	Ok(())
+}
+
+#[test]
+fn traces_are_preserved() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	let code = "local a = 2;\nlocal b = (a / 0);\nb + 1";
+
+	let unoptimized = s.evaluate_snippet("<test>", code).unwrap_err();
+	s.set_optimize(true);
+	let optimized = s.evaluate_snippet("<test>", code).unwrap_err();
+
+	ensure_eq!(s.stringify_err(&unoptimized), s.stringify_err(&optimized));
+	ensure_val_eq!(
+		s,
+		s.evaluate_snippet("<test>", "local a = 1; {b: a + 1}.b")?,
+		Val::Num(2.0)
+	);
+	Ok(())
+}
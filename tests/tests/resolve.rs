@@ -0,0 +1,45 @@
+use jrsonnet_evaluator::{
+	error::Result,
+	evaluate,
+	parser::{parse, resolve_scoped, ParserSettings, Source},
+	ContextBuilder, State, Thunk, Val,
+};
+
+mod common;
+
+fn eval_resolved(ctx_vars: &[&str], code: &str) -> Result<Val> {
+	let s = State::default();
+	let parsed = parse(
+		code,
+		&ParserSettings {
+			file_name: Source::new_virtual("<test>".into(), code.into()),
+		},
+	)
+	.expect("parse");
+	// Resolved as if `a` and `b` are defined in context
+	let resolved = resolve_scoped(parsed, &[vec!["a".into(), "b".into()]]).expect("resolve");
+
+	let mut ctx = ContextBuilder::new();
+	for (i, name) in ctx_vars.iter().enumerate() {
+		ctx.bind((*name).into(), Thunk::evaluated(Val::Num(i as f64)));
+	}
+	evaluate(s, ctx.build(), &resolved)
+}
+
+#[test]
+fn resolved_globals() -> Result<()> {
+	let Val::Num(n) = eval_resolved(&["a", "b"], "local c = 2; a + b * 10 + c * 100")? else {
+		unreachable!()
+	};
+	ensure_eq!(n, 210.0);
+	Ok(())
+}
+
+#[test]
+fn context_mismatch_is_error() -> Result<()> {
+	for vars in [&["a"][..], &["b", "a"], &[]] {
+		let kind = eval_resolved(vars, "b").map_err(|e| e.error().kind());
+		ensure_eq!((vars, kind.err()), (vars, Some("VariableSlotMismatch")));
+	}
+	Ok(())
+}
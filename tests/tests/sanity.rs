@@ -40,3 +40,17 @@ fn assert_negative() -> Result<()> {
 
 	Ok(())
 }
+
+#[test]
+fn deep_recursion_grows_native_stack() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_max_stack(100_000);
+
+	let v = s.evaluate_snippet(
+		"snip".to_owned(),
+		"local sum(n) = if n == 0 then 0 else n + sum(n - 1); sum(20000)",
+	)?;
+	ensure_val_eq!(s, v, Val::Num(200_010_000.0));
+	Ok(())
+}
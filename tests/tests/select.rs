@@ -0,0 +1,84 @@
+use jrsonnet_evaluator::{
+	error::Result, throw_runtime, State, Val, ValuePath, ValuePathSegment::*,
+};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+#[test]
+fn parse_path() -> Result<()> {
+	ensure_eq!(
+		"a.b[0].c".parse::<ValuePath>(),
+		Ok(ValuePath(vec![
+			Field("a".into()),
+			Field("b".into()),
+			Index(0),
+			Field("c".into()),
+		]))
+	);
+	ensure_eq!(
+		r#".a["dotted.name"]['q\'uote'][12]"#.parse::<ValuePath>(),
+		Ok(ValuePath(vec![
+			Field("a".into()),
+			Field("dotted.name".into()),
+			Field("q'uote".into()),
+			Index(12),
+		]))
+	);
+	ensure_eq!("".parse::<ValuePath>(), Ok(ValuePath::default()));
+	ensure!("a[".parse::<ValuePath>().is_err());
+	ensure!("a..b".parse::<ValuePath>().is_err());
+	ensure!("a[x]".parse::<ValuePath>().is_err());
+	ensure!("a['x".parse::<ValuePath>().is_err());
+
+	Ok(())
+}
+
+#[test]
+fn display_path() -> Result<()> {
+	let path: ValuePath = r#"a["dotted.name"][1]"#.parse().unwrap();
+	ensure_eq!(path.to_string(), r#".a["dotted.name"][1]"#);
+	Ok(())
+}
+
+#[test]
+fn select_lazy() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	let v = s.evaluate_snippet(
+		"snip".to_owned(),
+		"{a: {b: [{c: 1}, error 'element']}, sibling: error 'sibling'}",
+	)?;
+	let selected = v.get_path(s.clone(), &"a.b[0].c".parse().unwrap())?;
+	ensure_val_eq!(s, selected, Val::Num(1.0));
+
+	match v.get_path(s.clone(), &"a.b[2]".parse().unwrap()) {
+		Ok(_) => throw_runtime!("index is out of bounds"),
+		Err(e) => ensure_eq!(
+			e.error().to_string(),
+			"array out of bounds: 2 is not within [0,2)"
+		),
+	}
+
+	Ok(())
+}
+
+#[test]
+fn select_suggestions() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	let v = s.evaluate_snippet("snip".to_owned(), "{xx: 1, nested: {value: 2}}")?;
+	for (path, suggested) in [("x", "xx"), ("nested.valeu", "value")] {
+		match v.get_path(s.clone(), &path.parse().unwrap()) {
+			Ok(_) => throw_runtime!("field is missing"),
+			Err(e) => ensure_eq!(
+				(path, e.error().to_string().contains(suggested)),
+				(path, true)
+			),
+		}
+	}
+
+	Ok(())
+}
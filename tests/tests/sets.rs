@@ -0,0 +1,154 @@
+use std::cell::Cell;
+
+use jrsonnet_evaluator::{
+	error::Result,
+	function::{builtin, FuncVal},
+	typed::Any,
+	State, Thunk, Val,
+};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+/// Previous `std.jsonnet` implementations, native functions should behave the same
+const REFERENCE: &str = r"
+local id(x) = x;
+{
+	uniq(arr, keyF=id)::
+		local f(a, b) =
+			if std.length(a) == 0 then [b]
+			else if keyF(a[std.length(a) - 1]) == keyF(b) then a
+			else a + [b];
+		std.foldl(f, arr, []),
+	set(arr, keyF=id):: self.uniq(std.sort(arr, keyF), keyF),
+	setMember(x, arr, keyF=id):: std.length(self.setInter([x], arr, keyF)) > 0,
+	setUnion(a, b, keyF=id)::
+		local aux(a, b, i, j, acc) =
+			if i >= std.length(a) then acc + b[j:]
+			else if j >= std.length(b) then acc + a[i:]
+			else
+				local ak = keyF(a[i]), bk = keyF(b[j]);
+				if ak == bk then aux(a, b, i + 1, j + 1, acc + [a[i]])
+				else if ak < bk then aux(a, b, i + 1, j, acc + [a[i]])
+				else aux(a, b, i, j + 1, acc + [b[j]]);
+		aux(a, b, 0, 0, []),
+	setInter(a, b, keyF=id)::
+		local aux(a, b, i, j, acc) =
+			if i >= std.length(a) || j >= std.length(b) then acc
+			else if keyF(a[i]) == keyF(b[j]) then aux(a, b, i + 1, j + 1, acc + [a[i]])
+			else if keyF(a[i]) < keyF(b[j]) then aux(a, b, i + 1, j, acc)
+			else aux(a, b, i, j + 1, acc);
+		aux(a, b, 0, 0, []),
+	setDiff(a, b, keyF=id)::
+		local aux(a, b, i, j, acc) =
+			if i >= std.length(a) then acc
+			else if j >= std.length(b) then acc + a[i:]
+			else if keyF(a[i]) == keyF(b[j]) then aux(a, b, i + 1, j + 1, acc)
+			else if keyF(a[i]) < keyF(b[j]) then aux(a, b, i + 1, j, acc + [a[i]])
+			else aux(a, b, i, j + 1, acc);
+		aux(a, b, 0, 0, []),
+}
+";
+
+#[test]
+fn same_as_reference() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	let inputs = [
+		"[]",
+		"[1]",
+		"[3, 1, 2, 1, 3, 3]",
+		"[1, 2, 3, 5, 8]",
+		"[2, 3, 4]",
+		"['b', 'a', 'B', 'c', 'A']",
+		"['a', 'c', 'd']",
+	];
+	let key_fns = [
+		"id",
+		"function(x) if std.isString(x) then std.asciiLower(x) else x",
+		"function(x) if std.isNumber(x) then -x else x",
+	];
+	for key_f in key_fns {
+		for a in inputs {
+			for call in [format!("uniq({a}, {key_f})"), format!("set({a}, {key_f})")] {
+				check(&s, &call)?;
+			}
+			for b in inputs {
+				for call in [
+					format!("setUnion(std.set({a}, {key_f}), std.set({b}, {key_f}), {key_f})"),
+					format!("setInter(std.set({a}, {key_f}), std.set({b}, {key_f}), {key_f})"),
+					format!("setDiff(std.set({a}, {key_f}), std.set({b}, {key_f}), {key_f})"),
+				] {
+					check(&s, &call)?;
+				}
+			}
+			for x in ["1", "3", "9", "'a'", "'C'"] {
+				check(
+					&s,
+					&format!("setMember({x}, std.set({a}, {key_f}), {key_f})"),
+				)?;
+			}
+		}
+	}
+	Ok(())
+}
+
+fn check(s: &State, call: &str) -> Result<()> {
+	let run = |lib: &str| -> String {
+		let code = format!("local id(x) = x; local lib = {lib}; lib.{call}");
+		match s.evaluate_snippet("snip", code) {
+			Ok(v) => s
+				.manifest(v)
+				.map_or_else(|e| s.stringify_err(&e), |v| v.to_string()),
+			Err(e) => s.stringify_err(&e).lines().next().unwrap_or("").to_owned(),
+		}
+	};
+	let native = run("std");
+	let reference = run(REFERENCE);
+	ensure_eq!((call, native), (call, reference));
+	Ok(())
+}
+
+thread_local! {
+	static KEY_CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[builtin]
+fn counted_key(x: Any) -> Result<Any> {
+	KEY_CALLS.with(|c| c.set(c.get() + 1));
+	Ok(x)
+}
+
+#[test]
+fn key_function_is_called_once_per_element() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.add_global(
+		"countedKey".into(),
+		Thunk::evaluated(Val::Func(FuncVal::StaticBuiltin(counted_key::INST))),
+	);
+
+	for (code, calls) in [
+		("std.set(std.range(1, 1000), countedKey)", 1000),
+		("std.uniq(std.range(1, 1000), countedKey)", 1000),
+		(
+			"std.setUnion(std.range(1, 100), std.range(50, 150), countedKey)",
+			201,
+		),
+		(
+			"std.setInter(std.range(1, 100), std.range(50, 150), countedKey)",
+			201,
+		),
+		(
+			"std.setDiff(std.range(1, 100), std.range(50, 150), countedKey)",
+			201,
+		),
+	] {
+		KEY_CALLS.with(|c| c.set(0));
+		let v = s.evaluate_snippet("snip", code)?;
+		s.manifest(v)?;
+		ensure_eq!((code, KEY_CALLS.with(Cell::get)), (code, calls));
+	}
+	Ok(())
+}
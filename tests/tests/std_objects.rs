@@ -0,0 +1,141 @@
+use jrsonnet_evaluator::{error::Result, State};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+/// Previous `std.jsonnet` implementations, native functions should behave the same
+const REFERENCE: &str = r"
+{
+	mapWithKey(func, obj)::
+		if !std.isFunction(func) then
+			error ('std.mapWithKey first param must be function, got ' + std.type(func))
+		else if !std.isObject(obj) then
+			error ('std.mapWithKey second param must be object, got ' + std.type(obj))
+		else
+			{ [k]: func(k, obj[k]) for k in std.objectFields(obj) },
+	flattenArrays(arrs)::
+		std.foldl(function(a, b) a + b, arrs, []),
+	manifestYamlStream(value, indent_array_in_object=false, c_document_end=true)::
+		'---\n' + std.join(
+			'\n---\n', [std.manifestYamlDoc(e, indent_array_in_object) for e in value]
+		) + if c_document_end then '\n...\n' else '\n',
+	mergePatch(target, patch)::
+		if std.isObject(patch) then
+			local target_object =
+				if std.isObject(target) then target else {};
+			local target_fields =
+				if std.isObject(target_object) then self.objectFields(target_object) else [];
+			local null_fields = [k for k in self.objectFields(patch) if patch[k] == null];
+			local both_fields = std.setUnion(target_fields, self.objectFields(patch));
+			{
+				[k]:
+					if !std.objectHas(patch, k) then
+						target_object[k]
+					else if !std.objectHas(target_object, k) then
+						$.mergePatch(null, patch[k]) tailstrict
+					else
+						$.mergePatch(target_object[k], patch[k]) tailstrict
+				for k in std.setDiff(both_fields, null_fields)
+			}
+		else
+			patch,
+	prune(a)::
+		local isContent(b) =
+			if b == null then false
+			else if std.isArray(b) then std.length(b) > 0
+			else if std.isObject(b) then std.length(b) > 0
+			else true;
+		if std.isArray(a) then
+			[$.prune(x) for x in a if isContent($.prune(x))]
+		else if std.isObject(a) then {
+			[x]: $.prune(a[x])
+			for x in std.objectFields(a)
+			if isContent($.prune(a[x]))
+		} else
+			a,
+	get(o, f, default=null, inc_hidden=true)::
+		if std.objectHasEx(o, f, inc_hidden) then o[f] else default,
+	objectFields(o)::
+		std.objectFieldsEx(o, false),
+	objectValues(o)::
+		[o[k] for k in self.objectFields(o)],
+}
+";
+
+fn check(s: &State, call: &str) -> Result<()> {
+	// Only the error message is compared, stack traces are different
+	let run = |lib: &str| -> String {
+		let code = format!("local lib = {lib}; {call}");
+		match s.evaluate_snippet("snip", code).and_then(|v| s.manifest(v)) {
+			Ok(v) => v.to_string(),
+			Err(e) => s.stringify_err(&e).lines().next().unwrap_or("").to_owned(),
+		}
+	};
+	let native = run("std");
+	let reference = run(REFERENCE);
+	ensure_eq!((call, native), (call, reference));
+	Ok(())
+}
+
+#[test]
+fn same_as_reference() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	let objects = [
+		"{}",
+		"{a: 1, b: 2}",
+		"{a: 1, b:: 2, c: {d: null, e: [], f: {}}}",
+		"{a: null, c: {d: 3, e: null, g: [null, {}, [[]], 1]}, h: 'x'}",
+		"{a: {b: {c: 1}}, d+: [1], e: error 'unused'}",
+		"[1, {}]",
+		"null",
+		"'str'",
+	];
+	for a in objects {
+		check(&s, &format!("lib.prune({a})"))?;
+		for b in objects {
+			check(&s, &format!("lib.mergePatch({a}, {b})"))?;
+			check(
+				&s,
+				&format!("std.objectFieldsAll(lib.mergePatch({a}, {b}))"),
+			)?;
+		}
+		if a.starts_with('{') {
+			check(&s, &format!("lib.objectFields({a})"))?;
+			check(&s, &format!("lib.objectValues({a})"))?;
+			check(
+				&s,
+				&format!("std.objectFields(lib.mapWithKey(function(k, v) k, {a}))"),
+			)?;
+			check(&s, &format!("lib.mapWithKey(function(k, v) [k, v], {a}).a"))?;
+			for field in ["'a'", "'b'", "'z'"] {
+				check(&s, &format!("lib.get({a}, {field})"))?;
+				check(&s, &format!("lib.get({a}, {field}, 'default')"))?;
+				check(&s, &format!("lib.get({a}, {field}, inc_hidden=false)"))?;
+			}
+		}
+	}
+
+	for call in [
+		"lib.objectValues({a: 1, b:: 2, c: [3]})",
+		"std.length(lib.objectValues({a: 1, b: error 'eager'}))",
+		"std.length(lib.objectValues({a: 1, b:: error 'hidden'}))",
+		"lib.mapWithKey(1, {})",
+		"lib.mapWithKey(function(k, v) v, [])",
+		"lib.get({}, 'a', error 'unused') == null",
+		"lib.get({a: 1}, 'a', error 'unused')",
+		"lib.mergePatch(error 'unused', 1)",
+		"lib.mergePatch({a: error 'lazy', b: 1}, {b: null, c: {d: 1}})",
+		"lib.flattenArrays([])",
+		"lib.flattenArrays([[1, 2], [], [[3]], std.range(4, 5)])",
+		"lib.flattenArrays([[error 'lazy'], [1]])[1]",
+		"lib.manifestYamlStream([])",
+		"lib.manifestYamlStream([{a: [1, 2]}, 'b', [{c: null}]])",
+		"lib.manifestYamlStream([{a: [1, 2]}, 3], true)",
+		"lib.manifestYamlStream([{a: [1, 2]}, 3], c_document_end=false)",
+	] {
+		check(&s, call)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,55 @@
+use jrsonnet_evaluator::{error::Result, State, Val};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+#[test]
+fn long_concatenation_chain() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	// Deep chain of lazy concatenations should be flattened and dropped without recursion
+	let v = s.evaluate_snippet(
+		"snip",
+		"std.foldl(function(acc, i) acc + 'ab', std.range(1, 200000), '')",
+	)?;
+	let Val::Str(str) = &v else {
+		unreachable!("string expected")
+	};
+	ensure_eq!(str.len(), 400_000);
+	ensure_eq!(&str.clone().into_flat()[..6], "ababab");
+	drop(v);
+
+	let v = s.evaluate_snippet(
+		"snip",
+		"local s = std.foldr(function(i, acc) i + acc, std.range(0, 9), ''); [s, s[3], std.length(s)]",
+	)?;
+	ensure_val_eq!(s, v, s.evaluate_snippet("snip", "['0123456789', '3', 10]")?);
+	Ok(())
+}
+
+#[test]
+fn concatenated_strings_are_values() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+
+	for code in [
+		"'a' + 'b' == 'ab'",
+		"'a' + ('b' + 'c') == ('a' + 'b') + 'c'",
+		"'ab' + 'c' < 'ab' + 'd'",
+		"std.objectHas({ab: 1}, 'a' + 'b')",
+		"{['a' + 'b']: 1}.ab == 1",
+		"'x' + 1 + null + true == 'x1nulltrue'",
+		"std.set(['b' + 'a', 'a' + 'b', 'ab']) == ['ab', 'ba']",
+		"std.type('a' + 'b') == 'string'",
+		"('a' + 'b') * 2 == 'abab'",
+		"'%s-%s' % ['a' + 'b', 'c'] == 'ab-c'",
+	] {
+		let v = s.evaluate_snippet("snip", code)?;
+		ensure_eq!(
+			(code, s.manifest(v)?.to_string()),
+			(code, "true".to_owned())
+		);
+	}
+	Ok(())
+}
@@ -0,0 +1,49 @@
+use jrsonnet_evaluator::{error::Result, State, Val};
+use jrsonnet_stdlib::StateExt;
+
+mod common;
+
+#[test]
+fn tail_recursion_runs_in_constant_stack() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_max_stack(20);
+
+	let v = s.evaluate_snippet(
+		"snip".to_owned(),
+		r#"
+			local sum(n, acc) =
+				if n == 0 then acc
+				else local next = n - 1; sum(next, acc + n) tailstrict;
+			sum(10000, 0)
+		"#,
+	)?;
+	ensure_val_eq!(s, v, Val::Num(50_005_000.0));
+
+	let v = s.evaluate_snippet(
+		"snip".to_owned(),
+		r#"
+			local even(n) = if n == 0 then true else odd(n - 1) tailstrict,
+				odd(n) = if n == 0 then false else even(n - 1) tailstrict;
+			even(5001)
+		"#,
+	)?;
+	ensure_val_eq!(s, v, Val::Bool(false));
+	Ok(())
+}
+
+#[test]
+fn non_tail_recursion_overflows() -> Result<()> {
+	let s = State::default();
+	s.with_stdlib();
+	s.set_max_stack(20);
+
+	let e = s
+		.evaluate_snippet(
+			"snip".to_owned(),
+			"local sum(n) = if n == 0 then 0 else n + sum(n - 1) tailstrict; sum(100)",
+		)
+		.unwrap_err();
+	ensure!(s.stringify_err(&e).starts_with("stack overflow"));
+	Ok(())
+}